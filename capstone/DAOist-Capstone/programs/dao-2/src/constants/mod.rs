@@ -1,7 +1,2 @@
 pub const PUBKEY_L: usize = 32;
-pub const ENUM_L: usize = 1;
 pub const U64_L: usize = 8;
-pub const U16_L: usize = 2;
-pub const BOOL_L: usize = 1;
-pub const OPTION_L: usize = 1;
-pub const U8_L: usize = 1;
\ No newline at end of file
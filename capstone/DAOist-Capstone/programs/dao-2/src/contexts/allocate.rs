@@ -0,0 +1,94 @@
+use anchor_lang::prelude::*;
+
+use crate::{state::{config::DaoConfig, Allocation, ConvictionProposal, StakeState, Subsystem}, errors::DaoError};
+
+#[derive(Accounts)]
+pub struct Allocate<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+    #[account(
+        mut,
+        seeds=[b"stake", config.key().as_ref(), owner.key().as_ref()],
+        bump = stake_state.state_bump
+    )]
+    stake_state: Account<'info, StakeState>,
+    #[account(
+        mut,
+        seeds=[b"conviction", config.key().as_ref(), proposal.proposer.as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    proposal: Account<'info, ConvictionProposal>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        seeds=[b"allocation", proposal.key().as_ref(), owner.key().as_ref()],
+        bump,
+        space = Allocation::LEN
+    )]
+    allocation: Account<'info, Allocation>,
+    #[account(
+        seeds=[b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    config: Account<'info, DaoConfig>,
+    system_program: Program<'info, System>
+}
+
+impl<'info> Allocate<'info> {
+    pub fn allocate(
+        &mut self,
+        amount: u64,
+        bump: u8
+    ) -> Result<()> {
+        self.config.check_not_paused(Subsystem::Voting)?;
+        // An allocation account keeps the stake locked like a vote account
+        if self.allocation.init_if_needed(self.owner.key(), self.proposal.key(), bump)? {
+            self.stake_state.add_account()?;
+        }
+        self.stake_state.allocate(amount)?;
+        self.allocation.amount = self.allocation.amount.checked_add(amount).ok_or(DaoError::Overflow)?;
+        self.proposal.allocate(amount, self.config.conviction_rule.half_life)
+    }
+}
+
+#[derive(Accounts)]
+pub struct Deallocate<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+    #[account(
+        mut,
+        seeds=[b"stake", config.key().as_ref(), owner.key().as_ref()],
+        bump = stake_state.state_bump
+    )]
+    stake_state: Account<'info, StakeState>,
+    #[account(
+        mut,
+        seeds=[b"conviction", config.key().as_ref(), proposal.proposer.as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    proposal: Account<'info, ConvictionProposal>,
+    #[account(
+        mut,
+        close = owner,
+        seeds=[b"allocation", proposal.key().as_ref(), owner.key().as_ref()],
+        bump = allocation.bump
+    )]
+    allocation: Account<'info, Allocation>,
+    #[account(
+        seeds=[b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    config: Account<'info, DaoConfig>
+}
+
+impl<'info> Deallocate<'info> {
+    // Take the whole allocation back, also once the proposal has been funded
+    pub fn deallocate(
+        &mut self
+    ) -> Result<()> {
+        let amount = self.allocation.amount;
+        self.proposal.deallocate(amount, self.config.conviction_rule.half_life)?;
+        self.stake_state.deallocate(amount)?;
+        self.stake_state.remove_account()
+    }
+}
@@ -1,6 +1,6 @@
-use anchor_lang::{prelude::*, system_program::{Transfer, transfer}};
+use anchor_lang::{prelude::*, system_program::{Transfer, transfer}, solana_program::{instruction::Instruction, program::invoke_signed}};
 
-use crate::{state::{config::DaoConfig, Proposal, ProposalType}, errors::DaoError};
+use crate::{state::{config::DaoConfig, Proposal, ProposalStatus, ProposalType, Reputation, StakeState, Subsystem, InstructionData}, errors::DaoError, helpers::validate_treasury};
 
 #[derive(Accounts)]
 #[instruction(selected_treasury: String)]
@@ -8,6 +8,7 @@ pub struct CleanupProposal<'info> {
     #[account(mut)]
     initializer: Signer<'info>,
     #[account(mut)]
+    ///CHECK: This is safe. It's checked against the bounty payee
     payee: UncheckedAccount<'info>,
     #[account(
         mut,
@@ -17,11 +18,34 @@ pub struct CleanupProposal<'info> {
     )]
     proposal: Account<'info, Proposal>,
     #[account(
-        seeds=[&selected_treasury.as_ref(), config.key().as_ref()],
+        mut,
+        seeds=[b"stake", config.key().as_ref(), proposal.proposer.as_ref()],
+        bump = proposer_stake.state_bump
+    )]
+    proposer_stake: Account<'info, StakeState>,
+    // Written back before the proposer's, which wins if they're the same account
+    #[account(
+        mut,
+        seeds=[b"reputation", config.key().as_ref(), payee.key().as_ref()],
+        bump = payee_reputation.bump
+    )]
+    payee_reputation: Option<Account<'info, Reputation>>,
+    #[account(
+        init_if_needed,
+        payer = initializer,
+        seeds=[b"reputation", config.key().as_ref(), proposal.proposer.as_ref()],
+        bump,
+        space = Reputation::LEN
+    )]
+    proposer_reputation: Account<'info, Reputation>,
+    #[account(
+        mut,
+        seeds=[selected_treasury.as_bytes(), config.key().as_ref()],
         bump
     )]
     treasury: SystemAccount<'info>,
     #[account(
+        mut,
         seeds=[b"config", config.seed.to_le_bytes().as_ref()],
         bump = config.config_bump
     )]
@@ -31,30 +55,92 @@ pub struct CleanupProposal<'info> {
 
 impl<'info> CleanupProposal<'info> {
     pub fn cleanup_proposal(
-        &mut self
+        &mut self,
+        treasury: String,
+        reputation_bump: u8
     ) -> Result<()> {
+        self.check_treasury(treasury)?;
         // Try finalize
-        self.proposal.try_finalize();
-        self.proposal.is_failed()?;
-        Ok(())
+        self.proposal.try_finalize()?;
+        // Consumed proposals have done their job and can be closed too
+        match self.proposal.result {
+            ProposalStatus::Consumed => self.reward_proposer(reputation_bump)?,
+            _ => self.proposal.is_failed()?
+        }
+        // Its turnout moves the quorum of later proposals
+        self.config.record_turnout(&self.proposal)?;
+        // The proposal is closed, so it no longer counts toward the proposer's limit
+        self.proposer_stake.remove_proposal()
     }
 
     pub fn execute_proposal(
-        &mut self
+        &mut self,
+        treasury: String,
+        treasury_bump: u8,
+        reputation_bump: u8
     ) -> Result<()> {
+        self.check_treasury(treasury.clone())?;
+        self.config.check_proposal_not_paused(&self.proposal.proposal, Subsystem::Execution)?;
         // Try finalize proposal
-        self.proposal.try_finalize();
+        self.proposal.try_finalize()?;
         // Check if the status is successful
         self.proposal.is_succeeded()?;
+        // Give members who voted against it time to rage quit
+        self.proposal.check_execution_delay(self.config.execution_delay)?;
+        self.config.record_turnout(&self.proposal)?;
+        self.proposer_stake.remove_proposal()?;
+        self.reward_proposer(reputation_bump)?;
         match self.proposal.proposal {
-            ProposalType::Bounty(payee, payout) => self.payout_bounty(payee, payout),
-            ProposalType::Executable => self.execute_tx(),
+            ProposalType::Bounty(payee, payout) => {
+                self.payout_bounty(payee, payout, &treasury, treasury_bump)?;
+                self.reward_payee(payee)
+            },
+            ProposalType::Executable => err!(DaoError::ExecutableNotSupported),
             ProposalType::Vote => self.finalize_vote(),
+            // Only the consumer can act on it, through `consume_proposal`
+            ProposalType::Consumable(_) => err!(DaoError::InvalidConsumer),
+            ProposalType::Unpause => self.config.unpause(),
+            // These have their own instructions, which need the accounts they act on
+            ProposalType::SubDao(_, _) | ProposalType::ClawBack(_, _) | ProposalType::Dissolve(_) => err!(DaoError::InvalidProposalType),
+            ProposalType::Payroll(_) | ProposalType::TerminatePayroll(_) => err!(DaoError::InvalidProposalType),
+            ProposalType::GrantRole(_, _, _) | ProposalType::RevokeRole(_, _) => err!(DaoError::InvalidProposalType),
+            ProposalType::Swap(_) => err!(DaoError::InvalidProposalType),
+        }
+    }
+
+    // Make sure the selected treasury is the one the proposal was created against
+    pub fn check_treasury(
+        &self,
+        treasury: String
+    ) -> Result<()> {
+        require!(validate_treasury(treasury)? == self.proposal.treasury, DaoError::InvalidTreasury);
+        Ok(())
+    }
+
+    // The proposer earns reputation once their proposal has passed and been acted on
+    pub fn reward_proposer(
+        &mut self,
+        bump: u8
+    ) -> Result<()> {
+        let rule = self.config.reputation_rule;
+        self.proposer_reputation.init_if_needed(self.proposal.proposer, bump)?;
+        self.proposer_reputation.add(rule.pass_points, rule.half_life)
+    }
+
+    // Payees who have a reputation account earn it for a completed bounty, unless they proposed it themselves
+    pub fn reward_payee(
+        &mut self,
+        payee: Pubkey
+    ) -> Result<()> {
+        let rule = self.config.reputation_rule;
+        match self.payee_reputation.as_mut() {
+            Some(reputation) if payee != self.proposal.proposer => reputation.add(rule.bounty_points, rule.half_life),
+            _ => Ok(())
         }
     }
 
     pub fn finalize_vote(&self) -> Result<()> {
-        msg!("Vote result: {} / {} / {}", self.proposal.votes, self.proposal.quorum, self.proposal.threshold);
+        msg!("Vote result: {:?} of {} staked, tallied with {:?}", self.proposal.vote_counts, self.proposal.staked_supply, self.proposal.tally);
         msg!("Vote has {:?}", self.proposal.result);
         Ok(())
     }
@@ -62,7 +148,9 @@ impl<'info> CleanupProposal<'info> {
     pub fn payout_bounty(
         &self,
         payee: Pubkey,
-        payout: u64
+        payout: u64,
+        treasury: &String,
+        treasury_bump: u8
     ) -> Result<()> {
         require_keys_eq!(self.payee.key(), payee);
 
@@ -72,9 +160,9 @@ impl<'info> CleanupProposal<'info> {
         };
 
         let seeds = &[
-            &b"auth"[..],
+            treasury.as_bytes(),
             &self.config.key().to_bytes()[..],
-            &[self.config.auth_bump],
+            &[treasury_bump],
         ];
 
         let signer_seeds = &[&seeds[..]];
@@ -92,7 +180,7 @@ impl<'info> CleanupProposal<'info> {
     pub fn execute_tx(
         &self,
         ixs: &Vec<InstructionData>,
-        remaining_accounts: &[AccountInfo],
+        remaining_accounts: &[AccountInfo<'info>],
         treasury: &String
     ) -> Result<()> {
         let remaining_accounts = &mut remaining_accounts.iter();
@@ -102,7 +190,7 @@ impl<'info> CleanupProposal<'info> {
             let program_id = next_account_info(remaining_accounts)?;
             require_keys_eq!(program_id.key(), data.program_id, DaoError::InvalidInstructionData);
 
-            let mut final_ix: Instruction = Instruction::from(data.clone());
+            let final_ix: Instruction = Instruction::from(data.clone());
             let mut ix_accounts = vec![program_id.clone()];
 
             for key in &data.keys {
@@ -122,7 +210,7 @@ impl<'info> CleanupProposal<'info> {
                 config_key.as_ref()
             ];
 
-            let (_key, bump) = Pubkey::find_program_address(&authority_seeds, &ID);
+            let (_key, bump) = Pubkey::find_program_address(&authority_seeds, &crate::ID);
 
             let authority_seeds = [
                 treasury.as_ref(),
@@ -131,12 +219,12 @@ impl<'info> CleanupProposal<'info> {
             ];
 
             invoke_signed(
-                &final_ix, 
-                &ix_accounts, 
+                &final_ix,
+                &ix_accounts,
                 &[&authority_seeds]
             )?;
         }
 
         Ok(())
     }
-}
\ No newline at end of file
+}
@@ -1,5 +1,5 @@
 use anchor_lang::prelude::*;
-use anchor_spl::{token::{Token, TokenAccount, Mint, CloseAccount, close_account}, associated_token::AssociatedToken};
+use anchor_spl::{token_interface::{TokenInterface, TokenAccount, CloseAccount, close_account}, associated_token::AssociatedToken};
 
 use crate::{state::{config::DaoConfig, StakeState}, errors::DaoError};
 
@@ -7,25 +7,21 @@ use crate::{state::{config::DaoConfig, StakeState}, errors::DaoError};
 pub struct CleanupStake<'info> {
     #[account(mut)]
     owner: Signer<'info>,
+    // Not tied to the governance mint, it may predate the switch to the membership mint
     #[account(
         mut,
         seeds = [b"vault", config.key().as_ref(), owner.key().as_ref()],
         bump = stake_state.vault_bump,
-        token::mint = mint,
-        token::authority = stake_auth
+        token::authority = stake_auth,
+        token::token_program = token_program
     )]
-    stake_ata: Account<'info, TokenAccount>,
+    stake_ata: InterfaceAccount<'info, TokenAccount>,
     #[account(
         seeds=[b"auth", config.key().as_ref(), owner.key().as_ref()],
         bump = stake_state.auth_bump
     )]
     ///CHECK: This is safe. It's just used to sign things
     stake_auth: UncheckedAccount<'info>,
-    #[account(
-        seeds=[b"mint", config.key().as_ref()],
-        bump = config.mint_bump
-    )]
-    mint: Account<'info, Mint>,
     #[account(
         mut,
         close = owner,
@@ -38,7 +34,7 @@ pub struct CleanupStake<'info> {
         bump = config.config_bump
     )]
     config: Account<'info, DaoConfig>,
-    token_program: Program<'info, Token>,
+    token_program: Interface<'info, TokenInterface>,
     associated_token_program: Program<'info, AssociatedToken>,
     system_program: Program<'info, System>
 }
@@ -47,6 +43,7 @@ impl<'info> CleanupStake<'info> {
     pub fn cleanup_stake(
         &mut self
     ) -> Result<()> {
+        self.stake_state.check_proposals()?;
         self.close_stake_ata()?;
         match self.stake_state.check_stake() {
             Ok(_) => err!(DaoError::InvalidStakeAmount),
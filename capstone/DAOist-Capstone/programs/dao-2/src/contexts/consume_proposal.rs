@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+
+use crate::{state::{config::DaoConfig, Proposal, Subsystem}, interface::check_version};
+
+#[derive(Accounts)]
+pub struct ConsumeProposal<'info> {
+    // The consumer named in the proposal, usually a PDA of the calling program
+    authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds=[b"proposal", config.key().as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    proposal: Account<'info, Proposal>,
+    #[account(
+        seeds=[b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    config: Account<'info, DaoConfig>
+}
+
+impl<'info> ConsumeProposal<'info> {
+    pub fn consume_proposal(
+        &mut self,
+        version: u8
+    ) -> Result<()> {
+        check_version(version)?;
+        self.config.check_not_paused(Subsystem::Execution)?;
+        // Try finalize proposal
+        self.proposal.try_finalize()?;
+        // It has to have passed, including its execution delay
+        self.proposal.is_succeeded()?;
+        self.proposal.check_execution_delay(self.config.execution_delay)?;
+        self.proposal.consume(self.authority.key())
+    }
+}
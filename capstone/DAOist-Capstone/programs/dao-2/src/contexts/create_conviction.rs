@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{config::DaoConfig, ConvictionProposal, StakeState, Subsystem};
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct CreateConviction<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+    #[account(
+        seeds=[b"stake", config.key().as_ref(), owner.key().as_ref()],
+        bump = stake_state.state_bump
+    )]
+    stake_state: Account<'info, StakeState>,
+    #[account(
+        init,
+        payer = owner,
+        seeds=[b"conviction", config.key().as_ref(), owner.key().as_ref(), id.to_le_bytes().as_ref()],
+        bump,
+        space = ConvictionProposal::LEN
+    )]
+    proposal: Account<'info, ConvictionProposal>,
+    ///CHECK: This is safe. It's only recorded as the payee
+    payee: UncheckedAccount<'info>,
+    #[account(
+        seeds=[b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    config: Account<'info, DaoConfig>,
+    system_program: Program<'info, System>
+}
+
+impl<'info> CreateConviction<'info> {
+    pub fn create_conviction(
+        &mut self,
+        id: u64,
+        amount: u64,
+        bump: u8
+    ) -> Result<()> {
+        self.config.check_not_paused(Subsystem::Proposals)?;
+        self.config.conviction_rule.check_enabled()?;
+        // Make sure user has staked
+        self.stake_state.check_stake_amount(self.config.min_stake)?;
+        self.proposal.init(id, self.owner.key(), self.payee.key(), amount, bump)
+    }
+}
@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+
+use crate::{state::{config::DaoConfig, Payroll, PayrollEntry}, helpers::validate_treasury};
+
+#[derive(Accounts)]
+#[instruction(id: u64, selected_treasury: String, period: u64, payments: u64, entries: Vec<PayrollEntry>)]
+pub struct CreatePayroll<'info> {
+    #[account(mut)]
+    creator: Signer<'info>,
+    #[account(
+        init,
+        payer = creator,
+        seeds=[b"payroll", config.key().as_ref(), creator.key().as_ref(), id.to_le_bytes().as_ref()],
+        bump,
+        space = Payroll::space(entries.len())
+    )]
+    payroll: Account<'info, Payroll>,
+    #[account(
+        seeds=[b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    config: Account<'info, DaoConfig>,
+    system_program: Program<'info, System>
+}
+
+impl<'info> CreatePayroll<'info> {
+    pub fn create_payroll(
+        &mut self,
+        id: u64,
+        treasury: String,
+        period: u64,
+        payments: u64,
+        entries: Vec<PayrollEntry>,
+        replaces: Option<Pubkey>,
+        bump: u8
+    ) -> Result<()> {
+        // Nothing is paid until a `Payroll` proposal activates it
+        self.payroll.init(
+            id,
+            self.creator.key(),
+            validate_treasury(treasury)?,
+            period,
+            payments,
+            entries,
+            replaces,
+            bump
+        )
+    }
+}
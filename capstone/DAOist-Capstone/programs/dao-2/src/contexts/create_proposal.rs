@@ -1,9 +1,13 @@
 use anchor_lang::{prelude::*, system_program::{Transfer, transfer}};
+use anchor_spl::{
+    token::{Mint, Token, TokenAccount, TransferChecked, transfer_checked},
+    associated_token::AssociatedToken
+};
 
-use crate::{state::{config::DaoConfig, Proposal, StakeState, ProposalType, VoteType}, errors::DaoError};
+use crate::{state::{config::DaoConfig, MemberRoles, Proposal, Reputation, StakeState, ProposalType, Subsystem}, errors::DaoError};
 
 #[derive(Accounts)]
-#[instruction(id: u64, selected_treasury: String)]
+#[instruction(id: u64, selected_treasury: String, name: String, gist: String, proposal: ProposalType, expiry: u64, choices: u8)]
 pub struct CreateProposal<'info> {
     #[account(mut)]
     owner: Signer<'info>,
@@ -18,19 +22,52 @@ pub struct CreateProposal<'info> {
         payer = owner,
         seeds=[b"proposal", config.key().as_ref(), id.to_le_bytes().as_ref()],
         bump,
-        space = Proposal::LEN
+        space = Proposal::space(choices)
     )]
     proposal: Account<'info, Proposal>,
     #[account(
-        seeds=[&selected_treasury.as_ref(), config.key().as_ref()],
+        init_if_needed,
+        payer = owner,
+        seeds=[b"reputation", config.key().as_ref(), owner.key().as_ref()],
+        bump,
+        space = Reputation::LEN
+    )]
+    reputation: Account<'info, Reputation>,
+    // Only needed when the policy requires the proposer role
+    #[account(
+        seeds=[b"roles", config.key().as_ref(), owner.key().as_ref()],
+        bump = roles.bump
+    )]
+    roles: Option<Account<'info, MemberRoles>>,
+    #[account(
+        mut,
+        seeds=[selected_treasury.as_bytes(), config.key().as_ref()],
         bump
     )]
     treasury: SystemAccount<'info>,
     #[account(
+        mut,
         seeds=[b"config", config.seed.to_le_bytes().as_ref()],
         bump = config.config_bump
     )]
     config: Account<'info, DaoConfig>,
+    // Only needed when the DAO takes payments in a token
+    payment_mint: Option<Box<Account<'info, Mint>>>,
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = owner
+    )]
+    owner_payment: Option<Box<Account<'info, TokenAccount>>>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = payment_mint,
+        associated_token::authority = treasury
+    )]
+    treasury_payment: Option<Box<Account<'info, TokenAccount>>>,
+    token_program: Option<Program<'info, Token>>,
+    associated_token_program: Option<Program<'info, AssociatedToken>>,
     system_program: Program<'info, System>
 }
 
@@ -39,38 +76,49 @@ impl<'info> CreateProposal<'info> {
     pub fn create_proposal(
         &mut self,
         id: u64,
+        treasury: String,
         name: String,
         gist: String,
         proposal: ProposalType,
-        quorum: u64,
-        threshold: u64,
         expiry: u64,
         choices:u8,
-        bump: u8
+        optimistic: bool,
+        bump: u8,
+        reputation_bump: u8
     ) -> Result<()> {
         // Lógica para lidar com outros tipos de propostas
         // freeze the nfts instead of stake
-        // check nr of ids with proposal status open if bigger than certain number. quadratic fees.(future)
+        self.config.check_proposal_not_paused(&proposal, Subsystem::Proposals)?;
+        // Proposals have nowhere to keep instructions yet, so an executable one could never be executed
+        require!(proposal != ProposalType::Executable, DaoError::ExecutableNotSupported);
+        // Make sure user may propose
+        self.config.role_policy.check_can_propose(self.owner.key(), &proposal, self.roles.as_deref())?;
         // Make sure user has staked
         self.config.check_min_stake(self.stake_state.amount)?;
+        // Make sure user has earned enough reputation
+        self.reputation.init_if_needed(self.owner.key(), reputation_bump)?;
+        self.reputation.decay(self.config.reputation_rule.half_life)?;
+        self.config.reputation_rule.check_can_propose(self.reputation.score)?;
+        // Count it toward the member's open proposals
+        self.stake_state.add_proposal(self.config.max_open_proposals_per_member)?;
         // Check ID and add proposal
         self.config.add_proposal(id)?;
         // Check minimum choices
         self.config.check_min_choices(choices)?;
-        // Check minimum quorum
-        self.config.check_min_quorum(quorum)?;
-        // Check minimum threshold
-        self.config.check_min_threshold(threshold)?;
         // Check max expiry
         self.config.check_max_expiry(expiry)?;
+        // Check the proposal may skip the vote
+        let mode = self.config.proposal_mode(&proposal, treasury.clone(), optimistic)?;
         // Initialize the proposal
         self.proposal.init(
             id,
+            self.owner.key(),
+            treasury,
             name, // A proposal name
             gist, // 72 bytes (39 bytes + / + 32 byte ID)
             proposal,
-            quorum,
-            threshold,
+            self.config.tally_rule(&proposal),
+            mode,
             expiry,
             choices,
             bump
@@ -80,6 +128,12 @@ impl<'info> CreateProposal<'info> {
     pub fn pay_proposal_fee(
         &mut self
     ) -> Result<()> {
+        // Escalates with the proposals the member already has open
+        let fee = self.config.proposal_fee_for(self.stake_state.open_proposals)?;
+        if let Some(mint) = self.config.payment_mint {
+            return self.pay_proposal_fee_in_token(mint, fee);
+        }
+
         let accounts = Transfer {
             from: self.owner.to_account_info(),
             to: self.treasury.to_account_info()
@@ -90,6 +144,32 @@ impl<'info> CreateProposal<'info> {
             accounts
         );
 
-        transfer(ctx, self.config.proposal_fee)
+        transfer(ctx, fee)
+    }
+
+    pub fn pay_proposal_fee_in_token(
+        &mut self,
+        mint: Pubkey,
+        fee: u64
+    ) -> Result<()> {
+        let payment_mint = self.payment_mint.as_ref().ok_or(DaoError::InvalidPaymentMint)?;
+        require_keys_eq!(payment_mint.key(), mint, DaoError::InvalidPaymentMint);
+        let from = self.owner_payment.as_ref().ok_or(DaoError::InvalidPaymentMint)?;
+        let to = self.treasury_payment.as_ref().ok_or(DaoError::InvalidPaymentMint)?;
+        let token_program = self.token_program.as_ref().ok_or(DaoError::InvalidPaymentMint)?;
+
+        let accounts = TransferChecked {
+            from: from.to_account_info(),
+            mint: payment_mint.to_account_info(),
+            to: to.to_account_info(),
+            authority: self.owner.to_account_info()
+        };
+
+        let ctx = CpiContext::new(
+            token_program.to_account_info(),
+            accounts
+        );
+
+        transfer_checked(ctx, fee, payment_mint.decimals)
     }
 }
\ No newline at end of file
@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+
+use crate::{state::{config::DaoConfig, SwapOrder}, helpers::validate_treasury};
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct CreateSwap<'info> {
+    #[account(mut)]
+    creator: Signer<'info>,
+    #[account(
+        init,
+        payer = creator,
+        seeds=[b"swap", config.key().as_ref(), creator.key().as_ref(), id.to_le_bytes().as_ref()],
+        bump,
+        space = SwapOrder::LEN
+    )]
+    order: Account<'info, SwapOrder>,
+    #[account(owner = anchor_amm_2023::ID)]
+    ///CHECK: This is safe. The AMM checks it's a pool of the right mints when the swap is made
+    pool: UncheckedAccount<'info>,
+    mint_out: Account<'info, Mint>,
+    #[account(
+        seeds=[b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    config: Account<'info, DaoConfig>,
+    system_program: Program<'info, System>
+}
+
+impl<'info> CreateSwap<'info> {
+    pub fn create_swap(
+        &mut self,
+        id: u64,
+        treasury: String,
+        amount_in: u64,
+        min_out: u64,
+        expiration: i64,
+        bump: u8
+    ) -> Result<()> {
+        // Nothing is traded until a `Swap` proposal passes
+        self.order.init(
+            id,
+            self.creator.key(),
+            validate_treasury(treasury)?,
+            self.pool.key(),
+            self.mint_out.key(),
+            amount_in,
+            min_out,
+            expiration,
+            bump
+        )
+    }
+}
@@ -0,0 +1,64 @@
+use anchor_lang::{prelude::*, system_program::{Transfer, transfer}};
+
+use crate::{state::{config::DaoConfig, ConvictionProposal, Subsystem}, errors::DaoError, helpers::validate_treasury};
+
+#[derive(Accounts)]
+#[instruction(selected_treasury: String)]
+pub struct ExecuteConviction<'info> {
+    #[account(
+        mut,
+        seeds=[b"conviction", config.key().as_ref(), proposal.proposer.as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    proposal: Account<'info, ConvictionProposal>,
+    #[account(mut, address = proposal.payee)]
+    ///CHECK: This is safe. It's the payee recorded in the proposal
+    payee: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds=[selected_treasury.as_bytes(), config.key().as_ref()],
+        bump
+    )]
+    treasury: SystemAccount<'info>,
+    #[account(
+        seeds=[b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    config: Account<'info, DaoConfig>,
+    system_program: Program<'info, System>
+}
+
+impl<'info> ExecuteConviction<'info> {
+    // Anyone can crank a proposal once enough conviction has built up behind it
+    pub fn execute_conviction(
+        &mut self,
+        treasury: String,
+        treasury_bump: u8
+    ) -> Result<()> {
+        self.config.check_not_paused(Subsystem::Execution)?;
+        let rule = self.config.conviction_rule;
+        require!(validate_treasury(treasury.clone())? == rule.pool, DaoError::InvalidTreasury);
+        self.proposal.execute(&rule, self.treasury.lamports(), self.config.staked_supply)?;
+
+        let accounts = Transfer {
+            from: self.treasury.to_account_info(),
+            to: self.payee.to_account_info()
+        };
+
+        let seeds = &[
+            treasury.as_bytes(),
+            &self.config.key().to_bytes()[..],
+            &[treasury_bump],
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let ctx = CpiContext::new_with_signer(
+            self.system_program.to_account_info(),
+            accounts,
+            signer_seeds
+        );
+
+        transfer(ctx, self.proposal.amount)
+    }
+}
@@ -0,0 +1,194 @@
+use anchor_lang::{prelude::*, system_program::{Transfer, transfer}};
+use anchor_spl::{
+    token::{Mint, Token, TokenAccount, SyncNative, sync_native, CloseAccount, close_account, spl_token::native_mint},
+    associated_token::AssociatedToken
+};
+use anchor_amm_2023::{cpi::accounts::Swap, program::AnchorAmm2023};
+
+use crate::{state::{config::DaoConfig, Proposal, ProposalType, Subsystem, SwapOrder}, errors::DaoError, helpers::validate_treasury};
+
+#[derive(Accounts)]
+#[instruction(selected_treasury: String)]
+pub struct ExecuteSwap<'info> {
+    #[account(mut)]
+    initializer: Signer<'info>,
+    #[account(
+        mut,
+        seeds=[b"proposal", config.key().as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    proposal: Box<Account<'info, Proposal>>,
+    #[account(
+        mut,
+        seeds=[b"swap", config.key().as_ref(), order.creator.as_ref(), order.id.to_le_bytes().as_ref()],
+        bump = order.bump
+    )]
+    order: Box<Account<'info, SwapOrder>>,
+    #[account(
+        mut,
+        seeds=[selected_treasury.as_bytes(), config.key().as_ref()],
+        bump
+    )]
+    treasury: SystemAccount<'info>,
+    // Wrapped lamports the treasury sells, closed again after the swap
+    #[account(
+        init_if_needed,
+        payer = initializer,
+        associated_token::mint = native_mint,
+        associated_token::authority = treasury
+    )]
+    treasury_in: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = initializer,
+        associated_token::mint = mint_out,
+        associated_token::authority = treasury
+    )]
+    treasury_out: Box<Account<'info, TokenAccount>>,
+    #[account(address = native_mint::ID)]
+    native_mint: Box<Account<'info, Mint>>,
+    #[account(address = order.mint_out)]
+    mint_out: Box<Account<'info, Mint>>,
+    // The pool's mints, in its own order
+    mint_x: Box<Account<'info, Mint>>,
+    mint_y: Box<Account<'info, Mint>>,
+    #[account(mut)]
+    ///CHECK: This is safe. The AMM checks its own vaults
+    vault_x: UncheckedAccount<'info>,
+    #[account(mut)]
+    ///CHECK: This is safe. The AMM checks its own vaults
+    vault_y: UncheckedAccount<'info>,
+    ///CHECK: This is safe. The AMM checks its own authority
+    pool_auth: UncheckedAccount<'info>,
+    #[account(address = order.pool)]
+    ///CHECK: This is safe. It's the pool the order was voted on with
+    pool: UncheckedAccount<'info>,
+    #[account(
+        seeds=[b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    config: Box<Account<'info, DaoConfig>>,
+    amm_program: Program<'info, AnchorAmm2023>,
+    token_program: Program<'info, Token>,
+    associated_token_program: Program<'info, AssociatedToken>,
+    system_program: Program<'info, System>
+}
+
+impl<'info> ExecuteSwap<'info> {
+    pub fn execute_swap(
+        &mut self,
+        treasury: String,
+        treasury_bump: u8
+    ) -> Result<()> {
+        match self.proposal.proposal {
+            ProposalType::Swap(order) => require_keys_eq!(order, self.order.key(), DaoError::InvalidSwap),
+            _ => return err!(DaoError::InvalidProposalType)
+        }
+        // The proposal is voted on against the treasury that sells
+        require!(self.order.treasury == self.proposal.treasury, DaoError::InvalidTreasury);
+        require!(validate_treasury(treasury.clone())? == self.order.treasury, DaoError::InvalidTreasury);
+        self.config.check_not_paused(Subsystem::Execution)?;
+        self.proposal.carry_out(self.config.execution_delay)?;
+        self.order.execute()?;
+
+        let config_key = self.config.key();
+        let seeds = &[
+            treasury.as_bytes(),
+            config_key.as_ref(),
+            &[treasury_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        self.wrap(signer_seeds)?;
+        self.swap(signer_seeds)?;
+        self.unwrap(signer_seeds)
+    }
+
+    // Move the lamports to sell into the treasury's wrapped SOL account
+    fn wrap(
+        &self,
+        signer_seeds: &[&[&[u8]]]
+    ) -> Result<()> {
+        let accounts = Transfer {
+            from: self.treasury.to_account_info(),
+            to: self.treasury_in.to_account_info()
+        };
+
+        let ctx = CpiContext::new_with_signer(
+            self.system_program.to_account_info(),
+            accounts,
+            signer_seeds
+        );
+
+        transfer(ctx, self.order.amount_in)?;
+
+        let ctx = CpiContext::new(
+            self.token_program.to_account_info(),
+            SyncNative { account: self.treasury_in.to_account_info() }
+        );
+
+        sync_native(ctx)
+    }
+
+    fn swap(
+        &self,
+        signer_seeds: &[&[&[u8]]]
+    ) -> Result<()> {
+        // Sell whichever side of the pool is SOL for the other one
+        let is_x = self.mint_x.key() == native_mint::ID;
+        let (mint_in, mint_out) = match is_x {
+            true => (&self.mint_x, &self.mint_y),
+            false => (&self.mint_y, &self.mint_x)
+        };
+        require_keys_eq!(mint_in.key(), native_mint::ID, DaoError::InvalidSwap);
+        require_keys_eq!(mint_out.key(), self.order.mint_out, DaoError::InvalidSwap);
+
+        let (user_x, user_y) = match is_x {
+            true => (self.treasury_in.to_account_info(), self.treasury_out.to_account_info()),
+            false => (self.treasury_out.to_account_info(), self.treasury_in.to_account_info())
+        };
+
+        let accounts = Swap {
+            user: self.treasury.to_account_info(),
+            mint_x: self.mint_x.to_account_info(),
+            mint_y: self.mint_y.to_account_info(),
+            user_x,
+            user_y,
+            vault_x: self.vault_x.to_account_info(),
+            vault_y: self.vault_y.to_account_info(),
+            auth: self.pool_auth.to_account_info(),
+            config: self.pool.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+            associated_token_program: self.associated_token_program.to_account_info(),
+            system_program: self.system_program.to_account_info()
+        };
+
+        let ctx = CpiContext::new_with_signer(
+            self.amm_program.to_account_info(),
+            accounts,
+            signer_seeds
+        );
+
+        anchor_amm_2023::cpi::swap(ctx, is_x, self.order.amount_in, self.order.min_out, self.order.expiration)
+    }
+
+    // Whatever wasn't sold goes back to the treasury as lamports
+    fn unwrap(
+        &self,
+        signer_seeds: &[&[&[u8]]]
+    ) -> Result<()> {
+        let accounts = CloseAccount {
+            account: self.treasury_in.to_account_info(),
+            destination: self.treasury.to_account_info(),
+            authority: self.treasury.to_account_info()
+        };
+
+        let ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            accounts,
+            signer_seeds
+        );
+
+        close_account(ctx)
+    }
+}
@@ -0,0 +1,71 @@
+use anchor_lang::prelude::*;
+
+use crate::{state::{config::DaoConfig, Payroll, Proposal, ProposalType, Subsystem}, errors::DaoError};
+
+#[derive(Accounts)]
+pub struct GovernPayroll<'info> {
+    initializer: Signer<'info>,
+    #[account(
+        mut,
+        seeds=[b"proposal", config.key().as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    proposal: Account<'info, Proposal>,
+    #[account(
+        mut,
+        seeds=[b"payroll", config.key().as_ref(), payroll.creator.as_ref(), payroll.id.to_le_bytes().as_ref()],
+        bump = payroll.bump
+    )]
+    payroll: Account<'info, Payroll>,
+    // The payroll being amended, when activating one that replaces it
+    #[account(
+        mut,
+        seeds=[b"payroll", config.key().as_ref(), replaced.creator.as_ref(), replaced.id.to_le_bytes().as_ref()],
+        bump = replaced.bump
+    )]
+    replaced: Option<Account<'info, Payroll>>,
+    #[account(
+        seeds=[b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    config: Account<'info, DaoConfig>
+}
+
+impl<'info> GovernPayroll<'info> {
+    pub fn activate_payroll(
+        &mut self
+    ) -> Result<()> {
+        match self.proposal.proposal {
+            ProposalType::Payroll(payroll) => require_keys_eq!(payroll, self.payroll.key(), DaoError::InvalidPayroll),
+            _ => return err!(DaoError::InvalidProposalType)
+        }
+        // The proposal is voted on against the treasury that pays
+        require!(self.payroll.treasury == self.proposal.treasury, DaoError::InvalidTreasury);
+        self.carry_out()?;
+        self.payroll.activate()?;
+        // An amendment takes over from the payroll it replaces
+        match (self.payroll.replaces, self.replaced.as_mut()) {
+            (Some(key), Some(replaced)) if key == replaced.key() => replaced.terminate(),
+            (None, None) => Ok(()),
+            _ => err!(DaoError::InvalidPayroll)
+        }
+    }
+
+    pub fn terminate_payroll(
+        &mut self
+    ) -> Result<()> {
+        match self.proposal.proposal {
+            ProposalType::TerminatePayroll(payroll) => require_keys_eq!(payroll, self.payroll.key(), DaoError::InvalidPayroll),
+            _ => return err!(DaoError::InvalidProposalType)
+        }
+        self.carry_out()?;
+        self.payroll.terminate()
+    }
+
+    pub fn carry_out(
+        &mut self
+    ) -> Result<()> {
+        self.config.check_not_paused(Subsystem::Execution)?;
+        self.proposal.carry_out(self.config.execution_delay)
+    }
+}
@@ -0,0 +1,68 @@
+use anchor_lang::prelude::*;
+
+use crate::{state::{config::DaoConfig, MemberRoles, Proposal, ProposalType, Subsystem}, errors::DaoError};
+
+#[derive(Accounts)]
+pub struct GovernRoles<'info> {
+    #[account(mut)]
+    initializer: Signer<'info>,
+    #[account(
+        mut,
+        seeds=[b"proposal", config.key().as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    proposal: Account<'info, Proposal>,
+    ///CHECK: This is safe. It's checked against the member in the proposal
+    member: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = initializer,
+        seeds=[b"roles", config.key().as_ref(), member.key().as_ref()],
+        bump,
+        space = MemberRoles::LEN
+    )]
+    roles: Account<'info, MemberRoles>,
+    #[account(
+        seeds=[b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    config: Account<'info, DaoConfig>,
+    system_program: Program<'info, System>
+}
+
+impl<'info> GovernRoles<'info> {
+    pub fn grant_role(
+        &mut self,
+        bump: u8
+    ) -> Result<()> {
+        let (member, role, allowance) = match self.proposal.proposal {
+            ProposalType::GrantRole(member, role, allowance) => (member, role, allowance),
+            _ => return err!(DaoError::InvalidProposalType)
+        };
+        self.carry_out(member, bump)?;
+        self.roles.grant(role, allowance)
+    }
+
+    pub fn revoke_role(
+        &mut self,
+        bump: u8
+    ) -> Result<()> {
+        let (member, role) = match self.proposal.proposal {
+            ProposalType::RevokeRole(member, role) => (member, role),
+            _ => return err!(DaoError::InvalidProposalType)
+        };
+        self.carry_out(member, bump)?;
+        self.roles.revoke(role)
+    }
+
+    pub fn carry_out(
+        &mut self,
+        member: Pubkey,
+        bump: u8
+    ) -> Result<()> {
+        require_keys_eq!(member, self.member.key(), DaoError::MissingRole);
+        self.config.check_not_paused(Subsystem::Execution)?;
+        self.proposal.carry_out(self.config.execution_delay)?;
+        self.roles.init_if_needed(member, bump)
+    }
+}
@@ -0,0 +1,108 @@
+use anchor_lang::{prelude::*, system_program::{Transfer, transfer}};
+
+use crate::{state::{config::DaoConfig, Proposal, ProposalType, Subsystem}, errors::DaoError, helpers::validate_treasury};
+
+#[derive(Accounts)]
+#[instruction(selected_treasury: String)]
+pub struct GovernSubDao<'info> {
+    initializer: Signer<'info>,
+    #[account(
+        mut,
+        seeds=[b"proposal", config.key().as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    proposal: Account<'info, Proposal>,
+    #[account(
+        mut,
+        seeds=[selected_treasury.as_bytes(), config.key().as_ref()],
+        bump
+    )]
+    treasury: SystemAccount<'info>,
+    #[account(
+        seeds=[b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    config: Box<Account<'info, DaoConfig>>,
+    #[account(
+        mut,
+        seeds=[b"treasury", sub_dao_config.key().as_ref()],
+        bump = sub_dao_config.main_treasury_bump
+    )]
+    sub_dao_treasury: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds=[b"config", sub_dao_config.seed.to_le_bytes().as_ref()],
+        bump = sub_dao_config.config_bump
+    )]
+    sub_dao_config: Box<Account<'info, DaoConfig>>,
+    system_program: Program<'info, System>
+}
+
+impl<'info> GovernSubDao<'info> {
+    pub fn claw_back(
+        &mut self,
+        treasury: String
+    ) -> Result<()> {
+        let amount = match self.proposal.proposal {
+            ProposalType::ClawBack(sub_dao, amount) => {
+                require_keys_eq!(sub_dao, self.sub_dao_config.key(), DaoError::NotSubDao);
+                amount
+            },
+            _ => return err!(DaoError::InvalidProposalType)
+        };
+        self.carry_out(treasury)?;
+        self.withdraw(amount)
+    }
+
+    pub fn dissolve_sub_dao(
+        &mut self,
+        treasury: String
+    ) -> Result<()> {
+        match self.proposal.proposal {
+            ProposalType::Dissolve(sub_dao) => require_keys_eq!(sub_dao, self.sub_dao_config.key(), DaoError::NotSubDao),
+            _ => return err!(DaoError::InvalidProposalType)
+        }
+        self.carry_out(treasury)?;
+        self.sub_dao_config.dissolve()?;
+        // Members keep their stake, the treasury goes back to the parent
+        self.withdraw(self.sub_dao_treasury.lamports())
+    }
+
+    // Checks shared by every parent proposal acting on a sub-DAO
+    pub fn carry_out(
+        &mut self,
+        treasury: String
+    ) -> Result<()> {
+        require!(validate_treasury(treasury)? == self.proposal.treasury, DaoError::InvalidTreasury);
+        self.config.check_not_paused(Subsystem::Execution)?;
+        self.sub_dao_config.check_parent(self.config.key())?;
+        self.proposal.carry_out(self.config.execution_delay)
+    }
+
+    // Move lamports from the sub-DAO's main treasury back into the parent's treasury
+    pub fn withdraw(
+        &self,
+        amount: u64
+    ) -> Result<()> {
+        let accounts = Transfer {
+            from: self.sub_dao_treasury.to_account_info(),
+            to: self.treasury.to_account_info()
+        };
+
+        let seeds = &[
+            &b"treasury"[..],
+            &self.sub_dao_config.key().to_bytes()[..],
+            &[self.sub_dao_config.main_treasury_bump],
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let ctx = CpiContext::new_with_signer(
+            self.system_program.to_account_info(),
+            accounts,
+            signer_seeds
+        );
+
+        transfer(ctx, amount)
+    }
+}
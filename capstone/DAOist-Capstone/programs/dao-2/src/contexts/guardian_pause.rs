@@ -0,0 +1,31 @@
+use anchor_lang::prelude::*;
+
+use crate::{state::{config::DaoConfig, MemberRoles, PauseFlags, Role}, errors::DaoError};
+
+#[derive(Accounts)]
+pub struct GuardianPause<'info> {
+    guardian: Signer<'info>,
+    #[account(
+        seeds=[b"roles", config.key().as_ref(), guardian.key().as_ref()],
+        bump = roles.bump
+    )]
+    roles: Account<'info, MemberRoles>,
+    #[account(
+        mut,
+        seeds=[b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    config: Account<'info, DaoConfig>
+}
+
+impl<'info> GuardianPause<'info> {
+    pub fn guardian_pause(
+        &mut self,
+        flags: PauseFlags
+    ) -> Result<()> {
+        require!(self.config.role_policy.guardian_pause, DaoError::MissingRole);
+        self.roles.check(Role::Guardian)?;
+        // Same limits as a council pause
+        self.config.pause(flags)
+    }
+}
@@ -0,0 +1,92 @@
+use anchor_lang::{prelude::*, system_program::{CreateAccount, create_account}, solana_program::program::invoke};
+use anchor_spl::token_2022::{Token2022, InitializeMint2, initialize_mint2, spl_token_2022::{self, extension::ExtensionType, instruction::initialize_non_transferable_mint}};
+
+use crate::state::{config::DaoConfig, MultiSig};
+
+#[derive(Accounts)]
+pub struct InitMembership<'info> {
+    #[account(mut)]
+    payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds=[b"membership", config.key().as_ref()],
+        bump
+    )]
+    ///CHECK: This is safe. It's created as a non-transferable mint in the ix
+    mint: UncheckedAccount<'info>,
+    #[account(
+        seeds=[b"auth", config.key().as_ref()],
+        bump = config.auth_bump
+    )]
+    ///CHECK: This is safe. It's just used to sign things
+    auth: UncheckedAccount<'info>,
+    #[account(
+        seeds=[b"multisig", config.key().as_ref()],
+        bump = multi_sig.multisig_bump
+    )]
+    multi_sig: Account<'info, MultiSig>,
+    #[account(
+        mut,
+        seeds=[b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    config: Account<'info, DaoConfig>,
+    token_program: Program<'info, Token2022>,
+    system_program: Program<'info, System>
+}
+
+impl<'info> InitMembership<'info> {
+    pub fn init_membership(
+        &mut self,
+        bump: u8,
+        signers: &[AccountInfo<'info>]
+    ) -> Result<()> {
+        // Multisig signers are passed in as remaining accounts
+        self.multi_sig.check_signers(signers)?;
+        // Stake from here on is in the membership mint
+        self.config.set_membership_mint(bump)?;
+
+        let space = ExtensionType::get_account_len::<spl_token_2022::state::Mint>(&[ExtensionType::NonTransferable]);
+
+        let accounts = CreateAccount {
+            from: self.payer.to_account_info(),
+            to: self.mint.to_account_info()
+        };
+
+        let config_key = self.config.key();
+
+        let seeds = &[
+            &b"membership"[..],
+            config_key.as_ref(),
+            &[bump],
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let ctx = CpiContext::new_with_signer(
+            self.system_program.to_account_info(),
+            accounts,
+            signer_seeds
+        );
+
+        create_account(ctx, Rent::get()?.minimum_balance(space), space as u64, &spl_token_2022::ID)?;
+
+        // Extensions have to be set up before the mint itself
+        invoke(
+            &initialize_non_transferable_mint(&spl_token_2022::ID, &self.mint.key())?,
+            &[self.mint.to_account_info()]
+        )?;
+
+        let accounts = InitializeMint2 {
+            mint: self.mint.to_account_info()
+        };
+
+        let ctx = CpiContext::new(
+            self.token_program.to_account_info(),
+            accounts
+        );
+
+        // The DAO mints membership and freezes it while staked
+        initialize_mint2(ctx, 0, &self.auth.key(), Some(&self.auth.key()))
+    }
+}
@@ -11,7 +11,7 @@ use mpl_token_metadata::{
 
 use solana_program::program::invoke_signed;
 
-use crate::{errors::DaoError, state::{DaoConfig, MultiSig}};
+use crate::{errors::DaoError, state::{ConvictionRule, DaoConfig, MultiSig, OptimisticRule, PricingMode, QuorumRule, ReputationRule, RolePolicy, TallyRule, Whitelist}};
 
 #[derive(Accounts)]
 #[instruction(seed: u64, multisig_keys: Vec<Pubkey>)]
@@ -64,7 +64,7 @@ pub struct Initialize<'info> {
     #[account(
         init,
         payer = initializer,
-        space = MultiSig::FIXED_LEN + multisig_keys.len() * 32,
+        space = MultiSig::space(multisig_keys.len()),
         seeds=[b"multisig", config.key().as_ref()],
         bump,
 
@@ -75,6 +75,16 @@ pub struct Initialize<'info> {
         bump,
     )]
     multisig_treasury: SystemAccount<'info>,
+    #[account(
+        init,
+        payer = initializer,
+        space = Whitelist::LEN,
+        seeds=[b"whitelist", config.key().as_ref()],
+        bump
+    )]
+    whitelist: Box<Account<'info, Whitelist>>,
+    // Leave it out to take payments in lamports
+    payment_mint: Option<Box<Account<'info, Mint>>>,
     mpl_program: Program<'info, Metadata>,
     token_program: Program<'info, Token>,
     associated_token_program: Program<'info, AssociatedToken>,
@@ -90,14 +100,25 @@ impl<'info> Initialize<'info> {
         min_signers: u8,
         bumps: &BTreeMap<String, u8>,
         issue_price: u64,
+        pricing: PricingMode,
         proposal_fee: u64,
+        escalate_proposal_fee: bool,
+        max_open_proposals_per_member: u64,
         max_supply: u64,
-        min_quorum: u64,
-        min_threshold: u64,
         max_expiry: u64,
         min_stake:u64,
         min_choices:u8,
         prevoting_period: u64,
+        execution_delay: u64,
+        bounty_rule: TallyRule,
+        executable_rule: TallyRule,
+        vote_rule: TallyRule,
+        optimistic_rule: OptimisticRule,
+        reputation_rule: ReputationRule,
+        role_policy: RolePolicy,
+        conviction_rule: ConvictionRule,
+        quorum_rule: QuorumRule,
+        max_pause_duration: u64,
         multisig_share: u8,
         dev_treasury_share: u8,
         ops_treasury_share: u8,
@@ -111,27 +132,21 @@ impl<'info> Initialize<'info> {
             mint_bump,
             main_treasury_bump,
             multisig_bump,
-            multisig_treasury_bump
+            multisig_treasury_bump,
+            whitelist_bump
         ) = (
             *bumps.get("auth").ok_or(DaoError::BumpError)?,
             *bumps.get("config").ok_or(DaoError::BumpError)?,
             *bumps.get("collection").ok_or(DaoError::BumpError)?,
             *bumps.get("main_treasury").ok_or(DaoError::BumpError)?,
             *bumps.get("multi_sig").ok_or(DaoError::BumpError)?,
-            *bumps.get("multisig_treasury").ok_or(DaoError::BumpError)?
+            *bumps.get("multisig_treasury").ok_or(DaoError::BumpError)?,
+            *bumps.get("whitelist").ok_or(DaoError::BumpError)?
 
         );
 
         // Validate Multisig Params
-        let keys_len = multisig_keys.len() as u8;
-        let mut keys = multisig_keys;
-        keys.sort(); 
-        keys.dedup();
-
-        require_gt!(keys_len, 0, DaoError::InvalidKeysLen);
-        require_gte!(u8::MAX, keys_len, DaoError::InvalidKeysLen);
-        require_gte!(keys_len, min_signers, DaoError::InvalidSigners);
-        require_gt!(min_signers, 0, DaoError::InvalidSigners);
+        let keys = MultiSig::validate_keys(multisig_keys, min_signers)?;
 
         // Validate the treasury division
         let total_share: u8 = 100;
@@ -224,18 +239,33 @@ impl<'info> Initialize<'info> {
         // Initialize Multisig
         self.multi_sig.init(min_signers, keys, multisig_bump, multisig_treasury_bump)?;
 
+        // Start with an empty rage quit whitelist
+        self.whitelist.init(whitelist_bump)?;
+
         // Initialize DAO Config
         self.config.init(
             seed,
             issue_price,
+            pricing,
             proposal_fee,
+            escalate_proposal_fee,
+            max_open_proposals_per_member,
             max_supply,
-            min_quorum,
-            min_threshold,
             max_expiry,
             min_stake,
             min_choices,
             prevoting_period,
+            execution_delay,
+            bounty_rule,
+            executable_rule,
+            vote_rule,
+            optimistic_rule,
+            reputation_rule,
+            role_policy,
+            conviction_rule,
+            quorum_rule,
+            self.payment_mint.as_ref().map(|mint| mint.key()),
+            max_pause_duration,
             auth_bump,
             config_bump,
             mint_bump,
@@ -1,7 +1,7 @@
 use std::collections::BTreeMap;
 
 use anchor_lang::prelude::*;
-use anchor_spl::{token::{Token, TokenAccount, Mint}, associated_token::AssociatedToken};
+use anchor_spl::{token_interface::{TokenInterface, TokenAccount, Mint}, associated_token::AssociatedToken};
 
 use crate::{state::{config::DaoConfig, StakeState}, errors::DaoError};
 
@@ -11,18 +11,20 @@ pub struct InitializeStake<'info> {
     owner: Signer<'info>,
     #[account(
         associated_token::mint = mint,
-        associated_token::authority = owner
+        associated_token::authority = owner,
+        associated_token::token_program = token_program
     )]
-    owner_ata: Account<'info, TokenAccount>,
+    owner_ata: InterfaceAccount<'info, TokenAccount>,
     #[account(
         init,
         payer = owner,
         seeds = [b"vault", config.key().as_ref(), owner.key().as_ref()],
         bump,
         token::mint = mint,
-        token::authority = stake_auth
+        token::authority = stake_auth,
+        token::token_program = token_program
     )]
-    stake_ata: Account<'info, TokenAccount>,
+    stake_ata: InterfaceAccount<'info, TokenAccount>,
     #[account(
         seeds=[b"auth", config.key().as_ref(), owner.key().as_ref()],
         bump
@@ -30,10 +32,11 @@ pub struct InitializeStake<'info> {
     ///CHECK: This is safe. It's just used to sign things
     stake_auth: UncheckedAccount<'info>,
     #[account(
-        seeds=[b"mint", config.key().as_ref()],
-        bump = config.mint_bump
+        seeds=[config.governance_mint_seed(), config.key().as_ref()],
+        bump = config.governance_mint_bump(),
+        mint::token_program = token_program
     )]
-    collection: Account<'info, Mint>,
+    mint: InterfaceAccount<'info, Mint>,
     #[account(
         init,
         payer = owner,
@@ -47,14 +50,14 @@ pub struct InitializeStake<'info> {
         bump = config.config_bump
     )]
     config: Account<'info, DaoConfig>,
-    token_program: Program<'info, Token>,
+    token_program: Interface<'info, TokenInterface>,
     associated_token_program: Program<'info, AssociatedToken>,
     system_program: Program<'info, System>
 }
 
 impl<'info> InitializeStake<'info> {
     pub fn init(
-        &self,
+        &mut self,
         bumps: &BTreeMap<String, u8>
     ) -> Result<()> {
         self.stake_state.init(
@@ -1,6 +1,6 @@
 use anchor_lang::{prelude::*, system_program::{Transfer, transfer}};
 use anchor_spl::{
-    token::{Token, TokenAccount, MintTo, mint_to, Mint}, 
+    token::{Token, TokenAccount, MintTo, mint_to, Mint, TransferChecked, transfer_checked},
     associated_token::AssociatedToken,
     metadata::Metadata
 };
@@ -11,7 +11,7 @@ use mpl_token_metadata::{
 
 use solana_program::program::invoke_signed;
 
-use crate::state::{DaoConfig, MultiSig};
+use crate::{state::{DaoConfig, MultiSig, Subsystem}, errors::DaoError};
 
 #[derive(Accounts)]
 pub struct IssueTokens<'info> {
@@ -80,10 +80,47 @@ pub struct IssueTokens<'info> {
     )]
     collection: Account<'info, Mint>,
     #[account(
+        mut,
         seeds=[b"config", config.seed.to_le_bytes().as_ref()],
         bump = config.config_bump
     )]
     config: Account<'info, DaoConfig>,
+    // Only needed when the DAO takes payments in a token
+    payment_mint: Option<Box<Account<'info, Mint>>>,
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = initializer
+    )]
+    payer_payment: Option<Box<Account<'info, TokenAccount>>>,
+    #[account(
+        init_if_needed,
+        payer = initializer,
+        associated_token::mint = payment_mint,
+        associated_token::authority = main_treasury
+    )]
+    main_payment: Option<Box<Account<'info, TokenAccount>>>,
+    #[account(
+        init_if_needed,
+        payer = initializer,
+        associated_token::mint = payment_mint,
+        associated_token::authority = dev_treasury
+    )]
+    dev_payment: Option<Box<Account<'info, TokenAccount>>>,
+    #[account(
+        init_if_needed,
+        payer = initializer,
+        associated_token::mint = payment_mint,
+        associated_token::authority = ops_treasury
+    )]
+    ops_payment: Option<Box<Account<'info, TokenAccount>>>,
+    #[account(
+        init_if_needed,
+        payer = initializer,
+        associated_token::mint = payment_mint,
+        associated_token::authority = multisig_treasury
+    )]
+    multisig_payment: Option<Box<Account<'info, TokenAccount>>>,
     mpl_program: Program<'info, Metadata>,
     token_program: Program<'info, Token>,
     associated_token_program: Program<'info, AssociatedToken>,
@@ -92,75 +129,94 @@ pub struct IssueTokens<'info> {
 }
 
 impl<'info> IssueTokens<'info> {
+    // Pay the current price in lamports, or in the payment mint when the DAO has one
+    pub fn deposit(
+        &mut self,
+        max_price: u64
+    ) -> Result<()> {
+        self.config.check_not_paused(Subsystem::Issuance)?;
+        let price = self.config.next_issue_price(max_price)?;
+        match self.config.payment_mint {
+            Some(mint) => self.deposit_token(mint, price),
+            None => self.deposit_sol(price)
+        }
+    }
+
     pub fn deposit_sol(
-        &self
+        &self,
+        price: u64
     ) -> Result<()> {
-        let issue_price = self.config.issue_price;
-        let mut remaining_amount = issue_price;
-        let main_share = self.config.main_treasury_share as u64;
-        let dev_share = self.config.dev_treasury_share as u64;
-        let ops_share = self.config.ops_treasury_share as u64;
+        let split = self.config.split_payment(price)?;
 
         // Transfer to main treasury
-        let main_share = (issue_price * main_share) / 100;
-        remaining_amount = remaining_amount - main_share;
-
-        let main_accounts = Transfer {
-            from: self.initializer.to_account_info(),
-            to: self.main_treasury.to_account_info()
-        };
+        self.transfer_sol(&self.main_treasury, split.main)?;
 
-        let main_ctx = CpiContext::new(
-            self.system_program.to_account_info(),
-            main_accounts
-        );
+        // Transfer to dev treasury
+        self.transfer_sol(&self.dev_treasury, split.dev)?;
 
-        transfer(main_ctx, main_share)?;
+        // Transfer to ops treasury
+        self.transfer_sol(&self.ops_treasury, split.ops)?;
 
-        // Transfer to dev treasury
-        let dev_share = (issue_price * dev_share) / 100;
-        remaining_amount = remaining_amount - dev_share;
+        // Transfer to multisig treasury
+        self.transfer_sol(&self.multisig_treasury, split.multisig)
+    }
 
-        let dev_accounts = Transfer {
+    fn transfer_sol(
+        &self,
+        treasury: &SystemAccount<'info>,
+        amount: u64
+    ) -> Result<()> {
+        let accounts = Transfer {
             from: self.initializer.to_account_info(),
-            to: self.dev_treasury.to_account_info()
+            to: treasury.to_account_info()
         };
 
-        let dev_ctx = CpiContext::new(
+        let ctx = CpiContext::new(
             self.system_program.to_account_info(),
-            dev_accounts
+            accounts
         );
 
-        transfer(dev_ctx, dev_share)?;
-
-        // Transfer to ops treasury
-        let ops_share = (issue_price * ops_share) / 100;
-        remaining_amount = remaining_amount - ops_share;
+        transfer(ctx, amount)
+    }
 
-        let ops_accounts = Transfer {
-            from: self.initializer.to_account_info(),
-            to: self.ops_treasury.to_account_info()
-        };
+    pub fn deposit_token(
+        &self,
+        mint: Pubkey,
+        price: u64
+    ) -> Result<()> {
+        let payment_mint = self.payment_mint.as_ref().ok_or(DaoError::InvalidPaymentMint)?;
+        require_keys_eq!(payment_mint.key(), mint, DaoError::InvalidPaymentMint);
+        let split = self.config.split_payment(price)?;
 
-        let ops_ctx = CpiContext::new(
-            self.system_program.to_account_info(),
-            ops_accounts
-        );
+        // Transfer to the treasuries' token accounts, in the same shares
+        self.transfer_token(&self.main_payment, split.main)?;
+        self.transfer_token(&self.dev_payment, split.dev)?;
+        self.transfer_token(&self.ops_payment, split.ops)?;
+        self.transfer_token(&self.multisig_payment, split.multisig)
+    }
 
-        transfer(ops_ctx, ops_share)?;
+    fn transfer_token(
+        &self,
+        treasury: &Option<Box<Account<'info, TokenAccount>>>,
+        amount: u64
+    ) -> Result<()> {
+        let payment_mint = self.payment_mint.as_ref().ok_or(DaoError::InvalidPaymentMint)?;
+        let from = self.payer_payment.as_ref().ok_or(DaoError::InvalidPaymentMint)?;
+        let to = treasury.as_ref().ok_or(DaoError::InvalidPaymentMint)?;
 
-        // Transfer to multisig treasury
-        let mulsig_accounts = Transfer {
-            from: self.initializer.to_account_info(),
-            to: self.multisig_treasury.to_account_info()
+        let accounts = TransferChecked {
+            from: from.to_account_info(),
+            mint: payment_mint.to_account_info(),
+            to: to.to_account_info(),
+            authority: self.initializer.to_account_info()
         };
 
-        let mulsig_ctx = CpiContext::new(
-            self.system_program.to_account_info(),
-            mulsig_accounts
+        let ctx = CpiContext::new(
+            self.token_program.to_account_info(),
+            accounts
         );
 
-        transfer(mulsig_ctx, remaining_amount)
+        transfer_checked(ctx, amount, payment_mint.decimals)
     }
 
     pub fn issue_tokens(
@@ -0,0 +1,101 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{token_interface::{TokenAccount, Mint, MintTo, mint_to, FreezeAccount, freeze_account, ThawAccount, thaw_account}, token_2022::Token2022, associated_token::AssociatedToken};
+
+use crate::state::{config::DaoConfig, MultiSig, Subsystem};
+
+#[derive(Accounts)]
+pub struct IssueMembership<'info> {
+    #[account(mut)]
+    payer: Signer<'info>,
+    ///CHECK: This is safe. It only receives the membership tokens
+    member: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint,
+        associated_token::authority = member,
+        associated_token::token_program = token_program
+    )]
+    member_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds=[b"membership", config.key().as_ref()],
+        bump = config.membership_bump,
+        mint::token_program = token_program
+    )]
+    mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        seeds=[b"auth", config.key().as_ref()],
+        bump = config.auth_bump
+    )]
+    ///CHECK: This is safe. It's just used to sign things
+    auth: UncheckedAccount<'info>,
+    #[account(
+        seeds=[b"multisig", config.key().as_ref()],
+        bump = multi_sig.multisig_bump
+    )]
+    multi_sig: Account<'info, MultiSig>,
+    #[account(
+        seeds=[b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    config: Account<'info, DaoConfig>,
+    token_program: Program<'info, Token2022>,
+    associated_token_program: Program<'info, AssociatedToken>,
+    system_program: Program<'info, System>
+}
+
+impl<'info> IssueMembership<'info> {
+    pub fn issue_membership(
+        &mut self,
+        amount: u64,
+        signers: &[AccountInfo<'info>]
+    ) -> Result<()> {
+        self.config.check_not_paused(Subsystem::Issuance)?;
+        // Multisig signers are passed in as remaining accounts
+        self.multi_sig.check_signers(signers)?;
+
+        let seeds = &[
+            &b"auth"[..],
+            &self.config.key().to_bytes()[..],
+            &[self.config.auth_bump],
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        // A member with staked tokens has their account frozen, so thaw it for the mint and freeze it again after
+        let frozen = self.member_ata.is_frozen();
+        if frozen {
+            let accounts = ThawAccount {
+                account: self.member_ata.to_account_info(),
+                mint: self.mint.to_account_info(),
+                authority: self.auth.to_account_info()
+            };
+            thaw_account(CpiContext::new_with_signer(self.token_program.to_account_info(), accounts, signer_seeds))?;
+        }
+
+        let accounts = MintTo {
+            mint: self.mint.to_account_info(),
+            to: self.member_ata.to_account_info(),
+            authority: self.auth.to_account_info()
+        };
+
+        let ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            accounts,
+            signer_seeds
+        );
+
+        mint_to(ctx, amount)?;
+
+        if frozen {
+            let accounts = FreezeAccount {
+                account: self.member_ata.to_account_info(),
+                mint: self.mint.to_account_info(),
+                authority: self.auth.to_account_info()
+            };
+            freeze_account(CpiContext::new_with_signer(self.token_program.to_account_info(), accounts, signer_seeds))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,98 @@
+use anchor_lang::{prelude::*, Discriminator};
+
+use crate::{constants::{PUBKEY_L, U64_L}, state::{Allocation, Ballots, ConvictionProposal, DaoConfig, MemberRoles, MultiSig, Payroll, Proposal, Reputation, StakeState, SwapOrder, TallyRule, VoteState, Versioned, Whitelist, ACCOUNT_VERSION}, errors::DaoError, helpers::realloc};
+
+#[derive(Accounts)]
+pub struct Migrate<'info> {
+    #[account(mut)]
+    payer: Signer<'info>,
+    #[account(mut, owner = crate::ID)]
+    ///CHECK: This is safe. Its type is read from the discriminator and checked on deserialization
+    account: UncheckedAccount<'info>,
+    system_program: Program<'info, System>
+}
+
+impl<'info> Migrate<'info> {
+    pub fn migrate(&self) -> Result<()> {
+        let mut discriminator = [0u8; 8];
+        {
+            let data = self.account.try_borrow_data()?;
+            discriminator.copy_from_slice(data.get(..8).ok_or(DaoError::InvalidAccountType)?);
+            require!(!predates_versioning(discriminator, &data), DaoError::UnversionedAccount);
+        }
+        match discriminator {
+            Allocation::DISCRIMINATOR => self.migrate_account::<Allocation>(),
+            Ballots::DISCRIMINATOR => self.migrate_account::<Ballots>(),
+            ConvictionProposal::DISCRIMINATOR => self.migrate_account::<ConvictionProposal>(),
+            DaoConfig::DISCRIMINATOR => self.migrate_account::<DaoConfig>(),
+            MemberRoles::DISCRIMINATOR => self.migrate_account::<MemberRoles>(),
+            MultiSig::DISCRIMINATOR => self.migrate_account::<MultiSig>(),
+            Payroll::DISCRIMINATOR => self.migrate_account::<Payroll>(),
+            Proposal::DISCRIMINATOR => self.migrate_account::<Proposal>(),
+            Reputation::DISCRIMINATOR => self.migrate_account::<Reputation>(),
+            StakeState::DISCRIMINATOR => self.migrate_account::<StakeState>(),
+            SwapOrder::DISCRIMINATOR => self.migrate_account::<SwapOrder>(),
+            VoteState::DISCRIMINATOR => self.migrate_account::<VoteState>(),
+            Whitelist::DISCRIMINATOR => self.migrate_account::<Whitelist>(),
+            _ => err!(DaoError::InvalidAccountType)
+        }
+    }
+
+    // Read the account in its old layout, grow it to the current one and write it back
+    fn migrate_account<T: Versioned>(&self) -> Result<()> {
+        let info = self.account.to_account_info();
+
+        // Fields inserted since the account was written go in as zeroes where they belong
+        let mut data = info.try_borrow_data()?.to_vec();
+        let version = *data.get(8).ok_or(DaoError::InvalidAccountType)?;
+        for (added, offset, len) in inserted_fields(T::DISCRIMINATOR) {
+            if version < *added {
+                require_gte!(data.len(), *offset, DaoError::InvalidAccountType);
+                data.splice(offset..offset, vec![0; *len]);
+            }
+        }
+        // Fields appended since then read as zeroes
+        data.resize(data.len() + T::INIT_SPACE, 0);
+        let mut account = T::try_deserialize(&mut &data[..])?;
+        require_gt!(ACCOUNT_VERSION, account.version(), DaoError::AccountUpToDate);
+        account.set_version(ACCOUNT_VERSION);
+
+        let space = account.space().max(info.data_len());
+        realloc(&info, space, &self.payer.to_account_info(), &self.system_program.to_account_info())?;
+
+        let mut data = info.try_borrow_mut_data()?;
+        account.try_serialize(&mut &mut data[..])
+    }
+}
+
+// Where the staking pause flag went, after the other four. Everything before it hasn't moved since versioning
+const PAUSE_STAKING_OFFSET: usize = 8 + 1 + 11 * U64_L + 2 + 3 * TallyRule::INIT_SPACE + 4;
+
+// Fields added somewhere other than the end, as the version that added them, their offset and length
+fn inserted_fields(discriminator: [u8; 8]) -> &'static [(u8, usize, usize)] {
+    match discriminator {
+        DaoConfig::DISCRIMINATOR => &[(14, PAUSE_STAKING_OFFSET, 1)],
+        _ => &[]
+    }
+}
+
+// Accounts written before versioning have no version byte, so every field would be read one
+// byte early. They can't be migrated and have to be closed instead. Their layouts are told
+// apart by size, none of which a versioned layout of the same type has had
+fn predates_versioning(discriminator: [u8; 8], data: &[u8]) -> bool {
+    // Sizes without any items, and the size of an item
+    let (sizes, item): (&[usize], usize) = match discriminator {
+        DaoConfig::DISCRIMINATOR => (&[97, 98, 108, 116, 125, 145], 0),
+        MultiSig::DISCRIMINATOR => (&[15], PUBKEY_L),
+        Proposal::DISCRIMINATOR => (&[141, 215, 217, 223, 255], U64_L),
+        StakeState::DISCRIMINATOR => (&[67, 75], 0),
+        Whitelist::DISCRIMINATOR => (&[333], 0),
+        // Same size as the first versioned votes, but the last byte was left unused where those keep their bump
+        VoteState::DISCRIMINATOR => return data.len() == 51 && data[50] == 0,
+        _ => return false
+    };
+    sizes.iter().any(|size| match item {
+        0 => data.len() == *size,
+        _ => data.len() >= *size && (data.len() - size) % item == 0
+    })
+}
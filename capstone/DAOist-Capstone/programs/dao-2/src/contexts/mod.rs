@@ -1,19 +1,63 @@
 pub mod initialize;
 pub mod issue;
+pub mod verify_nft;
+pub mod init_membership;
+pub mod issue_membership;
+pub mod spawn_sub_dao;
+pub mod govern_sub_dao;
+pub mod create_payroll;
+pub mod govern_payroll;
+pub mod pay_payroll;
+pub mod govern_roles;
+pub mod treasurer_payout;
+pub mod guardian_pause;
+pub mod create_swap;
+pub mod execute_swap;
+pub mod create_conviction;
+pub mod allocate;
+pub mod execute_conviction;
 pub mod initialize_stake;
 pub mod cleanup_stake;
 pub mod stake;
 pub mod create_proposal;
 pub mod cleanup_proposal;
+pub mod consume_proposal;
 pub mod vote;
 pub mod unvote;
+pub mod relay_votes;
+pub mod rage_quit;
+pub mod update_whitelist;
+pub mod pause;
+pub mod migrate;
 
 pub use initialize::*;
 pub use issue::*;
+pub use verify_nft::*;
+pub use init_membership::*;
+pub use issue_membership::*;
+pub use spawn_sub_dao::*;
+pub use govern_sub_dao::*;
+pub use create_payroll::*;
+pub use govern_payroll::*;
+pub use pay_payroll::*;
+pub use govern_roles::*;
+pub use treasurer_payout::*;
+pub use guardian_pause::*;
+pub use create_swap::*;
+pub use execute_swap::*;
+pub use create_conviction::*;
+pub use allocate::*;
+pub use execute_conviction::*;
 pub use initialize_stake::*;
 pub use cleanup_stake::*;
 pub use stake::*;
 pub use create_proposal::*;
 pub use cleanup_proposal::*;
+pub use consume_proposal::*;
 pub use vote::*;
 pub use unvote::*;
+pub use relay_votes::*;
+pub use rage_quit::*;
+pub use update_whitelist::*;
+pub use pause::*;
+pub use migrate::*;
@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{config::DaoConfig, MultiSig, PauseFlags};
+
+#[derive(Accounts)]
+pub struct Pause<'info> {
+    #[account(
+        seeds=[b"multisig", config.key().as_ref()],
+        bump = multi_sig.multisig_bump
+    )]
+    multi_sig: Account<'info, MultiSig>,
+    #[account(
+        mut,
+        seeds=[b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    config: Account<'info, DaoConfig>
+}
+
+impl<'info> Pause<'info> {
+    pub fn pause(
+        &mut self,
+        flags: PauseFlags,
+        signers: &[AccountInfo<'info>]
+    ) -> Result<()> {
+        // Multisig signers are passed in as remaining accounts
+        self.multi_sig.check_signers(signers)?;
+        self.config.pause(flags)
+    }
+}
@@ -0,0 +1,78 @@
+use anchor_lang::{prelude::*, system_program::{Transfer, transfer}};
+
+use crate::{state::{config::DaoConfig, Payroll, Subsystem}, errors::DaoError, helpers::validate_treasury};
+
+#[derive(Accounts)]
+#[instruction(selected_treasury: String)]
+pub struct PayPayroll<'info> {
+    #[account(
+        mut,
+        seeds=[b"payroll", config.key().as_ref(), payroll.creator.as_ref(), payroll.id.to_le_bytes().as_ref()],
+        bump = payroll.bump
+    )]
+    payroll: Account<'info, Payroll>,
+    #[account(
+        mut,
+        seeds=[selected_treasury.as_bytes(), config.key().as_ref()],
+        bump
+    )]
+    treasury: SystemAccount<'info>,
+    #[account(
+        seeds=[b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    config: Account<'info, DaoConfig>,
+    system_program: Program<'info, System>
+}
+
+impl<'info> PayPayroll<'info> {
+    pub fn pay_payroll(
+        &mut self,
+        treasury: String,
+        treasury_bump: u8,
+        payees: &[AccountInfo<'info>]
+    ) -> Result<()> {
+        require!(validate_treasury(treasury.clone())? == self.payroll.treasury, DaoError::InvalidTreasury);
+        self.config.check_not_paused(Subsystem::Execution)?;
+        let due = self.payroll.due()?;
+        // Payees are passed in as remaining accounts, in the payroll's order
+        require_eq!(payees.len(), self.payroll.entries.len(), DaoError::InvalidPayee);
+
+        for (entry, payee) in self.payroll.entries.iter().zip(payees) {
+            require_keys_eq!(entry.payee, payee.key(), DaoError::InvalidPayee);
+            let amount = entry.amount.checked_mul(due).ok_or(DaoError::Overflow)?;
+            self.pay(payee, amount, &treasury, treasury_bump)?;
+        }
+
+        self.payroll.record(due)
+    }
+
+    pub fn pay(
+        &self,
+        payee: &AccountInfo<'info>,
+        amount: u64,
+        treasury: &String,
+        treasury_bump: u8
+    ) -> Result<()> {
+        let accounts = Transfer {
+            from: self.treasury.to_account_info(),
+            to: payee.clone()
+        };
+
+        let seeds = &[
+            treasury.as_bytes(),
+            &self.config.key().to_bytes()[..],
+            &[treasury_bump],
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let ctx = CpiContext::new_with_signer(
+            self.system_program.to_account_info(),
+            accounts,
+            signer_seeds
+        );
+
+        transfer(ctx, amount)
+    }
+}
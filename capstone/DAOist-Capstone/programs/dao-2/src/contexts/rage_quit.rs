@@ -0,0 +1,294 @@
+use anchor_lang::{prelude::*, system_program::{Transfer, transfer}};
+use anchor_spl::{token::{TokenAccount as TreasuryTokenAccount, Token, Transfer as TransferSpl, transfer as transfer_spl}, token_interface::{TokenInterface, TokenAccount, Mint, Burn, burn, FreezeAccount, freeze_account, ThawAccount, thaw_account}};
+
+use crate::{state::{config::DaoConfig, Proposal, ProposalType, StakeState, VoteState, Whitelist, AGAINST}, errors::DaoError, helpers::{is_soulbound, pro_rata}};
+
+#[derive(Accounts)]
+pub struct RageQuit<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+    #[account(
+        mut,
+        seeds=[b"stake", config.key().as_ref(), owner.key().as_ref()],
+        bump = stake_state.state_bump
+    )]
+    stake_state: Account<'info, StakeState>,
+    #[account(
+        mut,
+        seeds = [b"vault", config.key().as_ref(), owner.key().as_ref()],
+        bump = stake_state.vault_bump,
+        token::mint = mint,
+        token::authority = stake_auth,
+        token::token_program = token_program
+    )]
+    stake_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program
+    )]
+    owner_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        seeds=[b"auth", config.key().as_ref(), owner.key().as_ref()],
+        bump = stake_state.auth_bump
+    )]
+    ///CHECK: This is safe. It's just used to sign things
+    stake_auth: UncheckedAccount<'info>,
+    #[account(
+        seeds=[b"auth", config.key().as_ref()],
+        bump = config.auth_bump
+    )]
+    ///CHECK: This is safe. It's the freeze authority of the membership mint
+    dao_auth: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds=[config.governance_mint_seed(), config.key().as_ref()],
+        bump = config.governance_mint_bump(),
+        mint::token_program = token_program
+    )]
+    mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        seeds=[b"proposal", config.key().as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+    )]
+    proposal: Account<'info, Proposal>,
+    #[account(
+        mut,
+        close = owner,
+        has_one = owner,
+        seeds=[b"vote", owner.key().as_ref(), proposal.key().as_ref()],
+        bump = vote.bump
+    )]
+    vote: Account<'info, VoteState>,
+    #[account(
+        mut,
+        seeds=[b"treasury", config.key().as_ref()],
+        bump = config.main_treasury_bump
+    )]
+    treasury: SystemAccount<'info>,
+    #[account(
+        seeds=[b"whitelist", config.key().as_ref()],
+        bump = whitelist.bump
+    )]
+    whitelist: Account<'info, Whitelist>,
+    #[account(
+        mut,
+        seeds=[b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    config: Account<'info, DaoConfig>,
+    token_program: Interface<'info, TokenInterface>,
+    treasury_token_program: Program<'info, Token>,
+    system_program: Program<'info, System>
+}
+
+impl<'info> RageQuit<'info> {
+    pub fn rage_quit(
+        &mut self,
+        token_accounts: &[AccountInfo<'info>]
+    ) -> Result<()> {
+        // Not even a pause can run out the clock on a member's way out
+        self.config.check_not_dissolved()?;
+        // Only members who voted against a proposal that moves funds. Votes are keyed by
+        // owner and proposal, so this is the member's only vote on it
+        require!(self.vote.choice as usize == AGAINST, DaoError::RageQuitNotAllowed);
+        require!(matches!(self.proposal.proposal, ProposalType::Bounty(_, _)), DaoError::RageQuitNotAllowed);
+        // The proposal must have passed and still be in its execution delay
+        self.proposal.try_finalize()?;
+        self.proposal.is_succeeded()?;
+        self.proposal.check_rage_quit_window(self.config.execution_delay)?;
+
+        // Only the stake that objected leaves, the rest stays staked
+        let amount = self.vote.amount;
+        let staked_supply = self.config.staked_supply;
+        self.stake_state.check_stake_amount(amount)?;
+
+        // Pay out the member's share of everything the main treasury holds
+        self.withdraw_lamports(amount, staked_supply)?;
+        self.withdraw_tokens(token_accounts, amount, staked_supply)?;
+
+        // Forfeit the stake
+        self.burn_stake(amount)?;
+        self.config.remove_stake(amount)?;
+        self.stake_state.forfeit(amount)
+    }
+
+    pub fn withdraw_lamports(
+        &self,
+        amount: u64,
+        staked_supply: u64
+    ) -> Result<()> {
+        // Keep the treasury rent exempt
+        let available = self.treasury.lamports().saturating_sub(Rent::get()?.minimum_balance(0));
+        let share = pro_rata(available, amount, staked_supply)?;
+        if share == 0 {
+            return Ok(());
+        }
+
+        let accounts = Transfer {
+            from: self.treasury.to_account_info(),
+            to: self.owner.to_account_info()
+        };
+
+        let seeds = &[
+            &b"treasury"[..],
+            &self.config.key().to_bytes()[..],
+            &[self.config.main_treasury_bump],
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let ctx = CpiContext::new_with_signer(
+            self.system_program.to_account_info(),
+            accounts,
+            signer_seeds
+        );
+
+        transfer(ctx, share)
+    }
+
+    // Token accounts come in pairs of (treasury token account, member token account).
+    // Any whitelisted balance the member leaves out stays in the treasury.
+    pub fn withdraw_tokens(
+        &self,
+        token_accounts: &[AccountInfo<'info>],
+        amount: u64,
+        staked_supply: u64
+    ) -> Result<()> {
+        require!(token_accounts.len() % 2 == 0, DaoError::InvalidTreasury);
+
+        let seeds = &[
+            &b"treasury"[..],
+            &self.config.key().to_bytes()[..],
+            &[self.config.main_treasury_bump],
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let mut claimed: Vec<Pubkey> = vec![];
+
+        for pair in token_accounts.chunks(2) {
+            let treasury_ata = Account::<TreasuryTokenAccount>::try_from(&pair[0])?;
+            require_keys_eq!(treasury_ata.owner, self.treasury.key(), DaoError::InvalidTreasury);
+            self.whitelist.check_mint(&treasury_ata.mint)?;
+            // Each balance can only be claimed once
+            require!(!claimed.contains(&pair[0].key()), DaoError::InvalidTreasury);
+            claimed.push(pair[0].key());
+
+            let share = pro_rata(treasury_ata.amount, amount, staked_supply)?;
+            if share == 0 {
+                continue;
+            }
+
+            let accounts = TransferSpl {
+                from: pair[0].clone(),
+                to: pair[1].clone(),
+                authority: self.treasury.to_account_info()
+            };
+
+            let ctx = CpiContext::new_with_signer(
+                self.treasury_token_program.to_account_info(),
+                accounts,
+                signer_seeds
+            );
+
+            transfer_spl(ctx, share)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn burn_stake(
+        &self,
+        amount: u64
+    ) -> Result<()> {
+        if is_soulbound(&self.mint.to_account_info())? {
+            return self.burn_locked_stake(amount);
+        }
+
+        let accounts = Burn {
+            mint: self.mint.to_account_info(),
+            from: self.stake_ata.to_account_info(),
+            authority: self.stake_auth.to_account_info()
+        };
+
+        let seeds = &[
+            &b"auth"[..],
+            &self.config.key().to_bytes()[..],
+            &self.owner.key().to_bytes()[..],
+            &[self.stake_state.auth_bump],
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            accounts,
+            signer_seeds
+        );
+
+        burn(ctx, amount)
+    }
+
+    // Soulbound stake is frozen in the member's own account, thaw it and burn it there.
+    // Whatever stays staked is frozen again
+    pub fn burn_locked_stake(
+        &self,
+        amount: u64
+    ) -> Result<()> {
+        let accounts = ThawAccount {
+            account: self.owner_ata.to_account_info(),
+            mint: self.mint.to_account_info(),
+            authority: self.dao_auth.to_account_info()
+        };
+
+        let seeds = &[
+            &b"auth"[..],
+            &self.config.key().to_bytes()[..],
+            &[self.config.auth_bump],
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            accounts,
+            signer_seeds
+        );
+
+        thaw_account(ctx)?;
+
+        let accounts = Burn {
+            mint: self.mint.to_account_info(),
+            from: self.owner_ata.to_account_info(),
+            authority: self.owner.to_account_info()
+        };
+
+        let ctx = CpiContext::new(
+            self.token_program.to_account_info(),
+            accounts
+        );
+
+        burn(ctx, amount)?;
+
+        if self.stake_state.amount == amount {
+            return Ok(());
+        }
+
+        let accounts = FreezeAccount {
+            account: self.owner_ata.to_account_info(),
+            mint: self.mint.to_account_info(),
+            authority: self.dao_auth.to_account_info()
+        };
+
+        let ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            accounts,
+            signer_seeds
+        );
+
+        freeze_account(ctx)
+    }
+}
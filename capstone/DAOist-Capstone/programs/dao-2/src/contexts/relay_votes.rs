@@ -0,0 +1,140 @@
+use anchor_lang::{prelude::*, solana_program::sysvar::instructions::{load_current_index_checked, load_instruction_at_checked}};
+
+use crate::{state::{config::DaoConfig, Ballot, Ballots, Proposal, Reputation, StakeState, Subsystem, Versioned}, errors::DaoError, helpers::{create_pda, realloc, verified_messages}};
+
+#[derive(Accounts)]
+pub struct RelayVotes<'info> {
+    #[account(mut)]
+    relayer: Signer<'info>,
+    #[account(
+        mut,
+        seeds=[b"proposal", config.key().as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+    )]
+    proposal: Account<'info, Proposal>,
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        seeds=[b"ballots", proposal.key().as_ref()],
+        bump,
+        space = 8 + Ballots::INIT_SPACE
+    )]
+    ballots: Account<'info, Ballots>,
+    #[account(
+        mut,
+        seeds=[b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    config: Account<'info, DaoConfig>,
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    ///CHECK: This is safe. It's the instructions sysvar
+    instructions: UncheckedAccount<'info>,
+    system_program: Program<'info, System>
+}
+
+impl<'info> RelayVotes<'info> {
+    // Count ballots verified by the Ed25519 instruction right before this one.
+    // Each voter's stake, vote and reputation accounts are passed as remaining accounts, in the order of the signatures.
+    pub fn relay_votes(
+        &mut self,
+        voter_accounts: &[AccountInfo<'info>],
+        bump: u8
+    ) -> Result<()> {
+        self.config.check_proposal_not_paused(&self.proposal.proposal, Subsystem::Voting)?;
+        // Check proposal is open if not tries to initialize
+        if self.proposal.is_open().is_err() {
+            self.proposal.try_initialize(&self.config)?;
+        }
+        // Check proposal hasn't expired
+        self.proposal.check_expiry()?;
+        if self.ballots.version == 0 {
+            self.ballots.init(self.proposal.key(), bump)?;
+        }
+
+        let instructions = self.instructions.to_account_info();
+        let current = load_current_index_checked(&instructions)? as usize;
+        let previous = current.checked_sub(1).ok_or(DaoError::InvalidBallot)?;
+        let messages = verified_messages(&load_instruction_at_checked(previous, &instructions)?)?;
+        require_eq!(messages.len() * 3, voter_accounts.len(), DaoError::InvalidBallot);
+
+        for ((voter, message), accounts) in messages.iter().zip(voter_accounts.chunks(3)) {
+            self.relay_vote(voter, message, &accounts[0], &accounts[1], &accounts[2])?;
+        }
+
+        // Make room for any new relay indexes
+        let space = self.ballots.space();
+        realloc(&self.ballots.to_account_info(), space, &self.relayer.to_account_info(), &self.system_program.to_account_info())
+    }
+
+    fn relay_vote(
+        &mut self,
+        voter: &Pubkey,
+        message: &[u8],
+        info: &AccountInfo<'info>,
+        vote: &AccountInfo<'info>,
+        reputation_info: &AccountInfo<'info>
+    ) -> Result<()> {
+        let ballot = Ballot::try_from_slice(message).map_err(|_| DaoError::InvalidBallot)?;
+        require_keys_eq!(ballot.proposal, self.proposal.key(), DaoError::InvalidBallot);
+
+        let mut stake_state: Account<StakeState> = Account::try_from(info)?;
+        // The stake account has to belong to the key that signed the ballot
+        let config_key = self.config.key();
+        let seeds = [b"stake".as_ref(), config_key.as_ref(), voter.as_ref(), &[stake_state.state_bump]];
+        let address = Pubkey::create_program_address(&seeds, &crate::ID).map_err(|_| DaoError::InvalidBallot)?;
+        require_keys_eq!(address, info.key(), DaoError::InvalidBallot);
+        // A staker who voted directly can't have a ballot relayed too
+        let proposal_key = self.proposal.key();
+        let (vote_address, _) = Pubkey::find_program_address(&[b"vote", voter.as_ref(), proposal_key.as_ref()], &crate::ID);
+        require_keys_eq!(vote_address, vote.key(), DaoError::InvalidBallot);
+        require!(vote.data_is_empty(), DaoError::AlreadyVoted);
+
+        // Ensure vote amount > 0
+        require!(ballot.amount > 0, DaoError::InvalidVoteAmount);
+        // Make sure user has staked
+        stake_state.check_stake_amount(ballot.amount)?;
+        stake_state.check_votable_amount(ballot.amount, self.proposal.opened)?;
+        // Use up the nonce and lock the stake until the proposal expires
+        stake_state.relay(ballot.nonce, self.proposal.expiry)?;
+        // First relayed ballot of this staker gets a bit in every proposal's bitmap
+        if stake_state.relay_index == 0 {
+            stake_state.relay_index = self.config.add_relay_voter()?;
+        }
+        self.ballots.record(stake_state.relay_index - 1)?;
+        // Weigh the vote by the voter's reputation, the same as a direct vote
+        let rule = self.config.reputation_rule;
+        let mut reputation = self.load_reputation(voter, reputation_info)?;
+        reputation.decay(rule.half_life)?;
+        let weight = rule.vote_weight(ballot.amount, reputation.score)?;
+        // Add vote to proposal
+        self.proposal.add_vote(ballot.amount, weight, ballot.choice)?;
+        reputation.add(rule.vote_points, rule.half_life)?;
+        reputation.exit(&crate::ID)?;
+        stake_state.exit(&crate::ID)
+    }
+
+    // The voter's reputation account, created for them if this is the first time they need one
+    fn load_reputation(
+        &self,
+        voter: &Pubkey,
+        info: &AccountInfo<'info>
+    ) -> Result<Account<'info, Reputation>> {
+        let config_key = self.config.key();
+        let (address, bump) = Pubkey::find_program_address(&[b"reputation", config_key.as_ref(), voter.as_ref()], &crate::ID);
+        require_keys_eq!(address, info.key(), DaoError::InvalidBallot);
+        if !info.data_is_empty() {
+            return Account::try_from(info);
+        }
+
+        let seeds = &[
+            &b"reputation"[..],
+            &config_key.to_bytes()[..],
+            &voter.to_bytes()[..],
+            &[bump],
+        ];
+        create_pda(info, Reputation::LEN, &self.relayer.to_account_info(), &self.system_program.to_account_info(), &[&seeds[..]])?;
+        let mut reputation: Account<Reputation> = Account::try_from_unchecked(info)?;
+        reputation.init_if_needed(*voter, bump)?;
+        Ok(reputation)
+    }
+}
@@ -0,0 +1,153 @@
+use anchor_lang::{prelude::*, system_program::{Transfer, transfer}};
+
+use crate::{state::{config::DaoConfig, MultiSig, Proposal, ProposalType, Subsystem, Whitelist}, errors::DaoError, helpers::validate_treasury};
+
+#[derive(Accounts)]
+#[instruction(selected_treasury: String, seed: u64, multisig_keys: Vec<Pubkey>)]
+pub struct SpawnSubDao<'info> {
+    // The creator named in the parent's proposal
+    #[account(mut)]
+    creator: Signer<'info>,
+    #[account(
+        mut,
+        seeds=[b"proposal", parent_config.key().as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    proposal: Account<'info, Proposal>,
+    #[account(
+        mut,
+        seeds=[selected_treasury.as_bytes(), parent_config.key().as_ref()],
+        bump
+    )]
+    parent_treasury: SystemAccount<'info>,
+    #[account(
+        seeds=[b"config", parent_config.seed.to_le_bytes().as_ref()],
+        bump = parent_config.config_bump
+    )]
+    parent_config: Box<Account<'info, DaoConfig>>,
+    #[account(
+        seeds=[b"auth", config.key().as_ref()],
+        bump
+    )]
+    ///CHECK: This is safe. It's just used to sign things
+    auth: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds=[b"treasury", config.key().as_ref()],
+        bump
+    )]
+    main_treasury: SystemAccount<'info>,
+    #[account(
+        init,
+        payer = creator,
+        seeds=[b"config", seed.to_le_bytes().as_ref()],
+        bump,
+        space = DaoConfig::LEN
+    )]
+    config: Box<Account<'info, DaoConfig>>,
+    #[account(
+        init,
+        payer = creator,
+        space = MultiSig::space(multisig_keys.len()),
+        seeds=[b"multisig", config.key().as_ref()],
+        bump,
+    )]
+    multi_sig: Account<'info, MultiSig>,
+    #[account(
+        seeds=[b"multisig-treasury", multi_sig.key().as_ref()],
+        bump,
+    )]
+    multisig_treasury: SystemAccount<'info>,
+    #[account(
+        init,
+        payer = creator,
+        space = Whitelist::LEN,
+        seeds=[b"whitelist", config.key().as_ref()],
+        bump
+    )]
+    whitelist: Box<Account<'info, Whitelist>>,
+    system_program: Program<'info, System>
+}
+
+impl<'info> SpawnSubDao<'info> {
+    pub fn spawn_sub_dao(
+        &mut self,
+        treasury: String,
+        treasury_bump: u8,
+        seed: u64,
+        multisig_keys: Vec<Pubkey>,
+        min_signers: u8,
+        bumps: &std::collections::BTreeMap<String, u8>
+    ) -> Result<()> {
+        require!(validate_treasury(treasury.clone())? == self.proposal.treasury, DaoError::InvalidTreasury);
+        self.parent_config.check_not_paused(Subsystem::Execution)?;
+        // Only the creator the parent voted for can spawn it, once
+        let funding = match self.proposal.proposal {
+            ProposalType::SubDao(creator, funding) => {
+                require_keys_eq!(creator, self.creator.key(), DaoError::InvalidConsumer);
+                funding
+            },
+            _ => return err!(DaoError::InvalidProposalType)
+        };
+        self.proposal.carry_out(self.parent_config.execution_delay)?;
+
+        let (
+            auth_bump,
+            config_bump,
+            main_treasury_bump,
+            multisig_bump,
+            multisig_treasury_bump,
+            whitelist_bump
+        ) = (
+            *bumps.get("auth").ok_or(DaoError::BumpError)?,
+            *bumps.get("config").ok_or(DaoError::BumpError)?,
+            *bumps.get("main_treasury").ok_or(DaoError::BumpError)?,
+            *bumps.get("multi_sig").ok_or(DaoError::BumpError)?,
+            *bumps.get("multisig_treasury").ok_or(DaoError::BumpError)?,
+            *bumps.get("whitelist").ok_or(DaoError::BumpError)?
+        );
+
+        let keys = MultiSig::validate_keys(multisig_keys, min_signers)?;
+        self.multi_sig.init(min_signers, keys, multisig_bump, multisig_treasury_bump)?;
+        self.whitelist.init(whitelist_bump)?;
+        self.config.spawn(
+            &self.parent_config,
+            self.parent_config.key(),
+            seed,
+            auth_bump,
+            config_bump,
+            main_treasury_bump
+        )?;
+
+        self.fund(funding, &treasury, treasury_bump)
+    }
+
+    // Move the funding from the parent's treasury into the sub-DAO's main treasury
+    pub fn fund(
+        &self,
+        funding: u64,
+        treasury: &String,
+        treasury_bump: u8
+    ) -> Result<()> {
+        let accounts = Transfer {
+            from: self.parent_treasury.to_account_info(),
+            to: self.main_treasury.to_account_info()
+        };
+
+        let seeds = &[
+            treasury.as_bytes(),
+            &self.parent_config.key().to_bytes()[..],
+            &[treasury_bump],
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let ctx = CpiContext::new_with_signer(
+            self.system_program.to_account_info(),
+            accounts,
+            signer_seeds
+        );
+
+        transfer(ctx, funding)
+    }
+}
@@ -1,27 +1,28 @@
 use anchor_lang::prelude::*;
-use anchor_spl::{token::{Token, TokenAccount, Transfer as TransferSpl, transfer as transfer_spl, Mint}, associated_token::AssociatedToken};
+use anchor_spl::{token_interface::{TokenInterface, TokenAccount, Mint, TransferChecked, transfer_checked, FreezeAccount, freeze_account, ThawAccount, thaw_account}, associated_token::AssociatedToken};
 
-use crate::state::{config::DaoConfig, StakeState};
+use crate::{state::{config::DaoConfig, StakeState, Subsystem}, errors::DaoError, helpers::is_soulbound};
 
 #[derive(Accounts)]
 pub struct Stake<'info> {
     #[account(mut)]
     owner: Signer<'info>,
     #[account(
-        init,
-        payer = initializer,
-        mint::authority = auth,
-        mint::freeze_authority = auth,
-        mint::decimals = 0
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program
     )]
-    nft: Box<Account<'info, Mint>>,
+    owner_ata: InterfaceAccount<'info, TokenAccount>,
     #[account(
-        init,
-        payer = initializer,
-        associated_token::mint = nft,
-        associated_token::authority = initializer,
+        mut,
+        seeds = [b"vault", config.key().as_ref(), owner.key().as_ref()],
+        bump = stake_state.vault_bump,
+        token::mint = mint,
+        token::authority = auth,
+        token::token_program = token_program
     )]
-    token: Box<Account<'info, TokenAccount>>,
+    stake_ata: InterfaceAccount<'info, TokenAccount>,
     #[account(
         seeds=[b"auth", config.key().as_ref(), owner.key().as_ref()],
         bump = stake_state.auth_bump
@@ -29,22 +30,30 @@ pub struct Stake<'info> {
     ///CHECK: This is safe. It's just used to sign things
     auth: UncheckedAccount<'info>,
     #[account(
-        seeds=[b"mint", config.key().as_ref()],
-        bump = config.mint_bump
+        seeds=[b"auth", config.key().as_ref()],
+        bump = config.auth_bump
+    )]
+    ///CHECK: This is safe. It's the freeze authority of the membership mint
+    dao_auth: UncheckedAccount<'info>,
+    #[account(
+        seeds=[config.governance_mint_seed(), config.key().as_ref()],
+        bump = config.governance_mint_bump(),
+        mint::token_program = token_program
     )]
-    collection: Account<'info, Mint>,
+    mint: InterfaceAccount<'info, Mint>,
     #[account(
         mut,
-        seeds=[b"stake", config.seed.to_le_bytes().as_ref()],
+        seeds=[b"stake", config.key().as_ref(), owner.key().as_ref()],
         bump = stake_state.state_bump
     )]
     stake_state: Account<'info, StakeState>,
     #[account(
+        mut,
         seeds=[b"config", config.seed.to_le_bytes().as_ref()],
         bump = config.config_bump
     )]
     config: Account<'info, DaoConfig>,
-    token_program: Program<'info, Token>,
+    token_program: Interface<'info, TokenInterface>,
     associated_token_program: Program<'info, AssociatedToken>,
     system_program: Program<'info, System>
 }
@@ -54,11 +63,15 @@ impl<'info> Stake<'info> {
         &mut self,
         amount: u64
     ) -> Result<()> {
-        self.stake_state.stake(amount)?;
+        self.config.check_not_paused(Subsystem::Staking)?;
+        if is_soulbound(&self.mint.to_account_info())? {
+            return self.lock_tokens(amount);
+        }
 
-        let accounts = TransferSpl {
-            from: self.nft.to_account_info(),
-            to: self.token.to_account_info(),
+        let accounts = TransferChecked {
+            from: self.owner_ata.to_account_info(),
+            mint: self.mint.to_account_info(),
+            to: self.stake_ata.to_account_info(),
             authority: self.owner.to_account_info()
         };
 
@@ -66,17 +79,31 @@ impl<'info> Stake<'info> {
             self.token_program.to_account_info(),
             accounts
         );
-        transfer_spl(ctx, amount)
+        let vault_before = self.stake_ata.amount;
+        transfer_checked(ctx, amount, self.mint.decimals)?;
+
+        // Transfer fees are withheld in the vault, so only what arrived counts as stake
+        self.stake_ata.reload()?;
+        let received = self.stake_ata.amount.checked_sub(vault_before).ok_or(DaoError::Underflow)?;
+        self.stake_state.stake(received, self.config.max_expiry)?;
+        self.config.add_stake(received)
     }
 
     pub fn withdraw_tokens(
-        &self,
+        &mut self,
         amount: u64
     ) -> Result<()> {
+        self.config.check_not_paused(Subsystem::Staking)?;
         self.stake_state.unstake(amount)?;
+        self.config.remove_stake(amount)?;
 
-        let accounts = TransferSpl {
+        if is_soulbound(&self.mint.to_account_info())? {
+            return self.unlock_tokens();
+        }
+
+        let accounts = TransferChecked {
             from: self.stake_ata.to_account_info(),
+            mint: self.mint.to_account_info(),
             to: self.owner_ata.to_account_info(),
             authority: self.auth.to_account_info()
         };
@@ -84,7 +111,7 @@ impl<'info> Stake<'info> {
         let seeds = &[
             &b"auth"[..],
             &self.config.key().to_bytes()[..],
-            &self.auth.key().to_bytes()[..],
+            &self.owner.key().to_bytes()[..],
             &[self.stake_state.auth_bump],
         ];
 
@@ -96,6 +123,72 @@ impl<'info> Stake<'info> {
             signer_seeds
         );
 
-        transfer_spl(ctx, amount)
+        transfer_checked(ctx, amount, self.mint.decimals)
     }
-}
\ No newline at end of file
+
+    // Soulbound tokens stay in the member's account, frozen so they can't be burned while staked
+    pub fn lock_tokens(
+        &mut self,
+        amount: u64
+    ) -> Result<()> {
+        self.stake_state.stake(amount, self.config.max_expiry)?;
+        self.config.add_stake(amount)?;
+        require_gte!(self.owner_ata.amount, self.stake_state.amount, DaoError::InvalidStakeAmount);
+        if self.owner_ata.is_frozen() {
+            return Ok(());
+        }
+
+        let accounts = FreezeAccount {
+            account: self.owner_ata.to_account_info(),
+            mint: self.mint.to_account_info(),
+            authority: self.dao_auth.to_account_info()
+        };
+
+        let seeds = &[
+            &b"auth"[..],
+            &self.config.key().to_bytes()[..],
+            &[self.config.auth_bump],
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            accounts,
+            signer_seeds
+        );
+
+        freeze_account(ctx)
+    }
+
+    // Thaw the member's soulbound tokens once none of them are staked
+    pub fn unlock_tokens(
+        &mut self
+    ) -> Result<()> {
+        if self.stake_state.amount > 0 {
+            return Ok(());
+        }
+
+        let accounts = ThawAccount {
+            account: self.owner_ata.to_account_info(),
+            mint: self.mint.to_account_info(),
+            authority: self.dao_auth.to_account_info()
+        };
+
+        let seeds = &[
+            &b"auth"[..],
+            &self.config.key().to_bytes()[..],
+            &[self.config.auth_bump],
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            accounts,
+            signer_seeds
+        );
+
+        thaw_account(ctx)
+    }
+}
@@ -0,0 +1,66 @@
+use anchor_lang::{prelude::*, system_program::{Transfer, transfer}};
+
+use crate::{state::{config::DaoConfig, MemberRoles, Subsystem, TreasuryType}, errors::DaoError, helpers::validate_treasury};
+
+#[derive(Accounts)]
+#[instruction(selected_treasury: String)]
+pub struct TreasurerPayout<'info> {
+    treasurer: Signer<'info>,
+    #[account(
+        mut,
+        seeds=[b"roles", config.key().as_ref(), treasurer.key().as_ref()],
+        bump = roles.bump
+    )]
+    roles: Account<'info, MemberRoles>,
+    #[account(mut)]
+    ///CHECK: This is safe. It only receives lamports
+    payee: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds=[selected_treasury.as_bytes(), config.key().as_ref()],
+        bump
+    )]
+    treasury: SystemAccount<'info>,
+    #[account(
+        seeds=[b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    config: Account<'info, DaoConfig>,
+    system_program: Program<'info, System>
+}
+
+impl<'info> TreasurerPayout<'info> {
+    pub fn treasurer_payout(
+        &mut self,
+        treasury: String,
+        treasury_bump: u8,
+        amount: u64
+    ) -> Result<()> {
+        self.config.check_not_paused(Subsystem::Execution)?;
+        // Treasurers only pay out of the ops treasury, the rest stays behind a vote
+        require!(validate_treasury(treasury.clone())? == TreasuryType::Ops, DaoError::InvalidTreasury);
+        // Small payouts skip the vote, within the treasurer's allowance
+        self.roles.spend(amount, self.config.role_policy.treasurer_cap)?;
+
+        let accounts = Transfer {
+            from: self.treasury.to_account_info(),
+            to: self.payee.to_account_info()
+        };
+
+        let seeds = &[
+            treasury.as_bytes(),
+            &self.config.key().to_bytes()[..],
+            &[treasury_bump],
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let ctx = CpiContext::new_with_signer(
+            self.system_program.to_account_info(),
+            accounts,
+            signer_seeds
+        );
+
+        transfer(ctx, amount)
+    }
+}
@@ -1,6 +1,6 @@
 use anchor_lang::prelude::*;
 
-use crate::{state::{config::DaoConfig, Proposal, StakeState, VoteState}, errors::DaoError};
+use crate::{state::{config::DaoConfig, Proposal, Reputation, StakeState, Subsystem, VoteState}, errors::DaoError};
 
 #[derive(Accounts)]
 pub struct Unvote<'info> {
@@ -21,13 +21,21 @@ pub struct Unvote<'info> {
     #[account(
         mut,
         close = treasury,
-        seeds=[b"vote", nft_master.key().as_ref(), proposal.key().as_ref()],
+        seeds=[b"vote", owner.key().as_ref(), proposal.key().as_ref()],
         bump = vote.bump
     )]
     vote: Account<'info, VoteState>,
+    // Only removing a vote needs it. Votes cast before reputation existed can still be cleaned up
     #[account(
+        mut,
+        seeds=[b"reputation", config.key().as_ref(), owner.key().as_ref()],
+        bump = reputation.bump
+    )]
+    reputation: Option<Account<'info, Reputation>>,
+    #[account(
+        mut,
         seeds=[b"treasury", config.key().as_ref()],
-        bump = config.treasury_bump
+        bump = config.main_treasury_bump
     )]
     treasury: SystemAccount<'info>,
     #[account(
@@ -52,9 +60,14 @@ impl<'info> Unvote<'info> {
     pub fn remove_vote(
         &mut self
     ) -> Result<()> {
+        self.config.check_proposal_not_paused(&self.proposal.proposal, Subsystem::Voting)?;
         self.proposal.is_open()?;
         self.proposal.check_expiry()?;
-        self.proposal.remove_vote(self.vote.amount, self.vote.choice)?;
+        self.proposal.remove_vote(self.vote.amount, self.vote.weight(), self.vote.choice)?;
+        // Take back the points the vote earned, so voting again doesn't earn them twice
+        let rule = self.config.reputation_rule;
+        let reputation = self.reputation.as_mut().ok_or(DaoError::MissingReputation)?;
+        reputation.remove(rule.vote_points, rule.half_life)?;
         self.stake_state.remove_account()
     }
 }
\ No newline at end of file
@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{config::DaoConfig, MultiSig, Whitelist};
+
+#[derive(Accounts)]
+pub struct UpdateWhitelist<'info> {
+    #[account(
+        seeds=[b"multisig", config.key().as_ref()],
+        bump = multi_sig.multisig_bump
+    )]
+    multi_sig: Account<'info, MultiSig>,
+    #[account(
+        mut,
+        seeds=[b"whitelist", config.key().as_ref()],
+        bump = whitelist.bump
+    )]
+    whitelist: Account<'info, Whitelist>,
+    #[account(
+        seeds=[b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    config: Account<'info, DaoConfig>
+}
+
+impl<'info> UpdateWhitelist<'info> {
+    pub fn update_whitelist(
+        &mut self,
+        mints: Vec<Pubkey>,
+        signers: &[AccountInfo<'info>]
+    ) -> Result<()> {
+        // Multisig signers are passed in as remaining accounts
+        self.multi_sig.check_signers(signers)?;
+        self.whitelist.update(mints)
+    }
+}
@@ -1,6 +1,6 @@
-use anchor_lang::{prelude::*, system_program::Transfer};
+use anchor_lang::prelude::*;
 
-use crate::{state::{config::DaoConfig, Proposal, StakeState, VoteState}, errors::DaoError};
+use crate::{state::{config::DaoConfig, Ballots, Proposal, Reputation, StakeState, Subsystem, VoteState}, errors::DaoError};
 
 #[derive(Accounts)]
 
@@ -22,17 +22,30 @@ pub struct Vote<'info> {
     #[account(
         init,
         payer = owner,
-        seeds=[b"vote", edition.key().as_ref(), proposal.key().as_ref()],
+        seeds=[b"vote", owner.key().as_ref(), proposal.key().as_ref()],
         bump,
         space = VoteState::LEN,
     )]
     vote: Account<'info, VoteState>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        seeds=[b"reputation", config.key().as_ref(), owner.key().as_ref()],
+        bump,
+        space = Reputation::LEN,
+    )]
+    reputation: Account<'info, Reputation>,
+    #[account(
+        seeds=[b"ballots", proposal.key().as_ref()],
+        bump
+    )]
+    ///CHECK: This is safe. It's only read, and only once a ballot has been relayed for the proposal
+    ballots: UncheckedAccount<'info>,
     #[account(
         seeds=[b"config", config.seed.to_le_bytes().as_ref()],
         bump = config.config_bump
     )]
     config: Account<'info, DaoConfig>,
-    edition: UncheckedAccount<'info>,
     system_program: Program<'info, System>
 }
  
@@ -41,8 +54,10 @@ impl<'info> Vote<'info> {
         &mut self,
         amount: u64,
         choice: u8,
-        bump: u8
+        bump: u8,
+        reputation_bump: u8
     ) -> Result<()> {
+        self.config.check_proposal_not_paused(&self.proposal.proposal, Subsystem::Voting)?;
         // Check proposal is open if not tries to initialize
         if self.proposal.is_open().is_err() {
          self.proposal.try_initialize(&self.config)?;
@@ -51,16 +66,30 @@ impl<'info> Vote<'info> {
         self.proposal.check_expiry()?;
         // Ensure vote amount > 0
         require!(amount > 0, DaoError::InvalidVoteAmount);
-        // Add vote to proposal
-        self.proposal.add_vote(amount, choice)?;
-        // Make sure user has staked
+        // A staker whose ballot was already relayed can't vote directly too
+        if self.stake_state.relay_index > 0 && !self.ballots.data_is_empty() {
+            let ballots: Account<Ballots> = Account::try_from(&self.ballots.to_account_info())?;
+            require!(!ballots.has_voted(self.stake_state.relay_index - 1), DaoError::AlreadyVoted);
+        }
+        // Weigh the vote by the voter's reputation before this vote counts toward it
+        let rule = self.config.reputation_rule;
+        self.reputation.init_if_needed(self.owner.key(), reputation_bump)?;
+        self.reputation.decay(rule.half_life)?;
+        let weight = rule.vote_weight(amount, self.reputation.score)?;
+        // Make sure user has staked, before the proposal opened
         self.stake_state.check_stake_amount(amount)?;
+        self.stake_state.check_votable_amount(amount, self.proposal.opened)?;
+        // Add vote to proposal
+        self.proposal.add_vote(amount, weight, choice)?;
         // Add a vote account to the stake state
         self.stake_state.add_account()?;
-        // Initialize vote
+        // Earn reputation for taking part
+        self.reputation.add(rule.vote_points, rule.half_life)?;
+        // Initialize vote with its stake and weight, so removing it takes back what it added
         self.vote.init(
             self.owner.key(),
             amount,
+            weight,
             choice,
             bump
             
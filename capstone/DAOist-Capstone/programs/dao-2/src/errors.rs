@@ -48,4 +48,100 @@ pub enum DaoError {
     InvalidChoicesAmount,
     #[msg("Invalid choice")]
     InvalidChoice,
-}
+    #[msg("Invalid treasury")]
+    InvalidTreasury,
+    #[msg("Invalid multisig keys length")]
+    InvalidKeysLen,
+    #[msg("Invalid multisig signers")]
+    InvalidSigners,
+    #[msg("Invalid instruction data")]
+    InvalidInstructionData,
+    #[msg("Executable proposals are not supported yet")]
+    ExecutableNotSupported,
+    #[msg("Proposal is still in its execution delay")]
+    ExecutionDelay,
+    #[msg("Rage quit window is closed")]
+    RageQuitClosed,
+    #[msg("Only members who voted against a passed treasury proposal can rage quit")]
+    RageQuitNotAllowed,
+    #[msg("Mint is not whitelisted")]
+    MintNotWhitelisted,
+    #[msg("Whitelist is full")]
+    WhitelistFull,
+    #[msg("Invalid proposal account")]
+    InvalidProposalAccount,
+    #[msg("Invalid proposal consumer")]
+    InvalidConsumer,
+    #[msg("Unsupported interface version")]
+    InvalidInterfaceVersion,
+    #[msg("Too many open proposals")]
+    TooManyOpenProposals,
+    #[msg("Invalid open proposal limit")]
+    InvalidProposalLimit,
+    #[msg("This part of the DAO is paused")]
+    Paused,
+    #[msg("Account is already on the current layout")]
+    AccountUpToDate,
+    #[msg("Invalid account type")]
+    InvalidAccountType,
+    #[msg("Proposal can't be optimistic")]
+    OptimisticNotAllowed,
+    #[msg("Invalid ballot")]
+    InvalidBallot,
+    #[msg("Invalid ballot nonce")]
+    InvalidNonce,
+    #[msg("Already voted")]
+    AlreadyVoted,
+    #[msg("Stake is locked by relayed votes")]
+    VotesLocked,
+    #[msg("Governance mint is already in use")]
+    GovernanceMintInUse,
+    #[msg("Invalid reputation rule")]
+    InvalidReputationRule,
+    #[msg("Not enough reputation")]
+    InsufficientReputation,
+    #[msg("Invalid proposal type")]
+    InvalidProposalType,
+    #[msg("Not a sub-DAO of this DAO")]
+    NotSubDao,
+    #[msg("This DAO has been dissolved")]
+    Dissolved,
+    #[msg("Invalid payroll")]
+    InvalidPayroll,
+    #[msg("Invalid payroll status")]
+    InvalidPayrollStatus,
+    #[msg("No payment is due yet")]
+    NothingDue,
+    #[msg("Invalid payee")]
+    InvalidPayee,
+    #[msg("Missing role")]
+    MissingRole,
+    #[msg("Over the spend limit")]
+    OverSpendLimit,
+    #[msg("Invalid payment mint")]
+    InvalidPaymentMint,
+    #[msg("Invalid pricing")]
+    InvalidPricing,
+    #[msg("Price is above the buyer's max")]
+    PriceAboveMax,
+    #[msg("Invalid swap")]
+    InvalidSwap,
+    #[msg("Swap has expired")]
+    SwapExpired,
+    #[msg("Invalid conviction rule")]
+    InvalidConvictionRule,
+    #[msg("Invalid conviction request")]
+    InvalidConvictionRequest,
+    #[msg("Not enough conviction yet")]
+    ConvictionTooLow,
+    #[msg("Pausing again has to wait out the cooldown")]
+    PauseCooldown,
+    #[msg("Account predates versioning and can't be migrated")]
+    UnversionedAccount,
+    #[msg("No relay indexes are left")]
+    TooManyRelayVoters,
+    #[msg("Stake deposited after the proposal opened can't vote on it")]
+    StakeAfterOpen,
+    #[msg("Reputation account is missing")]
+    MissingReputation,
+}
\ No newline at end of file
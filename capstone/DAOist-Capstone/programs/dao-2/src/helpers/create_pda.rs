@@ -0,0 +1,33 @@
+use anchor_lang::{prelude::*, system_program::{Allocate, allocate, Assign, assign, CreateAccount, create_account}};
+
+use super::realloc;
+
+// Create a program account at a PDA, like `init` does. Anyone can send lamports to the
+// address first, so an account that already holds some is topped up instead of created
+pub fn create_pda<'info>(
+    account: &AccountInfo<'info>,
+    space: usize,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]]
+) -> Result<()> {
+    if account.lamports() == 0 {
+        let accounts = CreateAccount {
+            from: payer.clone(),
+            to: account.clone()
+        };
+        let ctx = CpiContext::new_with_signer(system_program.clone(), accounts, signer_seeds);
+        return create_account(ctx, Rent::get()?.minimum_balance(space), space as u64, &crate::ID);
+    }
+
+    let accounts = Allocate {
+        account_to_allocate: account.clone()
+    };
+    allocate(CpiContext::new_with_signer(system_program.clone(), accounts, signer_seeds), space as u64)?;
+    let accounts = Assign {
+        account_to_assign: account.clone()
+    };
+    assign(CpiContext::new_with_signer(system_program.clone(), accounts, signer_seeds), &crate::ID)?;
+    // Only tops up the rent, the account already has its space
+    realloc(account, space, payer, system_program)
+}
@@ -0,0 +1,33 @@
+use anchor_lang::{prelude::*, solana_program::{ed25519_program, instruction::Instruction}};
+
+use crate::errors::DaoError;
+
+// Layout of the Ed25519 precompile's instruction data: a signature count and a padding byte,
+// then seven u16 offsets per signature
+const OFFSETS_START: usize = 2;
+const OFFSETS_LEN: usize = 14;
+// An instruction index of u16::MAX points the precompile at its own instruction data
+const THIS_INSTRUCTION: u16 = u16::MAX;
+
+// The public key and message of each signature the precompile verified.
+// Only signatures whose key and message sit in the precompile instruction itself are accepted.
+pub fn verified_messages(ix: &Instruction) -> Result<Vec<(Pubkey, Vec<u8>)>> {
+    require_keys_eq!(ix.program_id, ed25519_program::ID, DaoError::InvalidBallot);
+    let data = &ix.data;
+    let count = *data.first().ok_or(DaoError::InvalidBallot)? as usize;
+
+    (0..count).map(|i| {
+        let start = OFFSETS_START + i * OFFSETS_LEN;
+        let offsets = data.get(start..start + OFFSETS_LEN).ok_or(DaoError::InvalidBallot)?;
+        let offset = |at: usize| u16::from_le_bytes([offsets[at], offsets[at + 1]]);
+
+        // Signature, public key and message instruction indexes
+        require!([offset(2), offset(6), offset(12)].iter().all(|index| *index == THIS_INSTRUCTION), DaoError::InvalidBallot);
+
+        let key_start = offset(4) as usize;
+        let message_start = offset(8) as usize;
+        let key = data.get(key_start..key_start + 32).ok_or(DaoError::InvalidBallot)?;
+        let message = data.get(message_start..message_start + offset(10) as usize).ok_or(DaoError::InvalidBallot)?;
+        Ok((Pubkey::try_from(key).map_err(|_| DaoError::InvalidBallot)?, message.to_vec()))
+    }).collect()
+}
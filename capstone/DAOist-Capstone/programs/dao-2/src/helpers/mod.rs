@@ -1,3 +1,13 @@
+pub mod create_pda;
+pub mod ed25519;
+pub mod pro_rata;
+pub mod realloc;
+pub mod soulbound;
 pub mod validate_treasury;
 
-use validate_treasury::*;
\ No newline at end of file
+pub use create_pda::*;
+pub use ed25519::*;
+pub use pro_rata::*;
+pub use realloc::*;
+pub use soulbound::*;
+pub use validate_treasury::*;
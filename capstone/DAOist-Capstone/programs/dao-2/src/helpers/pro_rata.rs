@@ -0,0 +1,10 @@
+use anchor_lang::prelude::*;
+use crate::errors::DaoError;
+
+// Share of `balance` owed to `amount` out of `supply`, rounded down
+pub fn pro_rata(balance: u64, amount: u64, supply: u64) -> Result<u64> {
+    let share = (balance as u128)
+        .checked_mul(amount as u128).ok_or(DaoError::Overflow)?
+        .checked_div(supply as u128).ok_or(DaoError::Underflow)?;
+    Ok(share as u64)
+}
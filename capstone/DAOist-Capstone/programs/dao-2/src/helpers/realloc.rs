@@ -0,0 +1,20 @@
+use anchor_lang::{prelude::*, system_program::{Transfer, transfer}};
+
+// Resize an account to `space`, with the payer topping up its rent if it grows
+pub fn realloc<'info>(
+    account: &AccountInfo<'info>,
+    space: usize,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>
+) -> Result<()> {
+    let rent = Rent::get()?.minimum_balance(space).saturating_sub(account.lamports());
+    if rent > 0 {
+        let accounts = Transfer {
+            from: payer.clone(),
+            to: account.clone()
+        };
+        transfer(CpiContext::new(system_program.clone(), accounts), rent)?;
+    }
+    account.realloc(space, true)?;
+    Ok(())
+}
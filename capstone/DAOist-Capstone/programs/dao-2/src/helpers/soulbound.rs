@@ -0,0 +1,12 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::{self, extension::{non_transferable::NonTransferable, BaseStateWithExtensions, StateWithExtensions}};
+
+// Non-transferable Token-2022 mints can't move into a stake vault, so they're staked in place
+pub fn is_soulbound(mint: &AccountInfo) -> Result<bool> {
+    if *mint.owner != spl_token_2022::ID {
+        return Ok(false);
+    }
+    let data = mint.try_borrow_data()?;
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    Ok(mint.get_extension::<NonTransferable>().is_ok())
+}
@@ -1,12 +1,12 @@
 use anchor_lang::prelude::*;
-use crate::{DaoError, state::TreasuryType};
+use crate::{errors::DaoError, state::TreasuryType};
 
-pub fn validate_treasury(treasury: String) -> Result<()> {
-    let _treasury_type = match treasury.as_str() {
+pub fn validate_treasury(treasury: String) -> Result<TreasuryType> {
+    let treasury_type = match treasury.as_str() {
         "treasury" => TreasuryType::Main,
         "dev-treasury" => TreasuryType::Dev,
         "ops-treasury" => TreasuryType::Ops,
         _ => { return err!(DaoError::InvalidTreasury); }
     };
-    Ok(())
-}
\ No newline at end of file
+    Ok(treasury_type)
+}
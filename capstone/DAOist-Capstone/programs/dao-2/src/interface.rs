@@ -0,0 +1,44 @@
+// Read interface for programs that gate their own instructions on DAO decisions.
+// Depend on this crate with the `cpi` (or `no-entrypoint`) feature, check a proposal
+// with `load_passed_proposal` and call `dao_2::cpi::consume_proposal` with `VERSION`
+// to use the decision up.
+use anchor_lang::prelude::*;
+
+use crate::{errors::DaoError, state::{Proposal, ProposalStatus}};
+
+// Bumped whenever the accounts or checks below change meaning
+pub const VERSION: u8 = 1;
+
+// Make sure `proposal` is a real dao-2 proposal of the DAO at `config`
+pub fn load_proposal(
+    proposal: &AccountInfo,
+    config: &Pubkey
+) -> Result<Proposal> {
+    require_keys_eq!(*proposal.owner, crate::ID, DaoError::InvalidProposalAccount);
+    let data = proposal.try_borrow_data()?;
+    let state = Proposal::try_deserialize(&mut &data[..])?;
+
+    let address = Pubkey::create_program_address(
+        &[b"proposal", config.as_ref(), state.id.to_le_bytes().as_ref(), &[state.bump]],
+        &crate::ID
+    ).map_err(|_| DaoError::InvalidProposalAccount)?;
+    require_keys_eq!(address, proposal.key(), DaoError::InvalidProposalAccount);
+
+    Ok(state)
+}
+
+// Status is as last written. Open proposals past their expiry are only finalized
+// by the next instruction that touches them, so a read can't be treated as failed.
+pub fn load_passed_proposal(
+    proposal: &AccountInfo,
+    config: &Pubkey
+) -> Result<Proposal> {
+    let state = load_proposal(proposal, config)?;
+    require!(state.result == ProposalStatus::Succeeded, DaoError::InvalidProposalStatus);
+    Ok(state)
+}
+
+pub fn check_version(version: u8) -> Result<()> {
+    require_eq!(version, VERSION, DaoError::InvalidInterfaceVersion);
+    Ok(())
+}
@@ -3,17 +3,17 @@ use anchor_lang::prelude::*;
 mod contexts;
 use contexts::*;
 mod constants;
-mod state;
-mod errors;
+pub mod state;
+use state::{ConvictionRule, OptimisticRule, PauseFlags, PayrollEntry, PricingMode, ProposalType, QuorumRule, ReputationRule, RolePolicy, TallyRule};
+pub mod errors;
+mod helpers;
+pub mod interface;
 
 declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
 
 #[program]
 pub mod dao_2 {
-    use anchor_lang::accounts::option;
-    use state::VoteType;
-
-    use crate::{errors::DaoError, state::{ProposalType}};
+    use crate::errors::DaoError;
 
     use super::*;
 
@@ -24,31 +24,45 @@ pub mod dao_2 {
         multisig_keys: Vec<Pubkey>,
         min_signers: u8,
         issue_price: u64,
+        pricing: PricingMode,
         proposal_fee: u64,
+        escalate_proposal_fee: bool,
+        max_open_proposals_per_member: u64,
         max_supply: u64,
-        min_quorum: u128,
-        min_threshold: u64,
         max_expiry: u64,
         min_stake: u64,
         min_choices: u8,
         prevoting_period: u64,
+        execution_delay: u64,
+        bounty_rule: TallyRule,
+        executable_rule: TallyRule,
+        vote_rule: TallyRule,
+        optimistic_rule: OptimisticRule,
+        reputation_rule: ReputationRule,
+        role_policy: RolePolicy,
+        conviction_rule: ConvictionRule,
+        quorum_rule: QuorumRule,
+        max_pause_duration: u64,
         multisig_share: u8,
         dev_treasury_share: u8,
-        ops_treasry_share: u8,
+        ops_treasury_share: u8,
         name: String,
         symbol: String,
         uri: String
     ) -> Result<()> {
         ctx.accounts.init(seed, multisig_keys, min_signers, &ctx.bumps, 
-            issue_price, proposal_fee, max_supply, min_quorum, 
-            min_threshold, max_expiry, min_stake, min_choices, prevoting_period,
-            multisig_share, dev_treasury_share, name, symbol, uri
+            issue_price, pricing, proposal_fee, escalate_proposal_fee, max_open_proposals_per_member,
+            max_supply, max_expiry, min_stake,
+            min_choices, prevoting_period, execution_delay,
+            bounty_rule, executable_rule, vote_rule, optimistic_rule, reputation_rule, role_policy, conviction_rule, quorum_rule, max_pause_duration,
+            multisig_share, dev_treasury_share, ops_treasury_share, name, symbol, uri
         )
     }
 
     // Handle token issuance
-    pub fn issue_tokens(ctx: Context<IssueTokens>, name: String, symbol: String, uri: String) -> Result<()> {
-        ctx.accounts.deposit_sol()?;
+    pub fn issue_tokens(ctx: Context<IssueTokens>, name: String, symbol: String, uri: String, max_price: u64) -> Result<()> {
+        // Fails if the price went up past what the buyer agreed to
+        ctx.accounts.deposit(max_price)?;
         ctx.accounts.issue_tokens(name, symbol, uri)
         // Taking name, symbol & uri inputs from the caller for now but this approach is flawed,
         // These parameter must come from the DAO initiator (pending for later...)
@@ -60,6 +74,18 @@ pub mod dao_2 {
     }
 
 
+    // Create the soulbound membership mint that staking uses from then on
+    pub fn init_membership<'info>(ctx: Context<'_, '_, '_, 'info, InitMembership<'info>>) -> Result<()> {
+        // Requires the multisig signers as remaining accounts
+        ctx.accounts.init_membership(*ctx.bumps.get("mint").ok_or(DaoError::BumpError)?, ctx.remaining_accounts)
+    }
+
+    // Mint soulbound membership tokens to a member
+    pub fn issue_membership<'info>(ctx: Context<'_, '_, '_, 'info, IssueMembership<'info>>, amount: u64) -> Result<()> {
+        // Requires the multisig signers as remaining accounts
+        ctx.accounts.issue_membership(amount, ctx.remaining_accounts)
+    }
+
     // Initialize a stake account for adding DAO tokens
     pub fn init_stake(ctx: Context<InitializeStake>) -> Result<()> {
         // Create a stake account
@@ -69,7 +95,7 @@ pub mod dao_2 {
     // Close a stake account when you're done with it
     pub fn close_stake(ctx: Context<CleanupStake>) -> Result<()> {
         // Create a stake account
-        ctx.accounts.cleanup_stake(&ctx.bumps)
+        ctx.accounts.cleanup_stake()
     }
 
     // Stake DAO tokens
@@ -88,15 +114,13 @@ pub mod dao_2 {
     pub fn create_proposal(
         ctx: Context<CreateProposal>, 
         id: u64, 
+        treasury: String,
         name: String, 
         gist: String, 
         proposal: ProposalType,
-        vote_type: VoteType,
-        quorum: u64, 
-        threshold: u64, 
         expiry: u64,
         choices:u8,
-        amount: u64, 
+        optimistic: bool,
     ) -> Result<()> {
         // Pay a proposal fee to DAO treasury
         ctx.accounts.pay_proposal_fee()?;
@@ -104,16 +128,15 @@ pub mod dao_2 {
         // Ensure user has actually got tokens staked and create a new proposal
         ctx.accounts.create_proposal(
             id, 
+            treasury,
             name, 
             gist,
             proposal,
-            vote_type,
-            quorum,
-            threshold,
             expiry,
-            choices, 
-            amount,
-            *ctx.bumps.get("proposal").ok_or(DaoError::BumpError)?
+            choices,
+            optimistic,
+            *ctx.bumps.get("proposal").ok_or(DaoError::BumpError)?,
+            *ctx.bumps.get("reputation").ok_or(DaoError::BumpError)?
         )
     }
 
@@ -122,26 +145,184 @@ pub mod dao_2 {
         ctx: Context<CleanupProposal>,
         treasury: String 
     ) -> Result<()> {
-        // Pay a proposal fee to DAO treasury
-        ctx.accounts.cleanup_proposal(treasury)
+        // Close a failed or consumed proposal
+        ctx.accounts.cleanup_proposal(treasury, *ctx.bumps.get("proposer_reputation").ok_or(DaoError::BumpError)?)
     }
 
-    // Cleanup a proposal
+    // Execute a proposal
     pub fn execute_proposal(
         ctx: Context<CleanupProposal>,
         treasury: String
     ) -> Result<()> {
-        let remaining_accounts = ctx.remaining_accounts;
-        // Pay a proposal fee to DAO treasury
-        ctx.accounts.execute_proposal(treasury, remaining_accounts)
+        // Execute a succeeded proposal from the selected treasury
+        ctx.accounts.execute_proposal(
+            treasury,
+            *ctx.bumps.get("treasury").ok_or(DaoError::BumpError)?,
+            *ctx.bumps.get("proposer_reputation").ok_or(DaoError::BumpError)?
+        )
+    }
+    // Use up a passed decision from another program
+    pub fn consume_proposal(ctx: Context<ConsumeProposal>, version: u8) -> Result<()> {
+        // Only the consumer named in the proposal can do this, once
+        ctx.accounts.consume_proposal(version)
+    }
+
+    // Spawn the sub-DAO a passed `SubDao` proposal allows, funded from the proposal's treasury
+    pub fn spawn_sub_dao(
+        ctx: Context<SpawnSubDao>,
+        treasury: String,
+        seed: u64,
+        multisig_keys: Vec<Pubkey>,
+        min_signers: u8
+    ) -> Result<()> {
+        // The sub-DAO starts with the parent's rules and its own council
+        ctx.accounts.spawn_sub_dao(
+            treasury,
+            *ctx.bumps.get("parent_treasury").ok_or(DaoError::BumpError)?,
+            seed,
+            multisig_keys,
+            min_signers,
+            &ctx.bumps
+        )
+    }
+
+    // Take SOL back from a sub-DAO after a passed `ClawBack` proposal
+    pub fn claw_back(ctx: Context<GovernSubDao>, treasury: String) -> Result<()> {
+        // Paid into the treasury the proposal was created against
+        ctx.accounts.claw_back(treasury)
+    }
+
+    // Shut a sub-DAO down after a passed `Dissolve` proposal
+    pub fn dissolve_sub_dao(ctx: Context<GovernSubDao>, treasury: String) -> Result<()> {
+        // Its main treasury goes back to the parent
+        ctx.accounts.dissolve_sub_dao(treasury)
+    }
+
+    // Draft a payroll for a `Payroll` proposal to activate
+    pub fn create_payroll(
+        ctx: Context<CreatePayroll>,
+        id: u64,
+        treasury: String,
+        period: u64,
+        payments: u64,
+        entries: Vec<PayrollEntry>,
+        replaces: Option<Pubkey>
+    ) -> Result<()> {
+        // Amend a payroll by drafting one that replaces it
+        ctx.accounts.create_payroll(
+            id,
+            treasury,
+            period,
+            payments,
+            entries,
+            replaces,
+            *ctx.bumps.get("payroll").ok_or(DaoError::BumpError)?
+        )
+    }
+
+    // Start a payroll after a passed `Payroll` proposal
+    pub fn activate_payroll(ctx: Context<GovernPayroll>) -> Result<()> {
+        // Terminates the payroll it replaces, if any
+        ctx.accounts.activate_payroll()
+    }
+
+    // Stop a payroll after a passed `TerminatePayroll` proposal
+    pub fn terminate_payroll(ctx: Context<GovernPayroll>) -> Result<()> {
+        // Nothing more is paid from then on
+        ctx.accounts.terminate_payroll()
+    }
+
+    // Pay whatever a payroll has due. Anyone can crank it
+    pub fn pay_payroll<'info>(ctx: Context<'_, '_, '_, 'info, PayPayroll<'info>>, treasury: String) -> Result<()> {
+        // Requires the payees as remaining accounts
+        ctx.accounts.pay_payroll(treasury, *ctx.bumps.get("treasury").ok_or(DaoError::BumpError)?, ctx.remaining_accounts)
+    }
+
+    // Give a member a role after a passed `GrantRole` proposal
+    pub fn grant_role(ctx: Context<GovernRoles>) -> Result<()> {
+        // Creates the member's role account the first time
+        ctx.accounts.grant_role(*ctx.bumps.get("roles").ok_or(DaoError::BumpError)?)
+    }
+
+    // Take a role away after a passed `RevokeRole` proposal
+    pub fn revoke_role(ctx: Context<GovernRoles>) -> Result<()> {
+        // Revoking the treasurer role clears its allowance
+        ctx.accounts.revoke_role(*ctx.bumps.get("roles").ok_or(DaoError::BumpError)?)
+    }
+
+    // Pay out of a treasury without a vote, as a treasurer
+    pub fn treasurer_payout(ctx: Context<TreasurerPayout>, treasury: String, amount: u64) -> Result<()> {
+        // Capped by the role policy and the treasurer's allowance
+        ctx.accounts.treasurer_payout(treasury, *ctx.bumps.get("treasury").ok_or(DaoError::BumpError)?, amount)
+    }
+
+    // Pause the DAO as a guardian, when the role policy allows it
+    pub fn guardian_pause(ctx: Context<GuardianPause>, flags: PauseFlags) -> Result<()> {
+        // Lifts like any other pause
+        ctx.accounts.guardian_pause(flags)
+    }
+
+    // Draft a trade for a `Swap` proposal to approve
+    pub fn create_swap(
+        ctx: Context<CreateSwap>,
+        id: u64,
+        treasury: String,
+        amount_in: u64,
+        min_out: u64,
+        expiration: i64
+    ) -> Result<()> {
+        // The pool and the token bought are fixed by the accounts
+        ctx.accounts.create_swap(id, treasury, amount_in, min_out, expiration, *ctx.bumps.get("order").ok_or(DaoError::BumpError)?)
+    }
+
+    // Sell treasury lamports through the AMM after a passed `Swap` proposal
+    pub fn execute_swap(ctx: Context<ExecuteSwap>, treasury: String) -> Result<()> {
+        // What's bought lands in the treasury's token account
+        ctx.accounts.execute_swap(treasury, *ctx.bumps.get("treasury").ok_or(DaoError::BumpError)?)
+    }
+
+    // Ask the conviction pool for funding, with no expiry
+    pub fn create_conviction(ctx: Context<CreateConviction>, id: u64, amount: u64) -> Result<()> {
+        // Conviction starts building once stake is allocated to it
+        ctx.accounts.create_conviction(id, amount, *ctx.bumps.get("proposal").ok_or(DaoError::BumpError)?)
+    }
+
+    // Put stake behind a conviction proposal
+    pub fn allocate(ctx: Context<Allocate>, amount: u64) -> Result<()> {
+        // Allocated stake stays locked until it's taken back
+        ctx.accounts.allocate(amount, *ctx.bumps.get("allocation").ok_or(DaoError::BumpError)?)
     }
+
+    // Take stake back from a conviction proposal
+    pub fn deallocate(ctx: Context<Deallocate>) -> Result<()> {
+        // Conviction starts fading towards the stake that's left
+        ctx.accounts.deallocate()
+    }
+
+    // Fund a conviction proposal that has crossed its threshold
+    pub fn execute_conviction(ctx: Context<ExecuteConviction>, treasury: String) -> Result<()> {
+        // Pays out of the rule's pool treasury
+        ctx.accounts.execute_conviction(treasury, *ctx.bumps.get("treasury").ok_or(DaoError::BumpError)?)
+    }
+
     // Vote on a proposal with token
     pub fn vote(ctx: Context<Vote>, amount: u64, choice: u8) -> Result<()> {
         // Increment total number of votes in the proposal
-        ctx.accounts.vote(amount, *ctx.bumps.get("vote").ok_or(DaoError::BumpError)?)
+        ctx.accounts.vote(
+            amount,
+            choice,
+            *ctx.bumps.get("vote").ok_or(DaoError::BumpError)?,
+            *ctx.bumps.get("reputation").ok_or(DaoError::BumpError)?
+        )
     }
 
 
+    // Count ballots stakers signed off-chain, verified by the Ed25519 instruction before this one
+    pub fn relay_votes<'info>(ctx: Context<'_, '_, '_, 'info, RelayVotes<'info>>) -> Result<()> {
+        // Requires each voter's stake, vote and reputation accounts as remaining accounts
+        ctx.accounts.relay_votes(ctx.remaining_accounts, *ctx.bumps.get("ballots").ok_or(DaoError::BumpError)?)
+    }
+
     // Close a voting position after a proposal has passed/expired
     pub fn cleanup_vote(ctx: Context<Unvote>) -> Result<()> {
         // Decrement votes for user
@@ -154,22 +335,27 @@ pub mod dao_2 {
         ctx.accounts.remove_vote()
     }
 
-     // Vote on a proposal with NFT
-     pub fn vote_nft(ctx: Context<VoteNft>, amount: u64, choice: u8) -> Result<()> {
-        // Increment total number of votes in the proposal
-         ctx.accounts.vote_nft(amount, *ctx.bumps.get("vote").ok_or(DaoError::BumpError)?)
-         
+    // Leave with a share of the main treasury after voting against a passed proposal
+    pub fn rage_quit<'info>(ctx: Context<'_, '_, '_, 'info, RageQuit<'info>>) -> Result<()> {
+        // Burn the stake and withdraw lamports plus whitelisted tokens
+        ctx.accounts.rage_quit(ctx.remaining_accounts)
     }
 
-    // Close a NFT voting position after a proposal has passed/expired
-    pub fn cleanup_vote_nft(ctx: Context<UnvoteNft>) -> Result<()> {
-        // Decrement votes for user
-        ctx.accounts.cleanup_vote_nft()
+    // Set the SPL tokens members can rage quit with
+    pub fn update_whitelist<'info>(ctx: Context<'_, '_, '_, 'info, UpdateWhitelist<'info>>, mints: Vec<Pubkey>) -> Result<()> {
+        // Requires the multisig signers as remaining accounts
+        ctx.accounts.update_whitelist(mints, ctx.remaining_accounts)
     }
 
-    // Close a voting position in an active proposal
-    pub fn remove_vote_nft(ctx: Context<UnvoteNft>) -> Result<()> {
-        // Decrement votes for user and proposal
-        ctx.accounts.remove_vote_nft()
-    }    
+    // Halt parts of the DAO until the pause expires or an `Unpause` proposal passes
+    pub fn pause<'info>(ctx: Context<'_, '_, '_, 'info, Pause<'info>>, flags: PauseFlags) -> Result<()> {
+        // Requires the multisig signers as remaining accounts
+        ctx.accounts.pause(flags, ctx.remaining_accounts)
+    }
+
+    // Bring an account written by an older version of the program up to the current layout
+    pub fn migrate(ctx: Context<Migrate>) -> Result<()> {
+        // Anyone can do this, the payer covers any extra rent
+        ctx.accounts.migrate()
+    }
 }
\ No newline at end of file
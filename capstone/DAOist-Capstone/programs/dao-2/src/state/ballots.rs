@@ -0,0 +1,77 @@
+use crate::errors::DaoError;
+use anchor_lang::{prelude::*, solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE};
+
+use super::{Versioned, ACCOUNT_VERSION};
+
+// What a staker signs off-chain to vote without sending a transaction
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Ballot {
+    pub proposal: Pubkey, // The proposal account, which also pins the DAO
+    pub choice: u8,
+    pub amount: u64,
+    pub nonce: u64 // Has to match the staker's next relay nonce
+}
+
+// Stakers who already had a ballot relayed for a proposal, one bit per relay index
+#[account]
+#[derive(InitSpace)]
+pub struct Ballots {
+    pub version: u8,
+    pub proposal: Pubkey,
+    pub bump: u8,
+    #[max_len(0)]
+    pub voted: Vec<u8> // Grows with the relay indexes, sized by `space`
+}
+
+// The whole bitmap has to fit in what one instruction can grow an account by
+pub const MAX_RELAY_VOTERS: u64 = ((MAX_PERMITTED_DATA_INCREASE - 8 - Ballots::INIT_SPACE) * 8) as u64;
+
+impl Ballots {
+    pub fn init(
+        &mut self,
+        proposal: Pubkey,
+        bump: u8
+    ) -> Result<()> {
+        self.version = ACCOUNT_VERSION;
+        self.proposal = proposal;
+        self.bump = bump;
+        self.voted = vec![];
+        Ok(())
+    }
+
+    pub fn has_voted(
+        &self,
+        relay_index: u64
+    ) -> bool {
+        let byte = (relay_index / 8) as usize;
+        let bit = 1u8 << (relay_index % 8);
+        self.voted.get(byte).map_or(false, |voted| voted & bit != 0)
+    }
+
+    pub fn record(
+        &mut self,
+        relay_index: u64
+    ) -> Result<()> {
+        require!(!self.has_voted(relay_index), DaoError::AlreadyVoted);
+        let byte = (relay_index / 8) as usize;
+        if self.voted.len() <= byte {
+            self.voted.resize(byte + 1, 0);
+        }
+        self.voted[byte] |= 1u8 << (relay_index % 8);
+        Ok(())
+    }
+}
+
+impl Versioned for Ballots {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn set_version(&mut self, version: u8) {
+        self.version = version;
+    }
+
+    fn space(&self) -> usize {
+        8 + Self::INIT_SPACE + self.voted.len()
+    }
+}
@@ -1,19 +1,31 @@
-use crate::{constants::*, errors::DaoError};
+use crate::{errors::DaoError, helpers::validate_treasury};
 use anchor_lang::prelude::*;
 
+use super::{ConvictionRule, OptimisticRule, PauseFlags, PricingMode, Proposal, ProposalMode, ProposalStatus, ProposalType, QuorumRule, ReputationRule, RolePolicy, Subsystem, TallyRule, Versioned, ACCOUNT_VERSION, MAX_BPS, MAX_RELAY_VOTERS};
+
 #[account]
+#[derive(InitSpace)]
 pub struct DaoConfig {
+    pub version: u8,
     pub seed: u64,
     pub issue_price: u64,
     pub proposal_fee: u64,
+    pub escalate_proposal_fee: bool,
+    pub max_open_proposals_per_member: u64,
     pub max_supply: u64,
-    pub min_quorum: u64,
-    pub min_threshold: u64,
     pub max_expiry: u64,
     pub min_stake: u64,
     pub min_choices: u8,
     pub prevoting_period: u64,
+    pub execution_delay: u64,
     pub proposal_count: u64,
+    pub staked_supply: u64,
+    pub bounty_rule: TallyRule,
+    pub executable_rule: TallyRule,
+    pub vote_rule: TallyRule,
+    pub paused: PauseFlags,
+    pub paused_until: u64, // Pauses lift on their own at this slot
+    pub max_pause_duration: u64,
     pub auth_bump: u8,
     pub config_bump: u8,
     pub mint_bump: u8,
@@ -22,24 +34,59 @@ pub struct DaoConfig {
     pub multisig_share: u8,
     pub main_treasury_share: u8,
     pub dev_treasury_share: u8,
-    pub ops_treasury_share: u8
+    pub ops_treasury_share: u8,
+    pub optimistic_rule: OptimisticRule,
+    pub relay_voters: u64, // Stakers who have had a ballot relayed, each gets the next relay index
+    pub membership_mint: bool, // Staking uses the soulbound membership mint instead of the collection mint
+    pub membership_bump: u8,
+    pub reputation_rule: ReputationRule,
+    pub parent: Pubkey, // The DAO that spawned this one, or the default key for a root DAO
+    pub dissolved: bool, // Dissolved by the parent, for good
+    pub role_policy: RolePolicy,
+    pub payment_mint: Option<Pubkey>, // Sales and proposal fees are paid in this token instead of lamports
+    pub pricing: PricingMode,
+    pub issued: u64, // NFTs sold so far
+    pub conviction_rule: ConvictionRule,
+    pub quorum_rule: QuorumRule,
+    pub average_turnout: u16, // Moving average of the turnout of closed proposals, in bps
+    pub turnout_samples: u64 // Closed proposals folded into the average
+}
+
+// A payment divided between the treasuries
+pub struct PaymentSplit {
+    pub main: u64,
+    pub dev: u64,
+    pub ops: u64,
+    pub multisig: u64
 }
 
 impl DaoConfig {
-    pub const LEN: usize = 8 + 10 * U64_L + 9 * U8_L;
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
 
     pub fn init(
         &mut self,
         seed: u64,
         issue_price: u64,
+        pricing: PricingMode,
         proposal_fee: u64,
+        escalate_proposal_fee: bool,
+        max_open_proposals_per_member: u64,
         max_supply: u64,
-        min_quorum: u64,
-        min_threshold: u64,
         max_expiry: u64,
         min_stake: u64,
         min_choices: u8,
         prevoting_period: u64,
+        execution_delay: u64,
+        bounty_rule: TallyRule,
+        executable_rule: TallyRule,
+        vote_rule: TallyRule,
+        optimistic_rule: OptimisticRule,
+        reputation_rule: ReputationRule,
+        role_policy: RolePolicy,
+        conviction_rule: ConvictionRule,
+        quorum_rule: QuorumRule,
+        payment_mint: Option<Pubkey>,
+        max_pause_duration: u64,
         auth_bump: u8,
         config_bump: u8,
         mint_bump: u8,
@@ -49,17 +96,36 @@ impl DaoConfig {
         dev_treasury_share: u8,
         ops_treasury_share: u8             
     ) -> Result<()> {
+        require_gt!(max_open_proposals_per_member, 0, DaoError::InvalidProposalLimit);
+        bounty_rule.validate()?;
+        executable_rule.validate()?;
+        vote_rule.validate()?;
+        optimistic_rule.validate()?;
+        reputation_rule.validate()?;
+        pricing.validate(issue_price)?;
+        conviction_rule.validate()?;
+        quorum_rule.validate()?;
+
+        self.version = ACCOUNT_VERSION;
         self.seed = seed;
         self.issue_price = issue_price;
         self.proposal_fee = proposal_fee;
+        self.escalate_proposal_fee = escalate_proposal_fee;
+        self.max_open_proposals_per_member = max_open_proposals_per_member;
         self.max_supply = max_supply;
-        self.min_quorum = min_quorum;
-        self.min_threshold = min_threshold;
         self.max_expiry = max_expiry;
         self.min_stake = min_stake;
         self.min_choices = min_choices;
         self.prevoting_period = prevoting_period;
+        self.execution_delay = execution_delay;
         self.proposal_count = 0;
+        self.staked_supply = 0;
+        self.bounty_rule = bounty_rule;
+        self.executable_rule = executable_rule;
+        self.vote_rule = vote_rule;
+        self.paused = PauseFlags::default();
+        self.paused_until = 0;
+        self.max_pause_duration = max_pause_duration;
         self.auth_bump = auth_bump;
         self.config_bump = config_bump;
         self.mint_bump = mint_bump;
@@ -68,6 +134,71 @@ impl DaoConfig {
         self.main_treasury_share = main_treasury_share;
         self.dev_treasury_share = dev_treasury_share;
         self.ops_treasury_share = ops_treasury_share;
+        self.optimistic_rule = optimistic_rule;
+        self.relay_voters = 0;
+        self.membership_mint = false;
+        self.membership_bump = 0;
+        self.reputation_rule = reputation_rule;
+        self.parent = Pubkey::default();
+        self.dissolved = false;
+        self.role_policy = role_policy;
+        self.payment_mint = payment_mint;
+        self.pricing = pricing;
+        self.issued = 0;
+        self.conviction_rule = conviction_rule;
+        self.quorum_rule = quorum_rule;
+        self.average_turnout = 0;
+        self.turnout_samples = 0;
+        Ok(())
+    }
+
+    // A sub-DAO starts out with its parent's rules and none of its state.
+    // It has no collection to sell, so its members stake a membership mint
+    pub fn spawn(
+        &mut self,
+        parent: &DaoConfig,
+        parent_key: Pubkey,
+        seed: u64,
+        auth_bump: u8,
+        config_bump: u8,
+        main_treasury_bump: u8
+    ) -> Result<()> {
+        self.clone_from(parent);
+        self.version = ACCOUNT_VERSION;
+        self.seed = seed;
+        self.proposal_count = 0;
+        self.staked_supply = 0;
+        self.paused = PauseFlags::default();
+        self.paused_until = 0;
+        self.auth_bump = auth_bump;
+        self.config_bump = config_bump;
+        self.mint_bump = 0;
+        self.treasury_bump = 0;
+        self.main_treasury_bump = main_treasury_bump;
+        self.relay_voters = 0;
+        self.membership_mint = false;
+        self.membership_bump = 0;
+        self.parent = parent_key;
+        self.dissolved = false;
+        self.issued = 0;
+        self.average_turnout = 0;
+        self.turnout_samples = 0;
+        Ok(())
+    }
+
+    pub fn check_parent(&self, parent: Pubkey) -> Result<()> {
+        require_keys_eq!(self.parent, parent, DaoError::NotSubDao);
+        Ok(())
+    }
+
+    pub fn check_not_dissolved(&self) -> Result<()> {
+        require!(!self.dissolved, DaoError::Dissolved);
+        Ok(())
+    }
+
+    // Only reachable through the parent's `Dissolve` proposal
+    pub fn dissolve(&mut self) -> Result<()> {
+        self.dissolved = true;
         Ok(())
     }
 
@@ -82,26 +213,173 @@ impl DaoConfig {
     }
 
 
-    
-    pub fn check_min_threshold(&self, threshold: u64) -> Result<()> {
-        require!(self.min_threshold <= threshold, DaoError::InvalidThreshold);
+    pub fn add_proposal(&mut self, id: u64) -> Result<()> {
+        self.proposal_count = self.proposal_count.checked_add(1).ok_or(DaoError::Overflow)?;
+        require!(self.proposal_count == id, DaoError::InvalidProposalSeed);
         Ok(())
     }
 
+    // With escalation on, a member's nth concurrent proposal costs n² times the base fee
+    pub fn proposal_fee_for(&self, open_proposals: u64) -> Result<u64> {
+        if !self.escalate_proposal_fee {
+            return Ok(self.proposal_fee);
+        }
+        let multiplier = open_proposals.checked_add(1).ok_or(DaoError::Overflow)?;
+        let fee = self.proposal_fee
+            .checked_mul(multiplier)
+            .and_then(|fee| fee.checked_mul(multiplier))
+            .ok_or(DaoError::Overflow)?;
+        Ok(fee)
+    }
 
-    pub fn add_proposal(&mut self, id: u64) -> Result<()> {
-        self.proposal_count = self.proposal_count.checked_add(1).ok_or(DaoError::Overflow)?;
-        require!(self.proposal_count == id, DaoError::InvalidProposalSeed);
+    // Price of the next NFT, counted as sold
+    pub fn next_issue_price(&mut self, max_price: u64) -> Result<u64> {
+        let price = self.pricing.price(self.issue_price, self.issued, Clock::get()?.slot)?;
+        require_gte!(max_price, price, DaoError::PriceAboveMax);
+        self.issued = self.issued.checked_add(1).ok_or(DaoError::Overflow)?;
+        Ok(price)
+    }
+
+    // Main, dev and ops get their share rounded down.
+    // The multisig treasury gets its share and whatever the rounding leaves over
+    pub fn split_payment(&self, amount: u64) -> Result<PaymentSplit> {
+        let share = |share: u8| (amount as u128 * share as u128 / 100) as u64;
+        let main = share(self.main_treasury_share);
+        let dev = share(self.dev_treasury_share);
+        let ops = share(self.ops_treasury_share);
+        let multisig = amount
+            .checked_sub(main)
+            .and_then(|amount| amount.checked_sub(dev))
+            .and_then(|amount| amount.checked_sub(ops))
+            .ok_or(DaoError::Overflow)?;
+        Ok(PaymentSplit { main, dev, ops, multisig })
+    }
+
+    // The council can pause right away, but can't push back the deadline of an active pause.
+    // Once a pause is over, the next one has to wait as long as a pause can last
+    pub fn pause(&mut self, flags: PauseFlags) -> Result<()> {
+        let slot = Clock::get()?.slot;
+        if slot < self.paused_until {
+            self.paused = self.paused.merge(flags);
+            return Ok(());
+        }
+        if self.paused_until > 0 {
+            let cooldown_end = self.paused_until.checked_add(self.max_pause_duration).ok_or(DaoError::Overflow)?;
+            require!(slot >= cooldown_end, DaoError::PauseCooldown);
+        }
+        self.paused = flags;
+        self.paused_until = slot.checked_add(self.max_pause_duration).ok_or(DaoError::Overflow)?;
+        Ok(())
+    }
+
+    // Only reachable through an `Unpause` proposal. The cooldown starts from here
+    pub fn unpause(&mut self) -> Result<()> {
+        self.paused = PauseFlags::default();
+        self.paused_until = self.paused_until.min(Clock::get()?.slot);
         Ok(())
     }
 
-    pub fn check_min_quorum(&self, quorum: u64) -> Result<()> {
-        require!(self.min_quorum <= quorum, DaoError::InvalidQuorum);
+    pub fn check_not_paused(&self, subsystem: Subsystem) -> Result<()> {
+        self.check_not_dissolved()?;
+        let paused = self.paused.is_paused(subsystem) && Clock::get()?.slot < self.paused_until;
+        require!(!paused, DaoError::Paused);
+        Ok(())
+    }
+
+    // Unpause proposals have to get through a pause, or it could never be lifted early
+    pub fn check_proposal_not_paused(&self, proposal: &ProposalType, subsystem: Subsystem) -> Result<()> {
+        match proposal {
+            ProposalType::Unpause => self.check_not_dissolved(),
+            _ => self.check_not_paused(subsystem)
+        }
+    }
+
+    pub fn governance_mint_seed(&self) -> &'static [u8] {
+        match self.membership_mint {
+            true => &b"membership"[..],
+            false => &b"mint"[..]
+        }
+    }
+
+    pub fn governance_mint_bump(&self) -> u8 {
+        match self.membership_mint {
+            true => self.membership_bump,
+            false => self.mint_bump
+        }
+    }
+
+    // Stake vaults hold the collection mint, so the switch can only happen before anyone stakes
+    pub fn set_membership_mint(&mut self, bump: u8) -> Result<()> {
+        require!(!self.membership_mint && self.staked_supply == 0, DaoError::GovernanceMintInUse);
+        self.membership_mint = true;
+        self.membership_bump = bump;
         Ok(())
     }
 
+    pub fn add_relay_voter(&mut self) -> Result<u64> {
+        require!(self.relay_voters < MAX_RELAY_VOTERS, DaoError::TooManyRelayVoters);
+        self.relay_voters = self.relay_voters.checked_add(1).ok_or(DaoError::Overflow)?;
+        Ok(self.relay_voters)
+    }
+
+    pub fn add_stake(&mut self, amount: u64) -> Result<()> {
+        self.staked_supply = self.staked_supply.checked_add(amount).ok_or(DaoError::Overflow)?;
+        Ok(())
+    }
+
+    pub fn remove_stake(&mut self, amount: u64) -> Result<()> {
+        self.staked_supply = self.staked_supply.checked_sub(amount).ok_or(DaoError::Underflow)?;
+        Ok(())
+    }
+
+    // The rule a new proposal of this type will be tallied with
+    pub fn tally_rule(&self, proposal: &ProposalType) -> TallyRule {
+        let mut rule = match proposal {
+            ProposalType::Bounty(_, _) => self.bounty_rule,
+            ProposalType::Executable | ProposalType::Consumable(_) | ProposalType::Unpause => self.executable_rule,
+            ProposalType::SubDao(_, _) | ProposalType::ClawBack(_, _) | ProposalType::Dissolve(_) => self.executable_rule,
+            ProposalType::Payroll(_) | ProposalType::TerminatePayroll(_) => self.executable_rule,
+            ProposalType::GrantRole(_, _, _) | ProposalType::RevokeRole(_, _) => self.executable_rule,
+            ProposalType::Swap(_) => self.executable_rule,
+            ProposalType::Vote => self.vote_rule
+        };
+        rule.quorum = self.quorum_rule.quorum(rule.quorum, self.average_turnout, self.turnout_samples);
+        rule
+    }
+
+    // Fold the turnout of a closed proposal into the average. Optimistic proposals
+    // don't ask for votes and proposals that never opened had none to ask for
+    pub fn record_turnout(&mut self, proposal: &Proposal) -> Result<()> {
+        if self.quorum_rule.weight == 0 || proposal.mode != ProposalMode::Standard || proposal.staked_supply == 0 {
+            return Ok(());
+        }
+        require!(proposal.result != ProposalStatus::Open && proposal.result != ProposalStatus::PreVoting, DaoError::InvalidProposalStatus);
+        let turnout = (proposal.stake_voted as u128 * MAX_BPS as u128 / proposal.staked_supply as u128).min(MAX_BPS as u128) as u16;
+        self.average_turnout = self.quorum_rule.average(self.average_turnout, self.turnout_samples, turnout);
+        self.turnout_samples = self.turnout_samples.checked_add(1).ok_or(DaoError::Overflow)?;
+        Ok(())
+    }
+
+    // Optimistic proposals have to be of a type and size the config allows
+    pub fn proposal_mode(&self, proposal: &ProposalType, treasury: String, optimistic: bool) -> Result<ProposalMode> {
+        match optimistic {
+            true => self.optimistic_rule.mode(proposal, validate_treasury(treasury)?),
+            false => Ok(ProposalMode::Standard)
+        }
+    }
+
     pub fn check_max_expiry(&self, expiry: u64) -> Result<()> {
         require!(self.max_expiry >= expiry, DaoError::InvalidExpiry);
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+impl Versioned for DaoConfig {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn set_version(&mut self, version: u8) {
+        self.version = version;
+    }
+}
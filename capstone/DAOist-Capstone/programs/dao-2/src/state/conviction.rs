@@ -0,0 +1,206 @@
+use crate::errors::DaoError;
+use anchor_lang::prelude::*;
+
+use super::{TreasuryType, Versioned, ACCOUNT_VERSION, MAX_BPS};
+
+// Fixed point the share of conviction still to build up is computed in
+const SCALE: u128 = 1_000_000_000_000;
+
+// How conviction builds up and how much a funding request needs
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ConvictionRule {
+    pub pool: TreasuryType, // Conviction proposals are funded from this treasury
+    pub half_life: u64, // Slots it takes conviction to get halfway to the stake behind it. 0 turns conviction voting off
+    pub max_share: u16, // Share of the pool a single request has to stay under, in bps
+    pub min_threshold: u16 // Share of the staked supply a request for nothing would need, in bps
+}
+
+impl ConvictionRule {
+    pub fn validate(&self) -> Result<()> {
+        if self.half_life == 0 {
+            return Ok(());
+        }
+        require!(self.max_share > 0 && self.max_share as u64 <= MAX_BPS, DaoError::InvalidConvictionRule);
+        require!(self.min_threshold as u64 <= MAX_BPS, DaoError::InvalidConvictionRule);
+        Ok(())
+    }
+
+    pub fn check_enabled(&self) -> Result<()> {
+        require!(self.half_life > 0, DaoError::InvalidConvictionRule);
+        Ok(())
+    }
+
+    // Conviction needed to take `requested` out of a pool holding `funds`.
+    // It goes up with the square of how close the request gets to `max_share`
+    pub fn threshold(
+        &self,
+        requested: u64,
+        funds: u64,
+        staked_supply: u64
+    ) -> Result<u64> {
+        require_gt!(funds, 0, DaoError::InvalidConvictionRequest);
+        let share = requested as u128 * MAX_BPS as u128 / funds as u128;
+        let max_share = self.max_share as u128;
+        require!(share < max_share, DaoError::InvalidConvictionRequest);
+        let gap = max_share - share;
+        let threshold = staked_supply as u128
+            * self.min_threshold as u128
+            * max_share
+            * max_share
+            / (MAX_BPS as u128 * gap * gap);
+        Ok(u64::try_from(threshold).unwrap_or(u64::MAX))
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ConvictionProposal {
+    pub version: u8,
+    pub id: u64,
+    pub proposer: Pubkey,
+    pub payee: Pubkey,
+    pub amount: u64, // Lamports requested from the pool
+    pub staked: u64, // Stake allocated to it right now
+    pub conviction: u64,
+    pub updated: u64, // Conviction has been brought up to date to this slot
+    pub executed: bool,
+    pub bump: u8
+}
+
+impl ConvictionProposal {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    pub fn init(
+        &mut self,
+        id: u64,
+        proposer: Pubkey,
+        payee: Pubkey,
+        amount: u64,
+        bump: u8
+    ) -> Result<()> {
+        require_gt!(amount, 0, DaoError::InvalidConvictionRequest);
+        self.version = ACCOUNT_VERSION;
+        self.id = id;
+        self.proposer = proposer;
+        self.payee = payee;
+        self.amount = amount;
+        self.staked = 0;
+        self.conviction = 0;
+        self.updated = Clock::get()?.slot;
+        self.executed = false;
+        self.bump = bump;
+        Ok(())
+    }
+
+    // Conviction closes half of its distance to the allocated stake every half-life,
+    // moving in a straight line within each one
+    pub fn update(
+        &mut self,
+        half_life: u64
+    ) -> Result<()> {
+        let slot = Clock::get()?.slot;
+        let elapsed = slot.saturating_sub(self.updated);
+        let halvings = elapsed / half_life;
+        let remaining = match halvings {
+            0..=63 => {
+                let start = SCALE >> halvings;
+                start - start * (elapsed % half_life) as u128 / (2 * half_life as u128)
+            },
+            _ => 0
+        };
+        let staked = self.staked as i128;
+        let distance = self.conviction as i128 - staked;
+        self.conviction = (staked + distance * remaining as i128 / SCALE as i128) as u64;
+        self.updated = slot;
+        Ok(())
+    }
+
+    pub fn allocate(
+        &mut self,
+        amount: u64,
+        half_life: u64
+    ) -> Result<()> {
+        require!(!self.executed, DaoError::InvalidConvictionRequest);
+        self.update(half_life)?;
+        self.staked = self.staked.checked_add(amount).ok_or(DaoError::Overflow)?;
+        Ok(())
+    }
+
+    pub fn deallocate(
+        &mut self,
+        amount: u64,
+        half_life: u64
+    ) -> Result<()> {
+        self.update(half_life)?;
+        self.staked = self.staked.checked_sub(amount).ok_or(DaoError::Underflow)?;
+        Ok(())
+    }
+
+    // Passes as soon as conviction reaches the threshold for what it asks of the pool
+    pub fn execute(
+        &mut self,
+        rule: &ConvictionRule,
+        funds: u64,
+        staked_supply: u64
+    ) -> Result<()> {
+        require!(!self.executed, DaoError::InvalidConvictionRequest);
+        self.update(rule.half_life)?;
+        let threshold = rule.threshold(self.amount, funds, staked_supply)?;
+        require!(self.conviction > 0 && self.conviction >= threshold, DaoError::ConvictionTooLow);
+        self.executed = true;
+        Ok(())
+    }
+}
+
+// Stake a member has put behind a conviction proposal
+#[account]
+#[derive(InitSpace)]
+pub struct Allocation {
+    pub version: u8,
+    pub owner: Pubkey,
+    pub proposal: Pubkey,
+    pub amount: u64,
+    pub bump: u8
+}
+
+impl Allocation {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    // Returns whether the account is new, so the stake state can count it as open
+    pub fn init_if_needed(
+        &mut self,
+        owner: Pubkey,
+        proposal: Pubkey,
+        bump: u8
+    ) -> Result<bool> {
+        if self.owner == owner {
+            return Ok(false);
+        }
+        self.version = ACCOUNT_VERSION;
+        self.owner = owner;
+        self.proposal = proposal;
+        self.amount = 0;
+        self.bump = bump;
+        Ok(true)
+    }
+}
+
+impl Versioned for ConvictionProposal {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn set_version(&mut self, version: u8) {
+        self.version = version;
+    }
+}
+
+impl Versioned for Allocation {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn set_version(&mut self, version: u8) {
+        self.version = version;
+    }
+}
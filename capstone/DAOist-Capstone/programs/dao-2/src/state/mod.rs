@@ -1,8 +1,34 @@
+pub mod ballots;
 pub mod config;
+pub mod conviction;
+pub mod multisig;
+pub mod optimistic;
+pub mod pause;
+pub mod pricing;
+pub mod payroll;
 pub mod proposal;
+pub mod reputation;
+pub mod roles;
 pub mod stake;
+pub mod swap;
+pub mod tally;
+pub mod version;
 pub mod vote;
+pub mod whitelist;
+pub use ballots::*;
 pub use config::*;
+pub use conviction::*;
+pub use multisig::*;
+pub use optimistic::*;
+pub use pause::*;
+pub use pricing::*;
+pub use payroll::*;
 pub use proposal::*;
+pub use reputation::*;
+pub use roles::*;
 pub use stake::*;
-pub use vote::*;
\ No newline at end of file
+pub use swap::*;
+pub use tally::*;
+pub use version::*;
+pub use vote::*;
+pub use whitelist::*;
@@ -1,10 +1,18 @@
 use anchor_lang::prelude::*;
 
+use crate::{constants::*, errors::DaoError};
+
+use super::{Versioned, ACCOUNT_VERSION};
+
 #[account]
+#[derive(InitSpace)]
 pub struct MultiSig {
+    /// The layout version of this account
+    pub version: u8,
     /// The minimum signers required to execute
     pub min_signers: u8,
-    /// The addresses of the signers
+    /// The addresses of the signers, sized by `space`
+    #[max_len(0)]
     pub keys: Vec<Pubkey>,
     /// The bump of multisig PDA
     pub multisig_bump: u8,
@@ -13,7 +21,27 @@ pub struct MultiSig {
 }
 
 impl MultiSig {
-    pub const FIXED_LEN: usize = 8 + 3 + 4;
+    pub fn space(keys: usize) -> usize {
+        8 + Self::INIT_SPACE + keys * PUBKEY_L
+    }
+
+    // Sort and dedup the signer keys, making sure enough of them can sign
+    pub fn validate_keys(
+        multisig_keys: Vec<Pubkey>,
+        min_signers: u8
+    ) -> Result<Vec<Pubkey>> {
+        require_gte!(u8::MAX as usize, multisig_keys.len(), DaoError::InvalidKeysLen);
+        let mut keys = multisig_keys;
+        keys.sort(); 
+        keys.dedup();
+        // Duplicates can't sign twice, so only distinct keys count toward the signers
+        let keys_len = keys.len() as u8;
+
+        require_gt!(keys_len, 0, DaoError::InvalidKeysLen);
+        require_gte!(keys_len, min_signers, DaoError::InvalidSigners);
+        require_gt!(min_signers, 0, DaoError::InvalidSigners);
+        Ok(keys)
+    }
 
     pub fn init(
         &mut self,
@@ -22,10 +50,37 @@ impl MultiSig {
         multisig_bump: u8,
         multisig_treasury_bump: u8
     ) -> Result<()> {
+        self.version = ACCOUNT_VERSION;
         self.min_signers = min_signers;
         self.keys = keys;
         self.multisig_bump = multisig_bump;
         self.multisig_treasury_bump = multisig_treasury_bump;
         Ok(())
     }
-}
\ No newline at end of file
+
+    // Make sure at least `min_signers` distinct multisig keys signed the transaction
+    pub fn check_signers(
+        &self,
+        signers: &[AccountInfo]
+    ) -> Result<()> {
+        let signed = self.keys.iter()
+            .filter(|key| signers.iter().any(|signer| signer.is_signer && signer.key == *key))
+            .count();
+        require_gte!(signed, self.min_signers as usize, DaoError::InvalidSigners);
+        Ok(())
+    }
+}
+
+impl Versioned for MultiSig {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn set_version(&mut self, version: u8) {
+        self.version = version;
+    }
+
+    fn space(&self) -> usize {
+        MultiSig::space(self.keys.len())
+    }
+}
@@ -0,0 +1,40 @@
+use crate::errors::DaoError;
+use anchor_lang::prelude::*;
+
+use super::{ProposalType, TreasuryType, MAX_BPS};
+
+// Which proposals may pass without a vote, and how much objection blocks them
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct OptimisticRule {
+    pub objection_threshold: u16, // Share of the staked supply that has to vote against to block it, in bps. 0 turns it off
+    pub bounty_treasury: TreasuryType, // Optimistic bounties can only be paid from this treasury
+    pub max_bounty: u64, // Largest optimistic bounty
+    pub allow_vote: bool // Allow optimistic `Vote` proposals
+}
+
+impl OptimisticRule {
+    pub fn validate(&self) -> Result<()> {
+        require!(self.objection_threshold as u64 <= MAX_BPS, DaoError::InvalidThreshold);
+        Ok(())
+    }
+
+    pub fn mode(
+        &self,
+        proposal: &ProposalType,
+        treasury: TreasuryType
+    ) -> Result<ProposalMode> {
+        let allowed = match proposal {
+            ProposalType::Bounty(_, amount) => treasury == self.bounty_treasury && *amount <= self.max_bounty,
+            ProposalType::Vote => self.allow_vote,
+            _ => false
+        };
+        require!(self.objection_threshold > 0 && allowed, DaoError::OptimisticNotAllowed);
+        Ok(ProposalMode::Optimistic { objection_threshold: self.objection_threshold })
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ProposalMode {
+    Standard, // Tallied with the proposal's `TallyRule`
+    Optimistic { objection_threshold: u16 } // Passes at expiry unless enough stake votes against
+}
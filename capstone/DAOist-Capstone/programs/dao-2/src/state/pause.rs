@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Subsystem {
+    Issuance, // Buying DAO NFTs
+    Proposals, // Creating proposals
+    Voting, // Casting and removing votes
+    Execution, // Executing and consuming, anything that acts on a result. Rage quitting stays open
+    Staking // Staking and unstaking
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct PauseFlags {
+    pub issuance: bool,
+    pub proposals: bool,
+    pub voting: bool,
+    pub execution: bool,
+    pub staking: bool
+}
+
+impl PauseFlags {
+    pub fn is_paused(&self, subsystem: Subsystem) -> bool {
+        match subsystem {
+            Subsystem::Issuance => self.issuance,
+            Subsystem::Proposals => self.proposals,
+            Subsystem::Voting => self.voting,
+            Subsystem::Execution => self.execution,
+            Subsystem::Staking => self.staking
+        }
+    }
+
+    // Pausing more subsystems never resumes the ones already paused
+    pub fn merge(&self, other: PauseFlags) -> PauseFlags {
+        PauseFlags {
+            issuance: self.issuance || other.issuance,
+            proposals: self.proposals || other.proposals,
+            voting: self.voting || other.voting,
+            execution: self.execution || other.execution,
+            staking: self.staking || other.staking
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use crate::{constants::*, errors::DaoError};
+use anchor_lang::prelude::*;
+
+use super::{TreasuryType, Versioned, ACCOUNT_VERSION};
+
+// Every payee is paid in the same crank, so they all have to fit in one transaction
+pub const MAX_PAYEES: usize = 16;
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PayrollEntry {
+    pub payee: Pubkey,
+    pub amount: u64 // Lamports per payment
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PayrollStatus {
+    Draft, // Waiting for a `Payroll` proposal to pass
+    Active,
+    Completed,
+    Terminated
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Payroll {
+    pub version: u8,
+    pub id: u64,
+    pub creator: Pubkey,
+    pub treasury: TreasuryType, // Paid from this treasury
+    pub period: u64, // Slots between payments
+    pub payments: u64, // Number of payments to make
+    pub paid: u64, // Payments made so far
+    pub next_payment: u64, // Slot the next payment is due
+    pub status: PayrollStatus,
+    pub replaces: Option<Pubkey>, // Payroll this one terminates when it's activated
+    pub bump: u8,
+    #[max_len(0)]
+    pub entries: Vec<PayrollEntry> // Sized by `space`
+}
+
+impl Payroll {
+    pub fn space(entries: usize) -> usize {
+        8 + Self::INIT_SPACE + entries * (PUBKEY_L + U64_L)
+    }
+
+    pub fn init(
+        &mut self,
+        id: u64,
+        creator: Pubkey,
+        treasury: TreasuryType,
+        period: u64,
+        payments: u64,
+        entries: Vec<PayrollEntry>,
+        replaces: Option<Pubkey>,
+        bump: u8
+    ) -> Result<()> {
+        require!(period > 0 && payments > 0, DaoError::InvalidPayroll);
+        require!(!entries.is_empty() && entries.len() <= MAX_PAYEES, DaoError::InvalidPayroll);
+        require!(entries.iter().all(|entry| entry.amount > 0), DaoError::InvalidPayroll);
+
+        self.version = ACCOUNT_VERSION;
+        self.id = id;
+        self.creator = creator;
+        self.treasury = treasury;
+        self.period = period;
+        self.payments = payments;
+        self.paid = 0;
+        self.next_payment = 0;
+        self.status = PayrollStatus::Draft;
+        self.replaces = replaces;
+        self.bump = bump;
+        self.entries = entries;
+        Ok(())
+    }
+
+    // Payments are made in arrears, so the first one is due a period after activation
+    pub fn activate(&mut self) -> Result<()> {
+        require!(self.status == PayrollStatus::Draft, DaoError::InvalidPayrollStatus);
+        self.status = PayrollStatus::Active;
+        self.next_payment = Clock::get()?.slot.checked_add(self.period).ok_or(DaoError::Overflow)?;
+        Ok(())
+    }
+
+    pub fn terminate(&mut self) -> Result<()> {
+        self.status = PayrollStatus::Terminated;
+        Ok(())
+    }
+
+    // Payments due now, catching up on any periods nobody cranked
+    pub fn due(&self) -> Result<u64> {
+        require!(self.status == PayrollStatus::Active, DaoError::InvalidPayrollStatus);
+        let slot = Clock::get()?.slot;
+        require!(slot >= self.next_payment, DaoError::NothingDue);
+        let periods = (slot - self.next_payment) / self.period + 1;
+        Ok(periods.min(self.payments - self.paid))
+    }
+
+    pub fn record(&mut self, payments: u64) -> Result<()> {
+        self.paid = self.paid.checked_add(payments).ok_or(DaoError::Overflow)?;
+        self.next_payment = payments
+            .checked_mul(self.period)
+            .and_then(|slots| self.next_payment.checked_add(slots))
+            .ok_or(DaoError::Overflow)?;
+        if self.paid == self.payments {
+            self.status = PayrollStatus::Completed;
+        }
+        Ok(())
+    }
+}
+
+impl Versioned for Payroll {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn set_version(&mut self, version: u8) {
+        self.version = version;
+    }
+
+    fn space(&self) -> usize {
+        Payroll::space(self.entries.len())
+    }
+}
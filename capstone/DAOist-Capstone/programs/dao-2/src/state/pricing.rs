@@ -0,0 +1,63 @@
+use crate::errors::DaoError;
+use anchor_lang::prelude::*;
+
+use super::MAX_BPS;
+
+// Fixed point the exponential curve is computed in
+const SCALE: u128 = 1_000_000_000_000;
+
+// How the price of the next NFT is set. `issue_price` is the base price, or the floor of an auction
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PricingMode {
+    Flat, // Always `issue_price`
+    Linear { step: u64 }, // Goes up by `step` with every NFT issued
+    Exponential { growth: u16 }, // Goes up by `growth` bps with every NFT issued
+    DutchAuction { start_price: u64, start: u64, duration: u64 } // Falls from `start_price` to `issue_price` over `duration` slots from `start`
+}
+
+impl PricingMode {
+    pub fn validate(&self, issue_price: u64) -> Result<()> {
+        if let PricingMode::DutchAuction { start_price, duration, .. } = self {
+            require!(*start_price >= issue_price && *duration > 0, DaoError::InvalidPricing);
+        }
+        Ok(())
+    }
+
+    pub fn price(
+        &self,
+        issue_price: u64,
+        issued: u64,
+        slot: u64
+    ) -> Result<u64> {
+        let price = match *self {
+            PricingMode::Flat => Some(issue_price),
+            PricingMode::Linear { step } => step.checked_mul(issued).and_then(|rise| rise.checked_add(issue_price)),
+            PricingMode::Exponential { growth } => {
+                let factor = pow_scaled(SCALE * (MAX_BPS + growth as u64) as u128 / MAX_BPS as u128, issued);
+                factor.and_then(|factor| factor.checked_mul(issue_price as u128))
+                    .and_then(|price| u64::try_from(price / SCALE).ok())
+            },
+            PricingMode::DutchAuction { start_price, start, duration } => {
+                let elapsed = slot.saturating_sub(start).min(duration);
+                let drop = (start_price - issue_price) as u128 * elapsed as u128 / duration as u128;
+                Some(start_price - drop as u64)
+            }
+        };
+        price.ok_or(DaoError::Overflow.into())
+    }
+}
+
+// `base` to the power of `exp`, both in `SCALE` fixed point, by squaring
+fn pow_scaled(mut base: u128, mut exp: u64) -> Option<u128> {
+    let mut result = SCALE;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result.checked_mul(base)? / SCALE;
+        }
+        exp >>= 1;
+        if exp > 0 {
+            base = base.checked_mul(base)? / SCALE;
+        }
+    }
+    Some(result)
+}
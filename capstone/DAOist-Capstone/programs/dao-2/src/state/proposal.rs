@@ -1,70 +1,91 @@
-use crate::{constants::*, errors::DaoError, accounts::Vote};
-use anchor_lang::prelude::*;
+use crate::{constants::*, errors::DaoError, helpers::validate_treasury};
+use anchor_lang::{prelude::*, solana_program::instruction::Instruction};
 
-use super::{ DaoConfig};
+use super::{DaoConfig, ProposalMode, Role, TallyRule, Versioned, ABSTAIN, ACCOUNT_VERSION, AGAINST, MAX_BPS};
 
 #[account]
+#[derive(InitSpace)]
 pub struct Proposal {
+    pub version: u8,
     pub id: u64, // A unique ID. Can be sequential or random.
+    pub proposer: Pubkey, // Whose open proposal count this counts toward
+    #[max_len(32)]
     pub name: String, // A proposal name
+    #[max_len(72)]
     pub gist: String, // 72 bytes (39 bytes + / + 32 char ID)
     pub proposal: ProposalType,
     pub result: ProposalStatus,
-    pub quorum: u8,
-    pub threshold: u64,
-    pub votes: u64,
+    pub tally: TallyRule, // Copied from the config when the proposal is created
+    pub staked_supply: u64, // Snapshot of the staked supply when voting opens
+    pub votes: u64, // Weight of every vote cast
     pub expiry: u64,
     pub choices: u8,
     pub bump: u8,
-    pub created_time: i64,
-    pub vote_counts: Vec<u64>,
-    pub treasury: TreasuryType
+    pub created_time: u64,
+    pub finalized_time: u64, // Start of the execution delay
+    #[max_len(0)]
+    pub vote_counts: Vec<u64>, // Sized by `space`, the weight behind each choice
+    pub treasury: TreasuryType,
+    pub mode: ProposalMode,
+    pub stake_counts: [u64; 3], // Stake behind each tallied choice, which quorum is measured on
+    pub stake_voted: u64, // Stake behind every vote cast
+    pub max_weight_bonus: u16, // Snapshot of the reputation bonus cap when voting opens
+    pub opened: u64 // Slot voting opened in. Stake deposited from then on can't vote
 }
 
 impl Proposal {
-    pub const LEN: usize = 8 + 32 + 72 + ENUM_L * 2 + U8_L * 2 + 3 * U64_L + U8_L;
+    pub fn space(choices: u8) -> usize {
+        8 + Self::INIT_SPACE + choices as usize * U64_L
+    }
+
     pub fn init(
         &mut self,
         id: u64,
+        proposer: Pubkey,
+        treasury: String,
         name: String,
         gist: String,
         proposal: ProposalType,
-        quorum: u8,
-        threshold:u64,
+        tally: TallyRule,
+        mode: ProposalMode,
         expiry: u64,
         choices: u8,
-        bump: u8  
+        bump: u8
     ) -> Result<()> {
         require!(name.len() < 33, DaoError::InvalidName);
         require!(gist.len() < 73, DaoError::InvalidGist);
-        
-        let treasury_type = match treasury.as_str() {
-            "treasury" => TreasuryType::Main,
-            "dev-treasury" => TreasuryType::Dev,
-            "ops-treasury" => TreasuryType::Ops,
-            _ => { return err!(DaoError::InvalidTreasury); }
-        };
+        // Every proposal needs at least the for, against and abstain choices
+        require!(choices as usize > ABSTAIN, DaoError::InvalidChoicesAmount);
+
+        let treasury_type = validate_treasury(treasury)?;
 
+        self.version = ACCOUNT_VERSION;
         self.id = id;
+        self.proposer = proposer;
         self.proposal = proposal;
 /*         self.vote_type = VoteType::SingleChoice; */
         self.name = name;
         self.gist = gist;
         self.result = ProposalStatus::PreVoting;
-        self.quorum = quorum;
-        self.threshold = threshold;
+        self.tally = tally;
+        self.mode = mode;
+        self.staked_supply = 0;
         self.votes = 0;
         self.bump = bump;
         self.expiry = Clock::get()?.slot.checked_add(expiry).ok_or(DaoError::Overflow)?;
         self.choices = choices;
         self.created_time = Clock::get()?.slot;
+        self.finalized_time = 0;
         self.vote_counts = vec![0; choices as usize];
         self.treasury = treasury_type;
+        self.stake_counts = [0; 3];
+        self.stake_voted = 0;
+        self.max_weight_bonus = 0;
+        self.opened = 0;
         Ok(())
 
     }
-}
-/* 
+/*
     pub fn is_single_choice(
     &self
 ) -> Result<()> {
@@ -79,31 +100,65 @@ impl Proposal {
     require!(self.vote_type == VoteType::MultipleChoice, DaoError::InvalidVoteType);
     Ok(())
 } */
-    // transition from PreVoting to Open 
+    // transition from PreVoting to Open
     pub fn try_initialize(
         &mut self,
         config:  &DaoConfig
-    ) { 
-        let required_time = self.created_time + config.prevoting_period;
+    ) -> Result<()> {
+        let required_time = self.created_time.checked_add(config.prevoting_period).ok_or(DaoError::Overflow)?;
         self.is_votable(required_time)?;
-        
-    }
-    
-        pub fn try_finalize(
-            &mut self
-        ) {
-
-
-            //vote_counts[0] = for, vote_counts[1] = abstain
-            
-            let quorum:u128 = (self.votes - self.vote_counts[2]) as u128 * ( self.quorum / 100 ) as u128;
-            quorum = quorum as u64;   
-            if self.votes >= self.threshold && self.vote_counts[0] >= quorum && self.check_expiry().is_ok() {
-                self.result = ProposalStatus::Succeeded
-            } else if self.votes < self.threshold && self.check_expiry().is_err() || self.vote_counts[1] >= quorum {
-                self.result = ProposalStatus::Failed
-            }
+        self.staked_supply = config.staked_supply;
+        self.max_weight_bonus = config.reputation_rule.max_weight_bonus;
+        self.opened = Clock::get()?.slot;
+        Ok(())
+    }
+
+    pub fn try_finalize(
+        &mut self
+    ) -> Result<()> {
+        let expired = self.check_expiry().is_err();
+        let result = match (self.result, self.mode) {
+            // Nobody voted before it expired, which is all an optimistic proposal needs
+            (ProposalStatus::PreVoting, ProposalMode::Standard) if expired => Some(ProposalStatus::Failed),
+            (ProposalStatus::PreVoting, ProposalMode::Optimistic { .. }) if expired => Some(ProposalStatus::Succeeded),
+            (ProposalStatus::Open, ProposalMode::Standard) => self.tally.tally(&self.vote_counts, &self.stake_counts, self.staked_supply, self.outstanding(), expired),
+            (ProposalStatus::Open, ProposalMode::Optimistic { objection_threshold }) => self.tally_objections(objection_threshold, expired),
+            _ => None
+        };
+        if let Some(result) = result {
+            self.result = result;
+            // A proposal finalized by expiry counts from its expiry, however late someone gets to it
+            self.finalized_time = match expired {
+                true => self.expiry,
+                false => Clock::get()?.slot
+            };
+        }
+        Ok(())
+    }
+
+    // Stake that hasn't voted yet, and the most weight it could vote with
+    fn outstanding(
+        &self
+    ) -> (u64, u64) {
+        let stake = self.staked_supply.saturating_sub(self.stake_voted);
+        let weight = stake as u128 * (MAX_BPS + self.max_weight_bonus as u64) as u128 / MAX_BPS as u128;
+        (stake, weight.min(u64::MAX as u128) as u64)
+    }
+
+    // Only stake against counts. Enough of it fails it right away, otherwise it passes at expiry
+    fn tally_objections(
+        &self,
+        objection_threshold: u16,
+        expired: bool
+    ) -> Option<ProposalStatus> {
+        let against = self.stake_counts[AGAINST] as u128;
+        let objected = against > 0 && against * MAX_BPS as u128 >= objection_threshold as u128 * self.staked_supply as u128;
+        match (objected, expired) {
+            (true, _) => Some(ProposalStatus::Failed),
+            (false, true) => Some(ProposalStatus::Succeeded),
+            (false, false) => None
         }
+    }
 
     pub fn check_expiry(
         &mut self
@@ -117,7 +172,7 @@ impl Proposal {
         required_time: u64,
     ) -> Result<()> {
         require!(self.result == ProposalStatus::PreVoting, DaoError::InvalidProposalStatus);
-        require!(Clock::get()?.slot; >= required_time, DaoError::InvalidRequiredTime);
+        require!(Clock::get()?.slot >= required_time, DaoError::InvalidRequiredTime);
         self.result = ProposalStatus::Open;
         Ok(())
     }
@@ -125,7 +180,7 @@ impl Proposal {
 
     pub fn is_open(
         &mut self
-    ) -> Result<()> {   
+    ) -> Result<()> {
         require!(self.result == ProposalStatus::Open, DaoError::InvalidProposalStatus);
         Ok(())
     }
@@ -137,6 +192,31 @@ impl Proposal {
         Ok(())
     }
 
+    // Sub-DAO proposals are carried out by their own instructions, then closed like consumed ones
+    pub fn carry_out(
+        &mut self,
+        delay: u64
+    ) -> Result<()> {
+        self.try_finalize()?;
+        self.is_succeeded()?;
+        self.check_execution_delay(delay)?;
+        self.result = ProposalStatus::Consumed;
+        Ok(())
+    }
+
+    // Mark a passed decision as used up by the program that acted on it
+    pub fn consume(
+        &mut self,
+        authority: Pubkey
+    ) -> Result<()> {
+        match self.proposal {
+            ProposalType::Consumable(consumer) => require_keys_eq!(consumer, authority, DaoError::InvalidConsumer),
+            _ => return err!(DaoError::InvalidConsumer)
+        }
+        self.result = ProposalStatus::Consumed;
+        Ok(())
+    }
+
     pub fn is_failed(
         &self
     ) -> Result<()> {
@@ -144,31 +224,77 @@ impl Proposal {
         Ok(())
     }
 
+    // Members get until the end of the delay to rage quit before the proposal can be executed
+    pub fn check_execution_delay(
+        &self,
+        delay: u64
+    ) -> Result<()> {
+        let executable_time = self.finalized_time.checked_add(delay).ok_or(DaoError::Overflow)?;
+        require!(Clock::get()?.slot >= executable_time, DaoError::ExecutionDelay);
+        Ok(())
+    }
+
+    pub fn check_rage_quit_window(
+        &self,
+        delay: u64
+    ) -> Result<()> {
+        let executable_time = self.finalized_time.checked_add(delay).ok_or(DaoError::Overflow)?;
+        require!(Clock::get()?.slot < executable_time, DaoError::RageQuitClosed);
+        Ok(())
+    }
+
     pub fn add_vote(
         &mut self,
         amount: u64,
+        weight: u64,
         choice : u8,
     ) -> Result<()> {
-        self.try_initialize();
         require!(self.result == ProposalStatus::Open, DaoError::InvalidProposalStatus);
         require!(choice < self.choices, DaoError::InvalidChoice);
-        self.votes = self.votes.checked_add(amount).ok_or(DaoError::Overflow)?;
-        self.vote_counts[choice as usize] = self.vote_counts[choice as usize].checked_add(amount).ok_or(DaoError::Overflow)?; 
-        self.try_finalize();
-        Ok(())
+        // Optimistic proposals can only be objected to
+        if let ProposalMode::Optimistic { .. } = self.mode {
+            require!(choice as usize == AGAINST, DaoError::InvalidChoice);
+        }
+        self.votes = self.votes.checked_add(weight).ok_or(DaoError::Overflow)?;
+        self.vote_counts[choice as usize] = self.vote_counts[choice as usize].checked_add(weight).ok_or(DaoError::Overflow)?;
+        self.stake_voted = self.stake_voted.checked_add(amount).ok_or(DaoError::Overflow)?;
+        if let Some(count) = self.stake_counts.get_mut(choice as usize) {
+            *count = count.checked_add(amount).ok_or(DaoError::Overflow)?;
+        }
+        self.try_finalize()
     }
 
     pub fn remove_vote(
         &mut self,
         amount: u64,
+        weight: u64,
         choice : u8,
     ) -> Result<()> {
         require!(self.result == ProposalStatus::Open, DaoError::InvalidProposalStatus);
-        self.votes = self.votes.checked_sub(amount).ok_or(DaoError::Underflow)?;
-        self.vote_counts[choice as usize] = self.vote_counts[choice as usize].checked_sub(amount).ok_or(DaoError::Underflow)?; 
+        self.votes = self.votes.checked_sub(weight).ok_or(DaoError::Underflow)?;
+        self.vote_counts[choice as usize] = self.vote_counts[choice as usize].checked_sub(weight).ok_or(DaoError::Underflow)?;
+        // Proposals migrated while open have no stake counts for the votes cast before
+        self.stake_voted = self.stake_voted.saturating_sub(amount);
+        if let Some(count) = self.stake_counts.get_mut(choice as usize) {
+            *count = count.saturating_sub(amount);
+        }
         Ok(())
     }
+}
 
+impl Versioned for Proposal {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn set_version(&mut self, version: u8) {
+        self.version = version;
+    }
+
+    fn space(&self) -> usize {
+        Proposal::space(self.choices)
+    }
+}
 
 /* #[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, Debug, PartialEq, Eq)]
 pub enum VoteType {
@@ -176,22 +302,33 @@ pub enum VoteType {
     MultipleChoice,
 }  */
 
-#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Eq)]
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Copy, Clone, PartialEq, Eq)]
 pub enum ProposalType {
     Bounty(Pubkey, u64), // Pay an address some amount of SOL
     Executable, // Sign some kind of instruction(s) with an accounts struct, etc
-    Vote // We just want to know what people think. No money involved
+    Vote, // We just want to know what people think. No money involved
+    Consumable(Pubkey), // Lets another program act on the decision once, signed by this authority
+    Unpause, // Lift an emergency pause before it expires
+    SubDao(Pubkey, u64), // Lets this creator spawn one sub-DAO, funded with some amount of SOL
+    ClawBack(Pubkey, u64), // Take some amount of SOL back from a sub-DAO's main treasury
+    Dissolve(Pubkey), // Shut a sub-DAO down for good and take back its main treasury
+    Payroll(Pubkey), // Start paying a drafted payroll, ending any payroll it replaces
+    TerminatePayroll(Pubkey), // Stop a payroll before its last payment
+    GrantRole(Pubkey, Role, u64), // Give a member a role. The amount is a treasurer's allowance
+    RevokeRole(Pubkey, Role), // Take a role away from a member
+    Swap(Pubkey) // Make the trade in a swap order
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Copy, Clone, Debug, PartialEq, Eq)]
 pub enum ProposalStatus {
     PreVoting,
     Open,
     Succeeded,
-    Failed
+    Failed,
+    Consumed
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Copy, Clone, Debug, PartialEq, Eq)]
 pub enum TreasuryType {
     Main,
     Ops,
@@ -214,14 +351,14 @@ pub struct InstructionAccount {
 
 impl From<InstructionData> for Instruction {
     fn from(instruction: InstructionData) -> Self {
-        Instruction { 
-            program_id: instruction.program_id, 
+        Instruction {
+            program_id: instruction.program_id,
             accounts: instruction.keys.iter().map(|account| AccountMeta {
                 pubkey: account.pubkey,
                 is_signer: account.is_signer,
                 is_writable: account.is_writable
-            }).collect(), 
+            }).collect(),
             data: instruction.data
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,122 @@
+use crate::errors::DaoError;
+use anchor_lang::prelude::*;
+
+use super::{Versioned, ACCOUNT_VERSION, MAX_BPS};
+
+// What members earn reputation for, how fast it fades and what it's worth
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ReputationRule {
+    pub vote_points: u64, // Earned for every vote cast
+    pub pass_points: u64, // Earned by the proposer once their proposal passes and is acted on
+    pub bounty_points: u64, // Earned by the payee when a bounty they didn't propose is paid out
+    pub half_life: u64, // Slots it takes a score to halve. 0 turns decay off
+    pub max_weight_bonus: u16, // Each point adds 1 bps of voting weight, up to this many bps. 0 turns it off
+    pub min_to_propose: u64 // Score needed to create a proposal
+}
+
+impl ReputationRule {
+    pub fn validate(&self) -> Result<()> {
+        // At most double the weight of the stake behind a vote
+        require!(self.max_weight_bonus as u64 <= MAX_BPS, DaoError::InvalidReputationRule);
+        Ok(())
+    }
+
+    // Voting weight of `amount` staked, with the voter's reputation bonus on top
+    pub fn vote_weight(&self, amount: u64, score: u64) -> Result<u64> {
+        let bonus = score.min(self.max_weight_bonus as u64);
+        let weight = (amount as u128)
+            .checked_mul((MAX_BPS + bonus) as u128)
+            .ok_or(DaoError::Overflow)?
+            / MAX_BPS as u128;
+        u64::try_from(weight).map_err(|_| DaoError::Overflow.into())
+    }
+
+    pub fn check_can_propose(&self, score: u64) -> Result<()> {
+        require_gte!(score, self.min_to_propose, DaoError::InsufficientReputation);
+        Ok(())
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Reputation {
+    pub version: u8,
+    pub owner: Pubkey,
+    pub score: u64,
+    pub updated: u64, // Decay has been applied up to this slot
+    pub bump: u8
+}
+
+impl Reputation {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    // Reputation accounts are created the first time a member needs one
+    pub fn init_if_needed(
+        &mut self,
+        owner: Pubkey,
+        bump: u8
+    ) -> Result<()> {
+        if self.owner == owner {
+            return Ok(());
+        }
+        self.version = ACCOUNT_VERSION;
+        self.owner = owner;
+        self.score = 0;
+        self.updated = Clock::get()?.slot;
+        self.bump = bump;
+        Ok(())
+    }
+
+    // Halve the score for every full half-life since the last update
+    pub fn decay(
+        &mut self,
+        half_life: u64
+    ) -> Result<()> {
+        let slot = Clock::get()?.slot;
+        if half_life == 0 {
+            self.updated = slot;
+            return Ok(());
+        }
+        let halvings = slot.saturating_sub(self.updated) / half_life;
+        self.score = match halvings {
+            0..=63 => self.score >> halvings,
+            _ => 0
+        };
+        // Keep the part of a half-life that hasn't passed yet
+        self.updated = self.updated
+            .checked_add(halvings * half_life)
+            .ok_or(DaoError::Overflow)?;
+        Ok(())
+    }
+
+    pub fn add(
+        &mut self,
+        points: u64,
+        half_life: u64
+    ) -> Result<()> {
+        self.decay(half_life)?;
+        self.score = self.score.checked_add(points).ok_or(DaoError::Overflow)?;
+        Ok(())
+    }
+
+    // Points may have partly decayed already, so this takes back at most what's left
+    pub fn remove(
+        &mut self,
+        points: u64,
+        half_life: u64
+    ) -> Result<()> {
+        self.decay(half_life)?;
+        self.score = self.score.saturating_sub(points);
+        Ok(())
+    }
+}
+
+impl Versioned for Reputation {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn set_version(&mut self, version: u8) {
+        self.version = version;
+    }
+}
@@ -0,0 +1,137 @@
+use crate::errors::DaoError;
+use anchor_lang::prelude::*;
+
+use super::{ProposalType, Versioned, ACCOUNT_VERSION};
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Role {
+    Proposer, // Can create proposals when the policy requires it
+    Treasurer, // Can pay out small amounts without a vote, up to an allowance
+    Guardian // Can pause the DAO like the council
+}
+
+// What roles let members do without a vote
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RolePolicy {
+    pub proposer_required: bool, // Only proposers can create proposals
+    pub treasurer_cap: u64, // Largest payout a treasurer can make at once from the ops treasury. 0 turns treasurer payouts off
+    pub guardian_pause: bool // Guardians can pause without the council
+}
+
+impl RolePolicy {
+    // Anyone can ask to become a proposer, or nobody could ever be granted the role
+    pub fn check_can_propose(
+        &self,
+        owner: Pubkey,
+        proposal: &ProposalType,
+        roles: Option<&MemberRoles>
+    ) -> Result<()> {
+        match proposal {
+            _ if !self.proposer_required => Ok(()),
+            ProposalType::GrantRole(member, Role::Proposer, _) if *member == owner => Ok(()),
+            _ => roles.ok_or(DaoError::MissingRole)?.check(Role::Proposer)
+        }
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct MemberRoles {
+    pub version: u8,
+    pub member: Pubkey,
+    pub proposer: bool,
+    pub treasurer: bool,
+    pub guardian: bool,
+    pub allowance: u64, // What's left of the treasurer's spend limit
+    pub bump: u8
+}
+
+impl MemberRoles {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    // Role accounts are created by the first proposal that grants one
+    pub fn init_if_needed(
+        &mut self,
+        member: Pubkey,
+        bump: u8
+    ) -> Result<()> {
+        if self.member == member {
+            return Ok(());
+        }
+        self.version = ACCOUNT_VERSION;
+        self.member = member;
+        self.proposer = false;
+        self.treasurer = false;
+        self.guardian = false;
+        self.allowance = 0;
+        self.bump = bump;
+        Ok(())
+    }
+
+    // Granting the treasurer role again resets the allowance
+    pub fn grant(
+        &mut self,
+        role: Role,
+        allowance: u64
+    ) -> Result<()> {
+        match role {
+            Role::Proposer => self.proposer = true,
+            Role::Treasurer => {
+                self.treasurer = true;
+                self.allowance = allowance;
+            },
+            Role::Guardian => self.guardian = true
+        }
+        Ok(())
+    }
+
+    pub fn revoke(
+        &mut self,
+        role: Role
+    ) -> Result<()> {
+        match role {
+            Role::Proposer => self.proposer = false,
+            Role::Treasurer => {
+                self.treasurer = false;
+                self.allowance = 0;
+            },
+            Role::Guardian => self.guardian = false
+        }
+        Ok(())
+    }
+
+    pub fn check(
+        &self,
+        role: Role
+    ) -> Result<()> {
+        let held = match role {
+            Role::Proposer => self.proposer,
+            Role::Treasurer => self.treasurer,
+            Role::Guardian => self.guardian
+        };
+        require!(held, DaoError::MissingRole);
+        Ok(())
+    }
+
+    // A payout has to be under the policy's cap and comes out of the allowance
+    pub fn spend(
+        &mut self,
+        amount: u64,
+        cap: u64
+    ) -> Result<()> {
+        self.check(Role::Treasurer)?;
+        require!(amount <= cap, DaoError::OverSpendLimit);
+        self.allowance = self.allowance.checked_sub(amount).ok_or(DaoError::OverSpendLimit)?;
+        Ok(())
+    }
+}
+
+impl Versioned for MemberRoles {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn set_version(&mut self, version: u8) {
+        self.version = version;
+    }
+}
@@ -1,19 +1,30 @@
-use crate::{constants::*, errors::DaoError};
+use crate::errors::DaoError;
 use anchor_lang::prelude::*;
 
+use super::{Versioned, ACCOUNT_VERSION};
+
 #[account]
+#[derive(InitSpace)]
 pub struct StakeState {
+    pub version: u8,
     pub owner: Pubkey,
     pub amount: u64,
     pub accounts: u64,
+    pub open_proposals: u64,
     pub updated: u64,
     pub vault_bump: u8,
     pub auth_bump: u8,
     pub state_bump: u8,
+    pub relay_index: u64, // 1 based position in the relayed ballots bitmaps, 0 until the first relayed ballot
+    pub nonce: u64, // The next relayed ballot has to carry this nonce
+    pub relayed_until: u64, // Relayed votes keep the stake locked until this slot
+    pub allocated: u64, // Stake put behind conviction proposals, which can't add up to more than `amount`
+    pub deposits: [u64; 2], // Stake deposited in the last two slots anything was deposited in, newest last
+    pub deposit_slots: [u64; 2] // Proposals opened in or before these slots can't count those deposits
 }
 
 impl StakeState {
-    pub const LEN: usize = 8 + PUBKEY_L + 3 * U64_L + 3 * U8_L;
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
 
     pub fn init(
         &mut self,  
@@ -22,9 +33,17 @@ impl StakeState {
         vault_bump: u8,
         auth_bump: u8
     ) -> Result<()> {
+        self.version = ACCOUNT_VERSION;
         self.owner = owner;
         self.amount = 0;
         self.accounts = 0;
+        self.open_proposals = 0;
+        self.relay_index = 0;
+        self.nonce = 0;
+        self.relayed_until = 0;
+        self.allocated = 0;
+        self.deposits = [0; 2];
+        self.deposit_slots = [0; 2];
         self.state_bump = state_bump;
         self.vault_bump = vault_bump;
         self.auth_bump = auth_bump;
@@ -33,19 +52,63 @@ impl StakeState {
 
     pub fn stake(
         &mut self,
-        amount: u64
+        amount: u64,
+        max_expiry: u64
     ) -> Result<()> {
-        self.amount.checked_add(amount).ok_or(DaoError::Overflow)?;
+        self.amount = self.amount.checked_add(amount).ok_or(DaoError::Overflow)?;
+        self.add_deposit(amount, max_expiry)?;
         self.update()
     }
 
+    fn add_deposit(&mut self, amount: u64, max_expiry: u64) -> Result<()> {
+        let slot = Clock::get()?.slot;
+        // Every proposal opened by a deposit's slot has expired once `max_expiry` has passed
+        for (deposit, deposit_slot) in self.deposits.iter_mut().zip(self.deposit_slots) {
+            if deposit_slot.saturating_add(max_expiry) <= slot {
+                *deposit = 0;
+            }
+        }
+        if self.deposit_slots[1] != slot {
+            // Only two slots are kept apart, so the older deposit waits as long as the newer one
+            self.deposits = [self.deposits[0].checked_add(self.deposits[1]).ok_or(DaoError::Overflow)?, 0];
+            self.deposit_slots = [self.deposit_slots[1], slot];
+        }
+        self.deposits[1] = self.deposits[1].checked_add(amount).ok_or(DaoError::Overflow)?;
+        Ok(())
+    }
+
+    // Take stake off the newest deposits first
+    fn remove_deposits(&mut self, mut amount: u64) {
+        for deposit in self.deposits.iter_mut().rev() {
+            let removed = amount.min(*deposit);
+            *deposit -= removed;
+            amount -= removed;
+        }
+    }
+
     pub fn unstake(
         &mut self,
         amount: u64
     ) -> Result<()> {
         self.check_accounts()?;
+        self.check_relayed()?;
         self.check_slot()?; // Don't allow staking and unstaking in the same slot
         self.amount = self.amount.checked_sub(amount).ok_or(DaoError::Underflow)?;
+        self.remove_deposits(amount);
+        self.update()
+    }
+
+    // Give up the stake behind an objecting vote when rage quitting, closing the last open vote account
+    pub fn forfeit(&mut self, amount: u64) -> Result<()> {
+        self.remove_account()?;
+        self.check_accounts()?;
+        self.check_relayed()?;
+        self.amount = self.amount.checked_sub(amount).ok_or(DaoError::Underflow)?;
+        // Stake put behind conviction proposals can't be forfeited
+        require_gte!(self.amount, self.allocated, DaoError::InsufficientStake);
+        // The forfeited stake could vote, so it's what was held before the deposits that goes
+        let deposited = self.deposits[0].saturating_add(self.deposits[1]);
+        self.remove_deposits(deposited.saturating_sub(self.amount));
         self.update()
     }
 
@@ -59,6 +122,36 @@ impl StakeState {
         Ok(())
     }
 
+    // Use up a nonce for a ballot signed off-chain and lock the stake until the proposal expires
+    pub fn relay(&mut self, nonce: u64, expiry: u64) -> Result<()> {
+        require!(self.nonce == nonce, DaoError::InvalidNonce);
+        self.nonce = self.nonce.checked_add(1).ok_or(DaoError::Overflow)?;
+        self.relayed_until = self.relayed_until.max(expiry);
+        Ok(())
+    }
+
+    pub fn allocate(&mut self, amount: u64) -> Result<()> {
+        self.allocated = self.allocated.checked_add(amount).ok_or(DaoError::Overflow)?;
+        require_gte!(self.amount, self.allocated, DaoError::InsufficientStake);
+        Ok(())
+    }
+
+    pub fn deallocate(&mut self, amount: u64) -> Result<()> {
+        self.allocated = self.allocated.checked_sub(amount).ok_or(DaoError::Underflow)?;
+        Ok(())
+    }
+
+    pub fn add_proposal(&mut self, max_open_proposals: u64) -> Result<()> {
+        require!(self.open_proposals < max_open_proposals, DaoError::TooManyOpenProposals);
+        self.open_proposals = self.open_proposals.checked_add(1).ok_or(DaoError::Overflow)?;
+        Ok(())
+    }
+
+    pub fn remove_proposal(&mut self) -> Result<()> {
+        self.open_proposals = self.open_proposals.checked_sub(1).ok_or(DaoError::Underflow)?;
+        Ok(())
+    }
+
     // This might be convenient later, but comment out for now
     // pub fn remove_accounts(&mut self, amount: u64) -> Result<()> {
     //     self.accounts.checked_sub(amount).ok_or(DaoError::Underflow)
@@ -81,6 +174,18 @@ impl StakeState {
         Ok(())
     }
 
+    // Relayed votes have no vote account to close, so they lock the stake until their proposals expire
+    pub fn check_relayed(&self) -> Result<()> {
+        require!(Clock::get()?.slot >= self.relayed_until, DaoError::VotesLocked);
+        Ok(())
+    }
+
+    // Open proposals still need this account to be closed out
+    pub fn check_proposals(&mut self) -> Result<()> {
+        require!(self.open_proposals == 0, DaoError::AccountsOpen);
+        Ok(())
+    }
+
     // Ensure staked amount > 0
     pub fn check_stake(&mut self) -> Result<()> {
         require!(self.amount > 0, DaoError::InsufficientStake);
@@ -92,4 +197,23 @@ impl StakeState {
         require!(self.amount >= amount, DaoError::InsufficientStake);
         Ok(())
     }
-}
\ No newline at end of file
+
+    // Stake deposited in or after the slot a proposal opened in can't vote on it
+    pub fn check_votable_amount(&self, amount: u64, opened: u64) -> Result<()> {
+        let votable = self.deposits.iter().zip(self.deposit_slots)
+            .filter(|(_, slot)| *slot >= opened)
+            .fold(self.amount, |votable, (deposit, _)| votable.saturating_sub(*deposit));
+        require!(votable >= amount, DaoError::StakeAfterOpen);
+        Ok(())
+    }
+}
+
+impl Versioned for StakeState {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn set_version(&mut self, version: u8) {
+        self.version = version;
+    }
+}
@@ -0,0 +1,71 @@
+use crate::errors::DaoError;
+use anchor_lang::prelude::*;
+
+use super::{TreasuryType, Versioned, ACCOUNT_VERSION};
+
+// A trade a `Swap` proposal approves, fixed before anyone votes on it
+#[account]
+#[derive(InitSpace)]
+pub struct SwapOrder {
+    pub version: u8,
+    pub id: u64,
+    pub creator: Pubkey,
+    pub treasury: TreasuryType, // Sells lamports from this treasury
+    pub pool: Pubkey, // AMM pool config to trade through
+    pub mint_out: Pubkey, // Token the treasury buys
+    pub amount_in: u64, // Lamports to sell
+    pub min_out: u64, // Least the trade may return
+    pub expiration: i64, // Unix timestamp the trade has to happen by
+    pub executed: bool,
+    pub bump: u8
+}
+
+impl SwapOrder {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    pub fn init(
+        &mut self,
+        id: u64,
+        creator: Pubkey,
+        treasury: TreasuryType,
+        pool: Pubkey,
+        mint_out: Pubkey,
+        amount_in: u64,
+        min_out: u64,
+        expiration: i64,
+        bump: u8
+    ) -> Result<()> {
+        require!(amount_in > 0 && min_out > 0, DaoError::InvalidSwap);
+        require!(expiration > Clock::get()?.unix_timestamp, DaoError::SwapExpired);
+
+        self.version = ACCOUNT_VERSION;
+        self.id = id;
+        self.creator = creator;
+        self.treasury = treasury;
+        self.pool = pool;
+        self.mint_out = mint_out;
+        self.amount_in = amount_in;
+        self.min_out = min_out;
+        self.expiration = expiration;
+        self.executed = false;
+        self.bump = bump;
+        Ok(())
+    }
+
+    pub fn execute(&mut self) -> Result<()> {
+        require!(!self.executed, DaoError::InvalidSwap);
+        require!(Clock::get()?.unix_timestamp <= self.expiration, DaoError::SwapExpired);
+        self.executed = true;
+        Ok(())
+    }
+}
+
+impl Versioned for SwapOrder {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn set_version(&mut self, version: u8) {
+        self.version = version;
+    }
+}
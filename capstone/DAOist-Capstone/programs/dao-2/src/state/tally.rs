@@ -0,0 +1,150 @@
+use crate::errors::DaoError;
+use anchor_lang::prelude::*;
+
+use super::ProposalStatus;
+
+// The first three choices of every proposal are tallied, any further choices are just recorded
+pub const FOR: usize = 0;
+pub const AGAINST: usize = 1;
+pub const ABSTAIN: usize = 2;
+
+pub const MAX_BPS: u64 = 10_000;
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AbstainRule {
+    Quorum, // Abstain counts toward quorum but not toward the threshold
+    Against, // Abstain counts toward quorum and as a vote against
+    Ignored // Abstain is recorded but counts for nothing
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TallyRule {
+    pub quorum: u16, // Share of the staked supply that has to take part, in bps
+    pub threshold: u16, // Share of the counted votes that has to be for, in bps. Ties fail
+    pub abstain: AbstainRule,
+    pub early_finalize: bool // Finalize before expiry once the outstanding stake can't change the result
+}
+
+// Lets quorums follow a moving average of recent turnout instead of staying where they were set
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct QuorumRule {
+    pub weight: u16, // Weight of the latest turnout in the average, in bps. 0 keeps every quorum fixed
+    pub ratio: u16, // Quorum as a share of the average turnout, in bps
+    pub min_quorum: u16, // Bounds the derived quorum stays within, in bps
+    pub max_quorum: u16
+}
+
+impl QuorumRule {
+    pub fn validate(&self) -> Result<()> {
+        if self.weight == 0 {
+            return Ok(());
+        }
+        require!(self.weight as u64 <= MAX_BPS, DaoError::InvalidQuorum);
+        require!(self.min_quorum <= self.max_quorum, DaoError::InvalidQuorum);
+        require!(self.max_quorum as u64 <= MAX_BPS, DaoError::InvalidQuorum);
+        Ok(())
+    }
+
+    // The first turnout seeds the average, later ones move it by `weight`
+    pub fn average(
+        &self,
+        average: u16,
+        samples: u64,
+        turnout: u16
+    ) -> u16 {
+        if samples == 0 {
+            return turnout;
+        }
+        let weight = self.weight as u64;
+        ((turnout as u64 * weight + average as u64 * (MAX_BPS - weight)) / MAX_BPS) as u16
+    }
+
+    // Rules keep their own quorum until there's a turnout to go by
+    pub fn quorum(
+        &self,
+        fixed: u16,
+        average: u16,
+        samples: u64
+    ) -> u16 {
+        if self.weight == 0 || samples == 0 {
+            return fixed;
+        }
+        let derived = (average as u64 * self.ratio as u64 / MAX_BPS) as u16;
+        derived.clamp(self.min_quorum, self.max_quorum)
+    }
+}
+
+impl TallyRule {
+    pub fn validate(&self) -> Result<()> {
+        require!(self.quorum as u64 <= MAX_BPS, DaoError::InvalidQuorum);
+        // A proposal needs more than `threshold`, so a 100% threshold could never pass
+        require!((self.threshold as u64) < MAX_BPS, DaoError::InvalidThreshold);
+        Ok(())
+    }
+
+    // Returns the final status, or None while the result can still change.
+    // Quorum is measured on the stake behind the votes and approval on their weight.
+    // `outstanding` is the stake that hasn't voted yet and the most weight it could add
+    pub fn tally(
+        &self,
+        vote_counts: &[u64],
+        stake_counts: &[u64; 3],
+        staked_supply: u64,
+        outstanding: (u64, u64),
+        expired: bool
+    ) -> Option<ProposalStatus> {
+        let (yes, no, abstain) = (
+            vote_counts[FOR] as u128,
+            vote_counts[AGAINST] as u128,
+            vote_counts[ABSTAIN] as u128
+        );
+        let (yes_stake, no_stake, abstain_stake) = (
+            stake_counts[FOR] as u128,
+            stake_counts[AGAINST] as u128,
+            stake_counts[ABSTAIN] as u128
+        );
+        let supply = staked_supply as u128;
+
+        if expired {
+            let passed = self.quorum_met(yes_stake, no_stake, abstain_stake, supply) && self.approved(yes, no, abstain);
+            return Some(self.result(passed));
+        }
+        if !self.early_finalize {
+            return None;
+        }
+
+        // Staked tokens that haven't voted yet could still land on either side
+        let (outstanding_stake, outstanding_weight) = (outstanding.0 as u128, outstanding.1 as u128);
+
+        if self.quorum_met(yes_stake, no_stake, abstain_stake, supply) && self.approved(yes, no + outstanding_weight, abstain) {
+            Some(ProposalStatus::Succeeded)
+        } else if !(self.quorum_met(yes_stake + outstanding_stake, no_stake, abstain_stake, supply) && self.approved(yes + outstanding_weight, no, abstain)) {
+            Some(ProposalStatus::Failed)
+        } else {
+            None
+        }
+    }
+
+    fn result(&self, passed: bool) -> ProposalStatus {
+        match passed {
+            true => ProposalStatus::Succeeded,
+            false => ProposalStatus::Failed
+        }
+    }
+
+    fn quorum_met(&self, yes: u128, no: u128, abstain: u128, supply: u128) -> bool {
+        let turnout = match self.abstain {
+            AbstainRule::Ignored => yes + no,
+            _ => yes + no + abstain
+        };
+        turnout * MAX_BPS as u128 >= self.quorum as u128 * supply
+    }
+
+    fn approved(&self, yes: u128, no: u128, abstain: u128) -> bool {
+        let counted = match self.abstain {
+            AbstainRule::Against => yes + no + abstain,
+            _ => yes + no
+        };
+        counted > 0 && yes * MAX_BPS as u128 > self.threshold as u128 * counted
+    }
+}
@@ -0,0 +1,19 @@
+use anchor_lang::{prelude::*, Discriminator};
+
+// Layout version written into every account. Bump it whenever a layout changes.
+// New fields are appended, and all zeroes has to be a valid value for them, so
+// `migrate` can read an old account by padding it with zeroes. A field added
+// anywhere else has to be listed in `migrate` with the version that added it.
+// Accounts written before the version byte existed can't be migrated.
+pub const ACCOUNT_VERSION: u8 = 14;
+
+pub trait Versioned: AccountSerialize + AccountDeserialize + Discriminator + Space {
+    fn version(&self) -> u8;
+
+    fn set_version(&mut self, version: u8);
+
+    // Space the current layout needs for this account
+    fn space(&self) -> usize {
+        8 + Self::INIT_SPACE
+    }
+}
@@ -1,29 +1,53 @@
-use crate::{constants::*, errors::DaoError, accounts::Vote};
 use anchor_lang::prelude::*;
 
+use super::{Versioned, ACCOUNT_VERSION};
+
 #[account]
+#[derive(InitSpace)]
 pub struct VoteState {
+    pub version: u8,
     pub owner: Pubkey,
-    pub amount: u64,
+    pub amount: u64, // Stake behind the vote
     pub choice: u8,
-    pub bump: u8
+    pub bump: u8,
+    pub weight: u64 // What the vote counts for, with the voter's reputation bonus
 }
 
 impl VoteState {
-    pub const LEN: usize = 8 + 1 + PUBKEY_L + U64_L + 2 * U8_L;
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
 
     pub fn init(
         &mut self,
         owner: Pubkey,
         amount: u64,
+        weight: u64,
         choice: u8,
         bump: u8,
     ) -> Result<()> {
+        self.version = ACCOUNT_VERSION;
         self.owner = owner;
         self.amount = amount;
         self.choice = choice;
         self.bump = bump;
+        self.weight = weight;
         Ok(())
     }
+
+    // Votes cast before weights were stored on their own counted their amount
+    pub fn weight(&self) -> u64 {
+        match self.weight {
+            0 => self.amount,
+            weight => weight
+        }
+    }
 }
 
+impl Versioned for VoteState {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn set_version(&mut self, version: u8) {
+        self.version = version;
+    }
+}
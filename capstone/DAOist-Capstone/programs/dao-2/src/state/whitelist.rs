@@ -0,0 +1,58 @@
+use crate::errors::DaoError;
+use anchor_lang::prelude::*;
+
+use super::{Versioned, ACCOUNT_VERSION};
+
+#[account]
+#[derive(InitSpace)]
+pub struct Whitelist {
+    pub version: u8,
+    #[max_len(10)] // MAX_MINTS
+    pub mints: Vec<Pubkey>, // SPL tokens in the main treasury that members can rage quit with
+    pub bump: u8
+}
+
+impl Whitelist {
+    pub const MAX_MINTS: usize = 10;
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    pub fn init(
+        &mut self,
+        bump: u8
+    ) -> Result<()> {
+        self.version = ACCOUNT_VERSION;
+        self.mints = vec![];
+        self.bump = bump;
+        Ok(())
+    }
+
+    pub fn update(
+        &mut self,
+        mints: Vec<Pubkey>
+    ) -> Result<()> {
+        let mut mints = mints;
+        mints.sort();
+        mints.dedup();
+        require_gte!(Self::MAX_MINTS, mints.len(), DaoError::WhitelistFull);
+        self.mints = mints;
+        Ok(())
+    }
+
+    pub fn check_mint(
+        &self,
+        mint: &Pubkey
+    ) -> Result<()> {
+        require!(self.mints.contains(mint), DaoError::MintNotWhitelisted);
+        Ok(())
+    }
+}
+
+impl Versioned for Whitelist {
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn set_version(&mut self, version: u8) {
+        self.version = version;
+    }
+}
@@ -0,0 +1,1404 @@
+#![allow(dead_code)]
+
+use anchor_lang::{
+    solana_program::{account_info::AccountInfo, entrypoint::ProgramResult},
+    system_program, AccountDeserialize, AnchorSerialize, InstructionData, ToAccountMetas,
+};
+use anchor_spl::{
+    associated_token::{self, get_associated_token_address, get_associated_token_address_with_program_id},
+    token::spl_token,
+    token_2022::spl_token_2022::{self, extension::StateWithExtensions},
+};
+use dao_2::{
+    errors::DaoError,
+    state::{
+        AbstainRule, Ballot, ConvictionRule, OptimisticRule, PauseFlags, PayrollEntry, PricingMode, ProposalType, QuorumRule, ReputationRule, RolePolicy,
+        TallyRule, TreasuryType,
+    },
+};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    clock::Clock,
+    ed25519_program,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    native_token::LAMPORTS_PER_SOL,
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    sysvar,
+    transaction::{Transaction, TransactionError},
+};
+
+// Anchor's entrypoint ties the accounts slice and its contents to the same lifetime,
+// which the native processor signature can't express, so hand it a leaked copy.
+fn process_dao(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    dao_2::entry(program_id, accounts, data)
+}
+
+fn process_amm(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    anchor_amm_2023::entry(program_id, accounts, data)
+}
+
+// Local stand-in for the Metaplex program. It accepts every instruction without
+// touching any account, which is enough for the metadata CPIs made on issuance.
+fn process_metadata_stand_in(_program_id: &Pubkey, _accounts: &[AccountInfo], _data: &[u8]) -> ProgramResult {
+    Ok(())
+}
+
+pub async fn start() -> ProgramTestContext {
+    let mut program_test = ProgramTest::new("dao_2", dao_2::ID, processor!(process_dao));
+    program_test.add_program(
+        "mpl_token_metadata",
+        mpl_token_metadata::ID,
+        processor!(process_metadata_stand_in),
+    );
+    program_test.add_program("anchor_amm_2023", anchor_amm_2023::ID, processor!(process_amm));
+    program_test.start_with_context().await
+}
+
+pub async fn process(
+    context: &mut ProgramTestContext,
+    ixs: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), BanksClientError> {
+    let blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    let tx = Transaction::new_signed_with_payer(ixs, Some(&context.payer.pubkey()), &all_signers, blockhash);
+    context.banks_client.process_transaction(tx).await
+}
+
+pub fn assert_dao_error(result: Result<(), BanksClientError>, error: DaoError) {
+    let code = u32::from(error);
+    match result.expect_err("transaction should have failed").unwrap() {
+        TransactionError::InstructionError(_, InstructionError::Custom(actual)) => {
+            assert_eq!(actual, code, "expected {:?}", error)
+        }
+        other => panic!("expected {:?}, got {:?}", error, other),
+    }
+}
+
+pub async fn slot(context: &mut ProgramTestContext) -> u64 {
+    context.banks_client.get_sysvar::<Clock>().await.unwrap().slot
+}
+
+// Move the bank forward by `slots` slots
+pub async fn warp(context: &mut ProgramTestContext, slots: u64) {
+    let current = slot(context).await;
+    context.warp_to_slot(current + slots).unwrap();
+}
+
+// Wait for a new blockhash so an identical transaction isn't rejected as a duplicate
+pub async fn refresh_blockhash(context: &mut ProgramTestContext) {
+    context.get_new_latest_blockhash().await.unwrap();
+}
+
+pub async fn lamports(context: &mut ProgramTestContext, address: Pubkey) -> u64 {
+    context.banks_client.get_balance(address).await.unwrap()
+}
+
+pub async fn account<T: AccountDeserialize>(context: &mut ProgramTestContext, address: Pubkey) -> T {
+    let account = context.banks_client.get_account(address).await.unwrap().expect("account not found");
+    T::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+pub async fn exists(context: &mut ProgramTestContext, address: Pubkey) -> bool {
+    context.banks_client.get_account(address).await.unwrap().is_some()
+}
+
+pub async fn token_amount(context: &mut ProgramTestContext, address: Pubkey) -> u64 {
+    let account = context.banks_client.get_account(address).await.unwrap().expect("token account not found");
+    spl_token::state::Account::unpack(&account.data).unwrap().amount
+}
+
+// 10% turnout, more than half of for + against, finalized as soon as the outcome is settled
+pub const MAJORITY: TallyRule = TallyRule {
+    quorum: 1_000,
+    threshold: 5_000,
+    abstain: AbstainRule::Quorum,
+    early_finalize: true,
+};
+
+// Ops treasury bounties up to 0.1 SOL pass unless 20% of the staked supply objects
+pub const OPS_BOUNTIES: OptimisticRule = OptimisticRule {
+    objection_threshold: 2_000,
+    bounty_treasury: TreasuryType::Ops,
+    max_bounty: LAMPORTS_PER_SOL / 10,
+    allow_vote: false,
+};
+
+// Reputation is tracked but earns nothing and counts for nothing
+pub const NO_REPUTATION: ReputationRule = ReputationRule {
+    vote_points: 0,
+    pass_points: 0,
+    bounty_points: 0,
+    half_life: 0,
+    max_weight_bonus: 0,
+    min_to_propose: 0,
+};
+
+// Roles are granted but let nobody skip a vote
+pub const NO_ROLES: RolePolicy = RolePolicy { proposer_required: false, treasurer_cap: 0, guardian_pause: false };
+
+// Conviction voting is turned off
+pub const NO_CONVICTION: ConvictionRule =
+    ConvictionRule { pool: TreasuryType::Main, half_life: 0, max_share: 0, min_threshold: 0 };
+
+// Every rule keeps the quorum it was set up with
+pub const FIXED_QUORUM: QuorumRule = QuorumRule { weight: 0, ratio: 0, min_quorum: 0, max_quorum: 0 };
+
+pub struct DaoParams {
+    pub multisig_keys: Vec<Pubkey>,
+    pub min_signers: u8,
+    pub issue_price: u64,
+    pub pricing: PricingMode,
+    pub proposal_fee: u64,
+    pub escalate_proposal_fee: bool,
+    pub max_open_proposals_per_member: u64,
+    pub max_supply: u64,
+    pub max_expiry: u64,
+    pub min_stake: u64,
+    pub min_choices: u8,
+    pub prevoting_period: u64,
+    pub execution_delay: u64,
+    pub bounty_rule: TallyRule,
+    pub executable_rule: TallyRule,
+    pub vote_rule: TallyRule,
+    pub optimistic_rule: OptimisticRule,
+    pub reputation_rule: ReputationRule,
+    pub role_policy: RolePolicy,
+    pub conviction_rule: ConvictionRule,
+    pub quorum_rule: QuorumRule,
+    pub payment_mint: Option<Pubkey>,
+    pub max_pause_duration: u64,
+    pub multisig_share: u8,
+    pub dev_treasury_share: u8,
+    pub ops_treasury_share: u8,
+}
+
+impl Default for DaoParams {
+    fn default() -> Self {
+        Self {
+            multisig_keys: vec![Pubkey::new_unique()],
+            min_signers: 1,
+            issue_price: LAMPORTS_PER_SOL,
+            pricing: PricingMode::Flat,
+            proposal_fee: LAMPORTS_PER_SOL / 100,
+            escalate_proposal_fee: false,
+            max_open_proposals_per_member: 2,
+            max_supply: 1_000,
+            max_expiry: 1_000,
+            min_stake: 1,
+            min_choices: 3,
+            prevoting_period: 10,
+            execution_delay: 10,
+            bounty_rule: MAJORITY,
+            executable_rule: MAJORITY,
+            vote_rule: MAJORITY,
+            optimistic_rule: OPS_BOUNTIES,
+            reputation_rule: NO_REPUTATION,
+            role_policy: NO_ROLES,
+            conviction_rule: NO_CONVICTION,
+            quorum_rule: FIXED_QUORUM,
+            payment_mint: None,
+            max_pause_duration: 100,
+            multisig_share: 10,
+            dev_treasury_share: 10,
+            ops_treasury_share: 10,
+        }
+    }
+}
+
+pub struct ProposalParams {
+    pub id: u64,
+    pub treasury: String,
+    pub name: String,
+    pub gist: String,
+    pub proposal: ProposalType,
+    pub expiry: u64,
+    pub choices: u8,
+    pub optimistic: bool,
+}
+
+impl ProposalParams {
+    pub fn bounty(id: u64, payee: Pubkey, amount: u64) -> Self {
+        Self {
+            id,
+            treasury: "treasury".to_string(),
+            name: "Bounty".to_string(),
+            gist: "gist".to_string(),
+            proposal: ProposalType::Bounty(payee, amount),
+            expiry: 100,
+            choices: 3,
+            optimistic: false,
+        }
+    }
+
+    pub fn optimistic_bounty(id: u64, payee: Pubkey, amount: u64) -> Self {
+        Self {
+            treasury: "ops-treasury".to_string(),
+            optimistic: true,
+            ..Self::bounty(id, payee, amount)
+        }
+    }
+}
+
+pub struct Dao {
+    pub seed: u64,
+    pub config: Pubkey,
+    pub auth: Pubkey,
+    pub main_treasury: Pubkey,
+    pub dev_treasury: Pubkey,
+    pub ops_treasury: Pubkey,
+    pub mint: Pubkey,
+    pub membership: Pubkey,
+    // Mint and token program that stake is held in
+    pub stake_mint: Pubkey,
+    pub stake_token_program: Pubkey,
+    pub multi_sig: Pubkey,
+    pub multisig_treasury: Pubkey,
+    pub whitelist: Pubkey,
+    // Token that sales and proposal fees are paid in, if not lamports
+    pub payment_mint: Option<Pubkey>,
+}
+
+impl Dao {
+    pub fn new(seed: u64) -> Self {
+        let config = pda(&[b"config", &seed.to_le_bytes()]);
+        let multi_sig = pda(&[b"multisig", config.as_ref()]);
+        Self {
+            seed,
+            config,
+            auth: pda(&[b"auth", config.as_ref()]),
+            main_treasury: pda(&[b"treasury", config.as_ref()]),
+            dev_treasury: pda(&[b"dev-treasury", config.as_ref()]),
+            ops_treasury: pda(&[b"ops-treasury", config.as_ref()]),
+            mint: pda(&[b"mint", config.as_ref()]),
+            membership: pda(&[b"membership", config.as_ref()]),
+            stake_mint: pda(&[b"mint", config.as_ref()]),
+            stake_token_program: spl_token::ID,
+            multi_sig,
+            multisig_treasury: pda(&[b"multisig-treasury", multi_sig.as_ref()]),
+            whitelist: pda(&[b"whitelist", config.as_ref()]),
+            payment_mint: None,
+        }
+    }
+
+    // Stake in the soulbound membership mint from now on
+    pub fn use_membership(&mut self) {
+        self.stake_mint = self.membership;
+        self.stake_token_program = spl_token_2022::ID;
+    }
+
+    // Associated token account of `owner` for the payment mint, if there is one
+    pub fn payment_ata(&self, owner: &Pubkey) -> Option<Pubkey> {
+        self.payment_mint.map(|mint| get_associated_token_address(owner, &mint))
+    }
+
+    pub fn stake_owner_ata(&self, owner: &Pubkey) -> Pubkey {
+        get_associated_token_address_with_program_id(owner, &self.stake_mint, &self.stake_token_program)
+    }
+
+    pub fn treasury(&self, name: &str) -> Pubkey {
+        pda(&[name.as_bytes(), self.config.as_ref()])
+    }
+
+    pub fn stake_state(&self, owner: &Pubkey) -> Pubkey {
+        pda(&[b"stake", self.config.as_ref(), owner.as_ref()])
+    }
+
+    pub fn stake_vault(&self, owner: &Pubkey) -> Pubkey {
+        pda(&[b"vault", self.config.as_ref(), owner.as_ref()])
+    }
+
+    pub fn stake_auth(&self, owner: &Pubkey) -> Pubkey {
+        pda(&[b"auth", self.config.as_ref(), owner.as_ref()])
+    }
+
+    pub fn reputation(&self, owner: &Pubkey) -> Pubkey {
+        pda(&[b"reputation", self.config.as_ref(), owner.as_ref()])
+    }
+
+    pub fn roles(&self, member: &Pubkey) -> Pubkey {
+        pda(&[b"roles", self.config.as_ref(), member.as_ref()])
+    }
+
+    pub fn payroll(&self, creator: &Pubkey, id: u64) -> Pubkey {
+        pda(&[b"payroll", self.config.as_ref(), creator.as_ref(), &id.to_le_bytes()])
+    }
+
+    pub fn proposal(&self, id: u64) -> Pubkey {
+        pda(&[b"proposal", self.config.as_ref(), &id.to_le_bytes()])
+    }
+
+    pub fn vote_state(&self, owner: &Pubkey, proposal: &Pubkey) -> Pubkey {
+        pda(&[b"vote", owner.as_ref(), proposal.as_ref()])
+    }
+
+    pub fn ballots(&self, id: u64) -> Pubkey {
+        pda(&[b"ballots", self.proposal(id).as_ref()])
+    }
+
+    pub fn initialize(&self, initializer: &Pubkey, params: &DaoParams) -> Instruction {
+        let (metadata, _) = mpl_token_metadata::pda::find_metadata_account(&self.mint);
+        let (edition, _) = mpl_token_metadata::pda::find_master_edition_account(&self.mint);
+        Instruction {
+            program_id: dao_2::ID,
+            accounts: dao_2::accounts::Initialize {
+                initializer: *initializer,
+                auth: self.auth,
+                main_treasury: self.main_treasury,
+                collection: self.mint,
+                token: get_associated_token_address(&self.auth, &self.mint),
+                metadata,
+                edition,
+                config: self.config,
+                multi_sig: self.multi_sig,
+                multisig_treasury: self.multisig_treasury,
+                whitelist: self.whitelist,
+                payment_mint: params.payment_mint,
+                mpl_program: mpl_token_metadata::ID,
+                token_program: spl_token::ID,
+                associated_token_program: associated_token::ID,
+                system_program: system_program::ID,
+                rent_program: sysvar::rent::ID,
+            }
+            .to_account_metas(None),
+            data: dao_2::instruction::Initialize {
+                seed: self.seed,
+                multisig_keys: params.multisig_keys.clone(),
+                min_signers: params.min_signers,
+                issue_price: params.issue_price,
+                pricing: params.pricing,
+                proposal_fee: params.proposal_fee,
+                escalate_proposal_fee: params.escalate_proposal_fee,
+                max_open_proposals_per_member: params.max_open_proposals_per_member,
+                max_supply: params.max_supply,
+                max_expiry: params.max_expiry,
+                min_stake: params.min_stake,
+                min_choices: params.min_choices,
+                prevoting_period: params.prevoting_period,
+                execution_delay: params.execution_delay,
+                bounty_rule: params.bounty_rule,
+                executable_rule: params.executable_rule,
+                vote_rule: params.vote_rule,
+                optimistic_rule: params.optimistic_rule,
+                reputation_rule: params.reputation_rule,
+                role_policy: params.role_policy,
+                conviction_rule: params.conviction_rule,
+                quorum_rule: params.quorum_rule,
+                max_pause_duration: params.max_pause_duration,
+                multisig_share: params.multisig_share,
+                dev_treasury_share: params.dev_treasury_share,
+                ops_treasury_share: params.ops_treasury_share,
+                name: "DAOist".to_string(),
+                symbol: "DAO".to_string(),
+                uri: "https://example.com/dao.json".to_string(),
+            }
+            .data(),
+        }
+    }
+
+    pub fn issue_tokens(&self, buyer: &Pubkey, nft: &Pubkey, max_price: u64) -> Instruction {
+        let (metadata, _) = mpl_token_metadata::pda::find_metadata_account(nft);
+        let (edition, _) = mpl_token_metadata::pda::find_master_edition_account(nft);
+        Instruction {
+            program_id: dao_2::ID,
+            accounts: dao_2::accounts::IssueTokens {
+                initializer: *buyer,
+                auth: self.auth,
+                main_treasury: self.main_treasury,
+                dev_treasury: self.dev_treasury,
+                ops_treasury: self.ops_treasury,
+                multisig_treasury: self.multisig_treasury,
+                multi_sig: self.multi_sig,
+                nft: *nft,
+                token: get_associated_token_address(buyer, nft),
+                metadata,
+                edition,
+                collection: self.mint,
+                config: self.config,
+                payment_mint: self.payment_mint,
+                payer_payment: self.payment_ata(buyer),
+                main_payment: self.payment_ata(&self.main_treasury),
+                dev_payment: self.payment_ata(&self.dev_treasury),
+                ops_payment: self.payment_ata(&self.ops_treasury),
+                multisig_payment: self.payment_ata(&self.multisig_treasury),
+                mpl_program: mpl_token_metadata::ID,
+                token_program: spl_token::ID,
+                associated_token_program: associated_token::ID,
+                system_program: system_program::ID,
+                rent_program: sysvar::rent::ID,
+            }
+            .to_account_metas(None),
+            data: dao_2::instruction::IssueTokens {
+                name: "DAOist #1".to_string(),
+                symbol: "DAO".to_string(),
+                uri: "https://example.com/1.json".to_string(),
+                max_price,
+            }
+            .data(),
+        }
+    }
+
+    pub fn init_stake(&self, owner: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: dao_2::ID,
+            accounts: dao_2::accounts::InitializeStake {
+                owner: *owner,
+                owner_ata: self.stake_owner_ata(owner),
+                stake_ata: self.stake_vault(owner),
+                stake_auth: self.stake_auth(owner),
+                mint: self.stake_mint,
+                stake_state: self.stake_state(owner),
+                config: self.config,
+                token_program: self.stake_token_program,
+                associated_token_program: associated_token::ID,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: dao_2::instruction::InitStake {}.data(),
+        }
+    }
+
+    fn stake_accounts(&self, owner: &Pubkey) -> Vec<anchor_lang::prelude::AccountMeta> {
+        dao_2::accounts::Stake {
+            owner: *owner,
+            owner_ata: self.stake_owner_ata(owner),
+            stake_ata: self.stake_vault(owner),
+            auth: self.stake_auth(owner),
+            dao_auth: self.auth,
+            mint: self.stake_mint,
+            stake_state: self.stake_state(owner),
+            config: self.config,
+            token_program: self.stake_token_program,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None)
+    }
+
+    pub fn stake(&self, owner: &Pubkey, amount: u64) -> Instruction {
+        Instruction {
+            program_id: dao_2::ID,
+            accounts: self.stake_accounts(owner),
+            data: dao_2::instruction::StakeTokens { amount }.data(),
+        }
+    }
+
+    pub fn unstake(&self, owner: &Pubkey, amount: u64) -> Instruction {
+        Instruction {
+            program_id: dao_2::ID,
+            accounts: self.stake_accounts(owner),
+            data: dao_2::instruction::UnstakeTokens { amount }.data(),
+        }
+    }
+
+    pub fn create_proposal(&self, owner: &Pubkey, params: &ProposalParams) -> Instruction {
+        self.create_proposal_with_roles(owner, params, None)
+    }
+
+    pub fn create_proposal_with_roles(&self, owner: &Pubkey, params: &ProposalParams, roles: Option<Pubkey>) -> Instruction {
+        let treasury = self.treasury(&params.treasury);
+        Instruction {
+            program_id: dao_2::ID,
+            accounts: dao_2::accounts::CreateProposal {
+                owner: *owner,
+                stake_state: self.stake_state(owner),
+                proposal: self.proposal(params.id),
+                reputation: self.reputation(owner),
+                roles,
+                treasury,
+                config: self.config,
+                payment_mint: self.payment_mint,
+                owner_payment: self.payment_ata(owner),
+                treasury_payment: self.payment_ata(&treasury),
+                token_program: self.payment_mint.map(|_| spl_token::ID),
+                associated_token_program: self.payment_mint.map(|_| associated_token::ID),
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: dao_2::instruction::CreateProposal {
+                id: params.id,
+                treasury: params.treasury.clone(),
+                name: params.name.clone(),
+                gist: params.gist.clone(),
+                proposal: params.proposal,
+                expiry: params.expiry,
+                choices: params.choices,
+                optimistic: params.optimistic,
+            }
+            .data(),
+        }
+    }
+
+    pub fn vote(&self, owner: &Pubkey, id: u64, amount: u64, choice: u8) -> Instruction {
+        let proposal = self.proposal(id);
+        Instruction {
+            program_id: dao_2::ID,
+            accounts: dao_2::accounts::Vote {
+                owner: *owner,
+                stake_state: self.stake_state(owner),
+                proposal,
+                vote: self.vote_state(owner, &proposal),
+                reputation: self.reputation(owner),
+                ballots: self.ballots(id),
+                config: self.config,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: dao_2::instruction::Vote { amount, choice }.data(),
+        }
+    }
+
+    fn unvote_accounts(&self, owner: &Pubkey, id: u64) -> Vec<anchor_lang::prelude::AccountMeta> {
+        let proposal = self.proposal(id);
+        dao_2::accounts::Unvote {
+            owner: *owner,
+            stake_state: self.stake_state(owner),
+            proposal,
+            vote: self.vote_state(owner, &proposal),
+            reputation: Some(self.reputation(owner)),
+            treasury: self.main_treasury,
+            config: self.config,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None)
+    }
+
+    pub fn cleanup_vote(&self, owner: &Pubkey, id: u64) -> Instruction {
+        Instruction {
+            program_id: dao_2::ID,
+            accounts: self.unvote_accounts(owner, id),
+            data: dao_2::instruction::CleanupVote {}.data(),
+        }
+    }
+
+    pub fn remove_vote(&self, owner: &Pubkey, id: u64) -> Instruction {
+        Instruction {
+            program_id: dao_2::ID,
+            accounts: self.unvote_accounts(owner, id),
+            data: dao_2::instruction::RemoveVote {}.data(),
+        }
+    }
+
+    fn cleanup_proposal_accounts(
+        &self,
+        initializer: &Pubkey,
+        proposer: &Pubkey,
+        payee: &Pubkey,
+        id: u64,
+        treasury: &str,
+        payee_reputation: Option<Pubkey>,
+    ) -> Vec<anchor_lang::prelude::AccountMeta> {
+        dao_2::accounts::CleanupProposal {
+            initializer: *initializer,
+            payee: *payee,
+            proposal: self.proposal(id),
+            proposer_stake: self.stake_state(proposer),
+            payee_reputation,
+            proposer_reputation: self.reputation(proposer),
+            treasury: self.treasury(treasury),
+            config: self.config,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None)
+    }
+
+    pub fn cleanup_proposal(&self, initializer: &Pubkey, proposer: &Pubkey, payee: &Pubkey, id: u64, treasury: &str) -> Instruction {
+        Instruction {
+            program_id: dao_2::ID,
+            accounts: self.cleanup_proposal_accounts(initializer, proposer, payee, id, treasury, None),
+            data: dao_2::instruction::CleanupProposal { treasury: treasury.to_string() }.data(),
+        }
+    }
+
+    pub fn execute_proposal(&self, initializer: &Pubkey, proposer: &Pubkey, payee: &Pubkey, id: u64, treasury: &str) -> Instruction {
+        Instruction {
+            program_id: dao_2::ID,
+            accounts: self.cleanup_proposal_accounts(initializer, proposer, payee, id, treasury, None),
+            data: dao_2::instruction::ExecuteProposal { treasury: treasury.to_string() }.data(),
+        }
+    }
+
+    // Execute a bounty paid to a member, who earns reputation for it
+    pub fn execute_bounty_to_member(&self, initializer: &Pubkey, proposer: &Pubkey, payee: &Pubkey, id: u64) -> Instruction {
+        Instruction {
+            program_id: dao_2::ID,
+            accounts: self.cleanup_proposal_accounts(
+                initializer,
+                proposer,
+                payee,
+                id,
+                "treasury",
+                Some(self.reputation(payee)),
+            ),
+            data: dao_2::instruction::ExecuteProposal { treasury: "treasury".to_string() }.data(),
+        }
+    }
+
+    pub fn consume_proposal(&self, authority: &Pubkey, id: u64, version: u8) -> Instruction {
+        Instruction {
+            program_id: dao_2::ID,
+            accounts: dao_2::accounts::ConsumeProposal {
+                authority: *authority,
+                proposal: self.proposal(id),
+                config: self.config,
+            }
+            .to_account_metas(None),
+            data: dao_2::instruction::ConsumeProposal { version }.data(),
+        }
+    }
+
+    // `token_accounts` are (treasury token account, member token account) pairs
+    pub fn rage_quit(&self, owner: &Pubkey, id: u64, token_accounts: &[(Pubkey, Pubkey)]) -> Instruction {
+        let proposal = self.proposal(id);
+        let mut accounts = dao_2::accounts::RageQuit {
+            owner: *owner,
+            stake_state: self.stake_state(owner),
+            stake_ata: self.stake_vault(owner),
+            owner_ata: self.stake_owner_ata(owner),
+            stake_auth: self.stake_auth(owner),
+            dao_auth: self.auth,
+            mint: self.stake_mint,
+            proposal,
+            vote: self.vote_state(owner, &proposal),
+            treasury: self.main_treasury,
+            whitelist: self.whitelist,
+            config: self.config,
+            token_program: self.stake_token_program,
+            treasury_token_program: spl_token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None);
+        for (treasury_account, owner_account) in token_accounts {
+            accounts.push(AccountMeta::new(*treasury_account, false));
+            accounts.push(AccountMeta::new(*owner_account, false));
+        }
+        Instruction {
+            program_id: dao_2::ID,
+            accounts,
+            data: dao_2::instruction::RageQuit {}.data(),
+        }
+    }
+
+    // `self` is the parent, `sub_dao` the DAO to spawn
+    pub fn spawn_sub_dao(&self, creator: &Pubkey, id: u64, sub_dao: &Dao, council: &[Pubkey]) -> Instruction {
+        Instruction {
+            program_id: dao_2::ID,
+            accounts: dao_2::accounts::SpawnSubDao {
+                creator: *creator,
+                proposal: self.proposal(id),
+                parent_treasury: self.main_treasury,
+                parent_config: self.config,
+                auth: sub_dao.auth,
+                main_treasury: sub_dao.main_treasury,
+                config: sub_dao.config,
+                multi_sig: sub_dao.multi_sig,
+                multisig_treasury: sub_dao.multisig_treasury,
+                whitelist: sub_dao.whitelist,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: dao_2::instruction::SpawnSubDao {
+                treasury: "treasury".to_string(),
+                seed: sub_dao.seed,
+                multisig_keys: council.to_vec(),
+                min_signers: 1,
+            }
+            .data(),
+        }
+    }
+
+    fn govern_sub_dao_accounts(&self, initializer: &Pubkey, id: u64, sub_dao: &Dao) -> Vec<AccountMeta> {
+        dao_2::accounts::GovernSubDao {
+            initializer: *initializer,
+            proposal: self.proposal(id),
+            treasury: self.main_treasury,
+            config: self.config,
+            sub_dao_treasury: sub_dao.main_treasury,
+            sub_dao_config: sub_dao.config,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None)
+    }
+
+    pub fn claw_back(&self, initializer: &Pubkey, id: u64, sub_dao: &Dao) -> Instruction {
+        Instruction {
+            program_id: dao_2::ID,
+            accounts: self.govern_sub_dao_accounts(initializer, id, sub_dao),
+            data: dao_2::instruction::ClawBack { treasury: "treasury".to_string() }.data(),
+        }
+    }
+
+    pub fn dissolve_sub_dao(&self, initializer: &Pubkey, id: u64, sub_dao: &Dao) -> Instruction {
+        Instruction {
+            program_id: dao_2::ID,
+            accounts: self.govern_sub_dao_accounts(initializer, id, sub_dao),
+            data: dao_2::instruction::DissolveSubDao { treasury: "treasury".to_string() }.data(),
+        }
+    }
+
+    // A draft payroll paid from the main treasury
+    pub fn create_payroll(
+        &self,
+        creator: &Pubkey,
+        id: u64,
+        period: u64,
+        payments: u64,
+        entries: Vec<PayrollEntry>,
+        replaces: Option<Pubkey>,
+    ) -> Instruction {
+        Instruction {
+            program_id: dao_2::ID,
+            accounts: dao_2::accounts::CreatePayroll {
+                creator: *creator,
+                payroll: self.payroll(creator, id),
+                config: self.config,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: dao_2::instruction::CreatePayroll {
+                id,
+                treasury: "treasury".to_string(),
+                period,
+                payments,
+                entries,
+                replaces,
+            }
+            .data(),
+        }
+    }
+
+    fn govern_payroll_accounts(&self, initializer: &Pubkey, id: u64, payroll: &Pubkey, replaced: Option<Pubkey>) -> Vec<AccountMeta> {
+        dao_2::accounts::GovernPayroll {
+            initializer: *initializer,
+            proposal: self.proposal(id),
+            payroll: *payroll,
+            replaced,
+            config: self.config,
+        }
+        .to_account_metas(None)
+    }
+
+    pub fn activate_payroll(&self, initializer: &Pubkey, id: u64, payroll: &Pubkey, replaced: Option<Pubkey>) -> Instruction {
+        Instruction {
+            program_id: dao_2::ID,
+            accounts: self.govern_payroll_accounts(initializer, id, payroll, replaced),
+            data: dao_2::instruction::ActivatePayroll {}.data(),
+        }
+    }
+
+    pub fn terminate_payroll(&self, initializer: &Pubkey, id: u64, payroll: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: dao_2::ID,
+            accounts: self.govern_payroll_accounts(initializer, id, payroll, None),
+            data: dao_2::instruction::TerminatePayroll {}.data(),
+        }
+    }
+
+    pub fn pay_payroll(&self, payroll: &Pubkey, payees: &[Pubkey]) -> Instruction {
+        let mut accounts = dao_2::accounts::PayPayroll {
+            payroll: *payroll,
+            treasury: self.main_treasury,
+            config: self.config,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend(payees.iter().map(|payee| AccountMeta::new(*payee, false)));
+        Instruction {
+            program_id: dao_2::ID,
+            accounts,
+            data: dao_2::instruction::PayPayroll { treasury: "treasury".to_string() }.data(),
+        }
+    }
+
+    fn govern_roles_accounts(&self, initializer: &Pubkey, id: u64, member: &Pubkey) -> Vec<AccountMeta> {
+        dao_2::accounts::GovernRoles {
+            initializer: *initializer,
+            proposal: self.proposal(id),
+            member: *member,
+            roles: self.roles(member),
+            config: self.config,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None)
+    }
+
+    pub fn grant_role(&self, initializer: &Pubkey, id: u64, member: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: dao_2::ID,
+            accounts: self.govern_roles_accounts(initializer, id, member),
+            data: dao_2::instruction::GrantRole {}.data(),
+        }
+    }
+
+    pub fn revoke_role(&self, initializer: &Pubkey, id: u64, member: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: dao_2::ID,
+            accounts: self.govern_roles_accounts(initializer, id, member),
+            data: dao_2::instruction::RevokeRole {}.data(),
+        }
+    }
+
+    pub fn treasurer_payout(&self, treasurer: &Pubkey, payee: &Pubkey, treasury: &str, amount: u64) -> Instruction {
+        Instruction {
+            program_id: dao_2::ID,
+            accounts: dao_2::accounts::TreasurerPayout {
+                treasurer: *treasurer,
+                roles: self.roles(treasurer),
+                payee: *payee,
+                treasury: self.treasury(treasury),
+                config: self.config,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: dao_2::instruction::TreasurerPayout { treasury: treasury.to_string(), amount }.data(),
+        }
+    }
+
+    pub fn guardian_pause(&self, guardian: &Pubkey, flags: PauseFlags) -> Instruction {
+        Instruction {
+            program_id: dao_2::ID,
+            accounts: dao_2::accounts::GuardianPause { guardian: *guardian, roles: self.roles(guardian), config: self.config }
+                .to_account_metas(None),
+            data: dao_2::instruction::GuardianPause { flags }.data(),
+        }
+    }
+
+    pub fn swap_order(&self, creator: &Pubkey, id: u64) -> Pubkey {
+        pda(&[b"swap", self.config.as_ref(), creator.as_ref(), &id.to_le_bytes()])
+    }
+
+    pub fn create_swap(
+        &self,
+        creator: &Pubkey,
+        id: u64,
+        pool: &Pool,
+        amount_in: u64,
+        min_out: u64,
+        expiration: i64,
+    ) -> Instruction {
+        Instruction {
+            program_id: dao_2::ID,
+            accounts: dao_2::accounts::CreateSwap {
+                creator: *creator,
+                order: self.swap_order(creator, id),
+                pool: pool.config,
+                mint_out: pool.mint_y,
+                config: self.config,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: dao_2::instruction::CreateSwap {
+                id,
+                treasury: "treasury".to_string(),
+                amount_in,
+                min_out,
+                expiration,
+            }
+            .data(),
+        }
+    }
+
+    // Sells lamports from the main treasury for the pool's Y token
+    pub fn execute_swap(&self, initializer: &Pubkey, id: u64, order: &Pubkey, pool: &Pool) -> Instruction {
+        Instruction {
+            program_id: dao_2::ID,
+            accounts: dao_2::accounts::ExecuteSwap {
+                initializer: *initializer,
+                proposal: self.proposal(id),
+                order: *order,
+                treasury: self.main_treasury,
+                treasury_in: get_associated_token_address(&self.main_treasury, &spl_token::native_mint::ID),
+                treasury_out: get_associated_token_address(&self.main_treasury, &pool.mint_y),
+                native_mint: spl_token::native_mint::ID,
+                mint_out: pool.mint_y,
+                mint_x: pool.mint_x,
+                mint_y: pool.mint_y,
+                vault_x: pool.vault_x,
+                vault_y: pool.vault_y,
+                pool_auth: pool.auth,
+                pool: pool.config,
+                amm_program: anchor_amm_2023::ID,
+                token_program: spl_token::ID,
+                associated_token_program: associated_token::ID,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: dao_2::instruction::ExecuteSwap { treasury: "treasury".to_string() }.data(),
+        }
+    }
+
+    pub fn conviction(&self, proposer: &Pubkey, id: u64) -> Pubkey {
+        pda(&[b"conviction", self.config.as_ref(), proposer.as_ref(), &id.to_le_bytes()])
+    }
+
+    pub fn allocation(&self, proposal: &Pubkey, owner: &Pubkey) -> Pubkey {
+        pda(&[b"allocation", proposal.as_ref(), owner.as_ref()])
+    }
+
+    pub fn create_conviction(&self, owner: &Pubkey, id: u64, payee: &Pubkey, amount: u64) -> Instruction {
+        Instruction {
+            program_id: dao_2::ID,
+            accounts: dao_2::accounts::CreateConviction {
+                owner: *owner,
+                stake_state: self.stake_state(owner),
+                proposal: self.conviction(owner, id),
+                payee: *payee,
+                config: self.config,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: dao_2::instruction::CreateConviction { id, amount }.data(),
+        }
+    }
+
+    pub fn allocate(&self, owner: &Pubkey, proposal: &Pubkey, amount: u64) -> Instruction {
+        Instruction {
+            program_id: dao_2::ID,
+            accounts: dao_2::accounts::Allocate {
+                owner: *owner,
+                stake_state: self.stake_state(owner),
+                proposal: *proposal,
+                allocation: self.allocation(proposal, owner),
+                config: self.config,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: dao_2::instruction::Allocate { amount }.data(),
+        }
+    }
+
+    pub fn deallocate(&self, owner: &Pubkey, proposal: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: dao_2::ID,
+            accounts: dao_2::accounts::Deallocate {
+                owner: *owner,
+                stake_state: self.stake_state(owner),
+                proposal: *proposal,
+                allocation: self.allocation(proposal, owner),
+                config: self.config,
+            }
+            .to_account_metas(None),
+            data: dao_2::instruction::Deallocate {}.data(),
+        }
+    }
+
+    pub fn execute_conviction(&self, proposal: &Pubkey, payee: &Pubkey, treasury: &str) -> Instruction {
+        Instruction {
+            program_id: dao_2::ID,
+            accounts: dao_2::accounts::ExecuteConviction {
+                proposal: *proposal,
+                payee: *payee,
+                treasury: self.treasury(treasury),
+                config: self.config,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: dao_2::instruction::ExecuteConviction { treasury: treasury.to_string() }.data(),
+        }
+    }
+
+    pub fn pause(&self, signers: &[Pubkey], flags: PauseFlags) -> Instruction {
+        let mut accounts = dao_2::accounts::Pause { multi_sig: self.multi_sig, config: self.config }.to_account_metas(None);
+        accounts.extend(signers.iter().map(|signer| AccountMeta::new_readonly(*signer, true)));
+        Instruction {
+            program_id: dao_2::ID,
+            accounts,
+            data: dao_2::instruction::Pause { flags }.data(),
+        }
+    }
+
+    pub fn init_membership(&self, payer: &Pubkey, signers: &[Pubkey]) -> Instruction {
+        let mut accounts = dao_2::accounts::InitMembership {
+            payer: *payer,
+            mint: self.membership,
+            auth: self.auth,
+            multi_sig: self.multi_sig,
+            config: self.config,
+            token_program: spl_token_2022::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend(signers.iter().map(|signer| AccountMeta::new_readonly(*signer, true)));
+        Instruction {
+            program_id: dao_2::ID,
+            accounts,
+            data: dao_2::instruction::InitMembership {}.data(),
+        }
+    }
+
+    pub fn issue_membership(&self, payer: &Pubkey, signers: &[Pubkey], member: &Pubkey, amount: u64) -> Instruction {
+        let mut accounts = dao_2::accounts::IssueMembership {
+            payer: *payer,
+            member: *member,
+            member_ata: get_associated_token_address_with_program_id(member, &self.membership, &spl_token_2022::ID),
+            mint: self.membership,
+            auth: self.auth,
+            multi_sig: self.multi_sig,
+            config: self.config,
+            token_program: spl_token_2022::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend(signers.iter().map(|signer| AccountMeta::new_readonly(*signer, true)));
+        Instruction {
+            program_id: dao_2::ID,
+            accounts,
+            data: dao_2::instruction::IssueMembership { amount }.data(),
+        }
+    }
+
+    pub fn relay_votes(&self, relayer: &Pubkey, id: u64, voters: &[Pubkey]) -> Instruction {
+        let mut accounts = dao_2::accounts::RelayVotes {
+            relayer: *relayer,
+            proposal: self.proposal(id),
+            ballots: self.ballots(id),
+            config: self.config,
+            instructions: sysvar::instructions::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None);
+        let proposal = self.proposal(id);
+        for voter in voters {
+            accounts.push(AccountMeta::new(self.stake_state(voter), false));
+            accounts.push(AccountMeta::new_readonly(self.vote_state(voter, &proposal), false));
+            accounts.push(AccountMeta::new(self.reputation(voter), false));
+        }
+        Instruction {
+            program_id: dao_2::ID,
+            accounts,
+            data: dao_2::instruction::RelayVotes {}.data(),
+        }
+    }
+
+    pub fn migrate(&self, payer: &Pubkey, account: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: dao_2::ID,
+            accounts: dao_2::accounts::Migrate {
+                payer: *payer,
+                account: *account,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: dao_2::instruction::Migrate {}.data(),
+        }
+    }
+
+    pub fn update_whitelist(&self, signers: &[Pubkey], mints: Vec<Pubkey>) -> Instruction {
+        let mut accounts = dao_2::accounts::UpdateWhitelist {
+            multi_sig: self.multi_sig,
+            whitelist: self.whitelist,
+            config: self.config,
+        }
+        .to_account_metas(None);
+        accounts.extend(signers.iter().map(|signer| AccountMeta::new_readonly(*signer, true)));
+        Instruction {
+            program_id: dao_2::ID,
+            accounts,
+            data: dao_2::instruction::UpdateWhitelist { mints }.data(),
+        }
+    }
+}
+
+// An `anchor-amm-2023` pool trading wrapped SOL as X for another token as Y
+pub struct Pool {
+    pub config: Pubkey,
+    pub auth: Pubkey,
+    pub mint_x: Pubkey,
+    pub mint_y: Pubkey,
+    pub mint_lp: Pubkey,
+    pub vault_x: Pubkey,
+    pub vault_y: Pubkey,
+}
+
+impl Pool {
+    pub fn new(seed: u64, mint_y: Pubkey) -> Self {
+        let amm_pda = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &anchor_amm_2023::ID).0;
+        let config = amm_pda(&[b"config", &seed.to_le_bytes()]);
+        let auth = amm_pda(&[b"auth", config.as_ref()]);
+        let mint_x = spl_token::native_mint::ID;
+        Self {
+            config,
+            auth,
+            mint_x,
+            mint_y,
+            mint_lp: amm_pda(&[b"lp", config.as_ref()]),
+            vault_x: get_associated_token_address(&auth, &mint_x),
+            vault_y: get_associated_token_address(&auth, &mint_y),
+        }
+    }
+
+    pub fn initialize(&self, initializer: &Pubkey, seed: u64, fee: u16) -> Instruction {
+        Instruction {
+            program_id: anchor_amm_2023::ID,
+            accounts: anchor_amm_2023::accounts::Initialize {
+                initializer: *initializer,
+                mint_x: self.mint_x,
+                mint_y: self.mint_y,
+                mint_lp: self.mint_lp,
+                vault_x: self.vault_x,
+                vault_y: self.vault_y,
+                auth: self.auth,
+                config: self.config,
+                token_program: spl_token::ID,
+                associated_token_program: associated_token::ID,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: anchor_amm_2023::instruction::Initialize { seed, fee, authority: None }.data(),
+        }
+    }
+
+    // The vault authority every pool shared before it was seeded by the pool's config
+    pub fn old_auth() -> Pubkey {
+        Pubkey::find_program_address(&[b"auth"], &anchor_amm_2023::ID).0
+    }
+
+    pub fn migrate(&self, payer: &Pubkey) -> Instruction {
+        let old_auth = Self::old_auth();
+        Instruction {
+            program_id: anchor_amm_2023::ID,
+            accounts: anchor_amm_2023::accounts::Migrate {
+                payer: *payer,
+                mint_x: self.mint_x,
+                mint_y: self.mint_y,
+                mint_lp: self.mint_lp,
+                old_vault_x: get_associated_token_address(&old_auth, &self.mint_x),
+                old_vault_y: get_associated_token_address(&old_auth, &self.mint_y),
+                vault_x: self.vault_x,
+                vault_y: self.vault_y,
+                old_auth,
+                auth: self.auth,
+                config: self.config,
+                token_program: spl_token::ID,
+                associated_token_program: associated_token::ID,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: anchor_amm_2023::instruction::Migrate {}.data(),
+        }
+    }
+}
+
+// An Ed25519 precompile instruction verifying each voter's signature over their ballot,
+// with keys, signatures and messages all stored in the instruction itself
+pub fn ed25519_ballots(ballots: &[(&Keypair, &Ballot)]) -> Instruction {
+    let header = 2 + ballots.len() * 14;
+    let mut offsets = vec![ballots.len() as u8, 0];
+    let mut payload = vec![];
+    for (voter, ballot) in ballots {
+        let message = ballot.try_to_vec().unwrap();
+        let signature = voter.sign_message(&message);
+        let key_offset = header + payload.len();
+        payload.extend_from_slice(voter.pubkey().as_ref());
+        let signature_offset = header + payload.len();
+        payload.extend_from_slice(signature.as_ref());
+        let message_offset = header + payload.len();
+        payload.extend_from_slice(&message);
+        // Signature, public key and message, each with the index of the instruction holding it
+        let this_instruction = u16::MAX as usize;
+        let fields = [
+            signature_offset,
+            this_instruction,
+            key_offset,
+            this_instruction,
+            message_offset,
+            message.len(),
+            this_instruction,
+        ];
+        for value in fields {
+            offsets.extend_from_slice(&(value as u16).to_le_bytes());
+        }
+    }
+    offsets.extend(payload);
+    Instruction { program_id: ed25519_program::ID, accounts: vec![], data: offsets }
+}
+
+pub fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &dao_2::ID).0
+}
+
+// Write an initialized SPL token account straight into the bank
+pub async fn set_token_account(context: &mut ProgramTestContext, address: Pubkey, mint: Pubkey, owner: Pubkey, amount: u64) {
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let mut data = vec![0u8; spl_token::state::Account::LEN];
+    spl_token::state::Account {
+        mint,
+        owner,
+        amount,
+        state: spl_token::state::AccountState::Initialized,
+        ..Default::default()
+    }
+    .pack_into_slice(&mut data);
+    context.set_account(
+        &address,
+        &Account {
+            lamports: rent.minimum_balance(data.len()),
+            data,
+            owner: spl_token::ID,
+            executable: false,
+            rent_epoch: 0,
+        }
+        .into(),
+    );
+}
+
+// Write a mint with `supply` tokens, or add `supply` to an existing one
+pub async fn set_mint_supply(context: &mut ProgramTestContext, address: Pubkey, supply: u64) {
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let mint = match context.banks_client.get_account(address).await.unwrap() {
+        Some(account) => {
+            let mut mint = spl_token::state::Mint::unpack(&account.data).unwrap();
+            mint.supply += supply;
+            mint
+        }
+        None => spl_token::state::Mint { supply, is_initialized: true, ..Default::default() },
+    };
+    let mut data = vec![0u8; spl_token::state::Mint::LEN];
+    mint.pack_into_slice(&mut data);
+    context.set_account(
+        &address,
+        &Account {
+            lamports: rent.minimum_balance(data.len()),
+            data,
+            owner: spl_token::ID,
+            executable: false,
+            rent_epoch: 0,
+        }
+        .into(),
+    );
+}
+
+// Amount and frozen state of a token account owned by either token program
+pub async fn token_state(context: &mut ProgramTestContext, address: Pubkey) -> (u64, bool) {
+    let account = context.banks_client.get_account(address).await.unwrap().expect("token account not found");
+    let state = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account.data).unwrap();
+    (state.base.amount, state.base.is_frozen())
+}
+
+pub async fn mint_supply(context: &mut ProgramTestContext, address: Pubkey) -> u64 {
+    let account = context.banks_client.get_account(address).await.unwrap().expect("mint not found");
+    spl_token::state::Mint::unpack(&account.data).unwrap().supply
+}
+
+// Create a funded keypair holding `tokens` governance tokens in its associated token account.
+// There is no instruction that mints governance tokens yet, so write the balance directly.
+pub async fn member(context: &mut ProgramTestContext, dao: &Dao, tokens: u64) -> Keypair {
+    let member = Keypair::new();
+    context.set_account(
+        &member.pubkey(),
+        &Account::new(10 * LAMPORTS_PER_SOL, 0, &system_program::ID).into(),
+    );
+    let ata = get_associated_token_address(&member.pubkey(), &dao.mint);
+    set_token_account(context, ata, dao.mint, member.pubkey(), tokens).await;
+    set_mint_supply(context, dao.mint, tokens).await;
+    fund_payments(context, dao, &member.pubkey()).await;
+    member
+}
+
+// Give `owner` payment tokens to pay with, when the DAO takes a token
+pub async fn fund_payments(context: &mut ProgramTestContext, dao: &Dao, owner: &Pubkey) {
+    if let (Some(mint), Some(ata)) = (dao.payment_mint, dao.payment_ata(owner)) {
+        set_token_account(context, ata, mint, *owner, 10 * LAMPORTS_PER_SOL).await;
+    }
+}
+
+pub async fn initialize(context: &mut ProgramTestContext, params: &DaoParams) -> Dao {
+    let mut dao = Dao::new(1);
+    dao.payment_mint = params.payment_mint;
+    let payer = context.payer.pubkey();
+    process(context, &[dao.initialize(&payer, params)], &[]).await.unwrap();
+    dao
+}
+
+// Initialize a DAO and a member who has staked `stake` of their `tokens` governance tokens
+pub async fn setup_staker(context: &mut ProgramTestContext, params: &DaoParams, tokens: u64, stake: u64) -> (Dao, Keypair) {
+    let dao = initialize(context, params).await;
+    let member = member(context, &dao, tokens).await;
+    let mut ixs = vec![dao.init_stake(&member.pubkey())];
+    if stake > 0 {
+        ixs.push(dao.stake(&member.pubkey(), stake));
+    }
+    process(context, &ixs, &[&member]).await.unwrap();
+    (dao, member)
+}
+
+// Add another member to an existing DAO who stakes all of their `stake` tokens
+pub async fn add_staker(context: &mut ProgramTestContext, dao: &Dao, stake: u64) -> Keypair {
+    let member = member(context, dao, stake).await;
+    process(context, &[dao.init_stake(&member.pubkey()), dao.stake(&member.pubkey(), stake)], &[&member])
+        .await
+        .unwrap();
+    member
+}
+
+// Buy a DAO NFT with a freshly funded buyer
+pub async fn issue(context: &mut ProgramTestContext, dao: &Dao) -> Result<(), BanksClientError> {
+    issue_at_most(context, dao, u64::MAX).await
+}
+
+// Buy a DAO NFT, as long as it costs no more than `max_price`
+pub async fn issue_at_most(context: &mut ProgramTestContext, dao: &Dao, max_price: u64) -> Result<(), BanksClientError> {
+    let buyer = Keypair::new();
+    context.set_account(
+        &buyer.pubkey(),
+        &Account::new(10 * LAMPORTS_PER_SOL, 0, &system_program::ID).into(),
+    );
+    fund_payments(context, dao, &buyer.pubkey()).await;
+    let nft = Keypair::new();
+    process(context, &[dao.issue_tokens(&buyer.pubkey(), &nft.pubkey(), max_price)], &[&buyer, &nft]).await
+}
+
+// A pool holding `x` lamports and `y` of a fresh token, written straight into its vaults
+pub async fn setup_pool(context: &mut ProgramTestContext, x: u64, y: u64) -> Pool {
+    let mint_y = Pubkey::new_unique();
+    set_mint_supply(context, spl_token::native_mint::ID, 0).await;
+    set_mint_supply(context, mint_y, y).await;
+    let pool = Pool::new(1, mint_y);
+    let payer = context.payer.pubkey();
+    process(context, &[pool.initialize(&payer, 1, 30)], &[]).await.unwrap();
+    set_token_account(context, pool.vault_x, pool.mint_x, pool.auth, x).await;
+    set_token_account(context, pool.vault_y, pool.mint_y, pool.auth, y).await;
+    pool
+}
+
+// A pool as created before vault authorities were seeded by the pool's config, with its vaults
+// and LP mint under the authority every pool shared
+pub async fn setup_old_pool(context: &mut ProgramTestContext, x: u64, y: u64) -> Pool {
+    let pool = setup_pool(context, x, y).await;
+    let (old_auth, old_bump) = Pubkey::find_program_address(&[b"auth"], &anchor_amm_2023::ID);
+    set_token_account(context, get_associated_token_address(&old_auth, &pool.mint_x), pool.mint_x, old_auth, x).await;
+    set_token_account(context, get_associated_token_address(&old_auth, &pool.mint_y), pool.mint_y, old_auth, y).await;
+    context.set_account(&pool.vault_x, &Account::default().into());
+    context.set_account(&pool.vault_y, &Account::default().into());
+
+    let mut mint_lp = context.banks_client.get_account(pool.mint_lp).await.unwrap().unwrap();
+    let mut mint = spl_token::state::Mint::unpack(&mint_lp.data).unwrap();
+    mint.mint_authority = COption::Some(old_auth);
+    mint.pack_into_slice(&mut mint_lp.data);
+    context.set_account(&pool.mint_lp, &mint_lp.into());
+
+    // Discriminator, seed, no update authority, both mints, fee and lock come before the auth bump
+    let mut config = context.banks_client.get_account(pool.config).await.unwrap().unwrap();
+    config.data[8 + 8 + 1 + 32 + 32 + 2 + 1] = old_bump;
+    context.set_account(&pool.config, &config.into());
+    pool
+}
+
+// Buy a DAO NFT so the main treasury holds enough lamports to pay bounties
+pub async fn fund_treasuries(context: &mut ProgramTestContext, dao: &Dao) {
+    issue(context, dao).await.unwrap();
+}
+
+// Create a proposal and warp past the pre-voting period so it can be voted on
+pub async fn open_proposal(context: &mut ProgramTestContext, dao: &Dao, member: &Keypair, params: &ProposalParams) {
+    process(context, &[dao.create_proposal(&member.pubkey(), params)], &[member]).await.unwrap();
+    warp(context, DaoParams::default().prevoting_period + 1).await;
+}
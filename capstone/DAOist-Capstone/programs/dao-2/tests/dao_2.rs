@@ -0,0 +1,2379 @@
+mod common;
+
+use common::*;
+use dao_2::{
+    errors::DaoError,
+    interface,
+    state::{
+        AbstainRule, Ballot, ConvictionProposal, ConvictionRule, DaoConfig, MemberRoles, MultiSig, PauseFlags, Payroll, PayrollEntry, PayrollStatus,
+        PricingMode, Proposal, ProposalStatus, ProposalType, QuorumRule, Reputation, ReputationRule, Role, RolePolicy, StakeState,
+        SwapOrder, TallyRule, TreasuryType, VoteState, ACCOUNT_VERSION,
+    },
+};
+use anchor_lang::system_program;
+use anchor_spl::{associated_token::get_associated_token_address, token::spl_token};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+    account::Account,
+    account_info::AccountInfo,
+    clock::Clock,
+    native_token::LAMPORTS_PER_SOL,
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+const BOUNTY: u64 = LAMPORTS_PER_SOL / 20;
+
+#[tokio::test]
+async fn initialize_creates_config_and_multisig() {
+    let mut context = start().await;
+    let params = DaoParams::default();
+    let dao = initialize(&mut context, &params).await;
+
+    let config: DaoConfig = account(&mut context, dao.config).await;
+    assert_eq!(config.seed, 1);
+    assert_eq!(config.issue_price, params.issue_price);
+    assert_eq!(config.proposal_count, 0);
+    assert_eq!(config.staked_supply, 0);
+    assert_eq!(config.bounty_rule, MAJORITY);
+    assert_eq!(config.main_treasury_share, 70);
+
+    let multi_sig: MultiSig = account(&mut context, dao.multi_sig).await;
+    assert_eq!(multi_sig.keys, params.multisig_keys);
+    assert_eq!(multi_sig.min_signers, 1);
+}
+
+#[tokio::test]
+async fn issue_tokens_splits_price_across_treasuries() {
+    let mut context = start().await;
+    let dao = initialize(&mut context, &DaoParams::default()).await;
+
+    fund_treasuries(&mut context, &dao).await;
+
+    assert_eq!(lamports(&mut context, dao.main_treasury).await, LAMPORTS_PER_SOL * 70 / 100);
+    assert_eq!(lamports(&mut context, dao.dev_treasury).await, LAMPORTS_PER_SOL / 10);
+    assert_eq!(lamports(&mut context, dao.ops_treasury).await, LAMPORTS_PER_SOL / 10);
+    assert_eq!(lamports(&mut context, dao.multisig_treasury).await, LAMPORTS_PER_SOL / 10);
+}
+
+// A stand-in for a stablecoin to pay in
+async fn payment_mint(context: &mut ProgramTestContext) -> Pubkey {
+    let mint = Pubkey::new_unique();
+    set_mint_supply(context, mint, 0).await;
+    mint
+}
+
+#[tokio::test]
+async fn issue_tokens_splits_token_price_across_treasury_accounts() {
+    let mut context = start().await;
+    let params = DaoParams { issue_price: 1_003, payment_mint: Some(payment_mint(&mut context).await), ..Default::default() };
+    let dao = initialize(&mut context, &params).await;
+
+    fund_treasuries(&mut context, &dao).await;
+
+    // Main, dev and ops round down and the multisig treasury gets what's left over
+    assert_eq!(token_amount(&mut context, dao.payment_ata(&dao.main_treasury).unwrap()).await, 702);
+    assert_eq!(token_amount(&mut context, dao.payment_ata(&dao.dev_treasury).unwrap()).await, 100);
+    assert_eq!(token_amount(&mut context, dao.payment_ata(&dao.ops_treasury).unwrap()).await, 100);
+    assert_eq!(token_amount(&mut context, dao.payment_ata(&dao.multisig_treasury).unwrap()).await, 101);
+    assert_eq!(lamports(&mut context, dao.main_treasury).await, 0);
+}
+
+#[tokio::test]
+async fn proposal_fee_is_paid_in_payment_mint() {
+    let mut context = start().await;
+    let params = DaoParams { payment_mint: Some(payment_mint(&mut context).await), ..Default::default() };
+    let (dao, member) = setup_staker(&mut context, &params, 100, 100).await;
+
+    open_proposal(&mut context, &dao, &member, &ProposalParams::bounty(1, Pubkey::new_unique(), BOUNTY)).await;
+
+    let fee = token_amount(&mut context, dao.payment_ata(&dao.main_treasury).unwrap()).await;
+    assert_eq!(fee, params.proposal_fee);
+}
+
+#[tokio::test]
+async fn issue_in_other_mint_fails() {
+    let mut context = start().await;
+    let params = DaoParams { payment_mint: Some(payment_mint(&mut context).await), ..Default::default() };
+    let mut dao = initialize(&mut context, &params).await;
+    dao.payment_mint = Some(payment_mint(&mut context).await);
+    assert_dao_error(issue(&mut context, &dao).await, DaoError::InvalidPaymentMint);
+}
+
+// Everything the treasuries have taken in from sales
+async fn sales(context: &mut ProgramTestContext, dao: &Dao) -> u64 {
+    let mut total = 0;
+    for treasury in [dao.main_treasury, dao.dev_treasury, dao.ops_treasury, dao.multisig_treasury] {
+        total += lamports(context, treasury).await;
+    }
+    total
+}
+
+#[tokio::test]
+async fn linear_curve_raises_price_with_every_sale() {
+    let mut context = start().await;
+    let params = DaoParams { pricing: PricingMode::Linear { step: LAMPORTS_PER_SOL / 2 }, ..Default::default() };
+    let dao = initialize(&mut context, &params).await;
+
+    issue(&mut context, &dao).await.unwrap();
+    assert_eq!(sales(&mut context, &dao).await, LAMPORTS_PER_SOL);
+    issue(&mut context, &dao).await.unwrap();
+    assert_eq!(sales(&mut context, &dao).await, LAMPORTS_PER_SOL * 5 / 2);
+
+    let config: DaoConfig = account(&mut context, dao.config).await;
+    assert_eq!(config.issued, 2);
+}
+
+#[tokio::test]
+async fn exponential_curve_compounds_with_every_sale() {
+    let mut context = start().await;
+    let params = DaoParams { pricing: PricingMode::Exponential { growth: 5_000 }, ..Default::default() };
+    let dao = initialize(&mut context, &params).await;
+
+    // 1 + 1.5 + 2.25
+    for _ in 0..3 {
+        issue(&mut context, &dao).await.unwrap();
+    }
+    assert_eq!(sales(&mut context, &dao).await, LAMPORTS_PER_SOL * 19 / 4);
+}
+
+#[tokio::test]
+async fn dutch_auction_decays_to_floor() {
+    let mut context = start().await;
+    let start_slot = slot(&mut context).await;
+    let auction = PricingMode::DutchAuction { start_price: 2 * LAMPORTS_PER_SOL, start: start_slot, duration: 100 };
+    let dao = initialize(&mut context, &DaoParams { pricing: auction, ..Default::default() }).await;
+
+    warp(&mut context, 50).await;
+    let elapsed = slot(&mut context).await - start_slot;
+    let price = 2 * LAMPORTS_PER_SOL - LAMPORTS_PER_SOL * elapsed / 100;
+    assert_dao_error(issue_at_most(&mut context, &dao, price - 1).await, DaoError::PriceAboveMax);
+    issue_at_most(&mut context, &dao, price).await.unwrap();
+    assert_eq!(sales(&mut context, &dao).await, price);
+
+    // Never below the floor
+    warp(&mut context, 100).await;
+    issue(&mut context, &dao).await.unwrap();
+    assert_eq!(sales(&mut context, &dao).await, price + LAMPORTS_PER_SOL);
+}
+
+#[tokio::test]
+async fn initialize_rejects_auction_starting_below_floor() {
+    let mut context = start().await;
+    let dao = Dao::new(1);
+    let params = DaoParams {
+        pricing: PricingMode::DutchAuction { start_price: LAMPORTS_PER_SOL / 2, start: 0, duration: 100 },
+        ..Default::default()
+    };
+    let payer = context.payer.pubkey();
+    assert_dao_error(process(&mut context, &[dao.initialize(&payer, &params)], &[]).await, DaoError::InvalidPricing);
+}
+
+#[tokio::test]
+async fn stake_and_unstake() {
+    let mut context = start().await;
+    let (dao, member) = setup_staker(&mut context, &DaoParams::default(), 100, 60).await;
+
+    let stake: StakeState = account(&mut context, dao.stake_state(&member.pubkey())).await;
+    assert_eq!(stake.amount, 60);
+    assert_eq!(token_amount(&mut context, dao.stake_vault(&member.pubkey())).await, 60);
+    let config: DaoConfig = account(&mut context, dao.config).await;
+    assert_eq!(config.staked_supply, 60);
+
+    warp(&mut context, 1).await;
+    process(&mut context, &[dao.unstake(&member.pubkey(), 60)], &[&member]).await.unwrap();
+
+    let stake: StakeState = account(&mut context, dao.stake_state(&member.pubkey())).await;
+    assert_eq!(stake.amount, 0);
+    assert_eq!(token_amount(&mut context, dao.stake_vault(&member.pubkey())).await, 0);
+    let config: DaoConfig = account(&mut context, dao.config).await;
+    assert_eq!(config.staked_supply, 0);
+}
+
+#[tokio::test]
+async fn bounty_proposal_lifecycle() {
+    let mut context = start().await;
+    let (dao, member) = setup_staker(&mut context, &DaoParams::default(), 100, 100).await;
+    fund_treasuries(&mut context, &dao).await;
+
+    let payee = Pubkey::new_unique();
+    let params = ProposalParams::bounty(1, payee, BOUNTY);
+    let treasury_before = lamports(&mut context, dao.main_treasury).await;
+    process(&mut context, &[dao.create_proposal(&member.pubkey(), &params)], &[&member]).await.unwrap();
+
+    let proposal: Proposal = account(&mut context, dao.proposal(1)).await;
+    assert_eq!(proposal.result, ProposalStatus::PreVoting);
+    assert_eq!(proposal.treasury, TreasuryType::Main);
+    assert_eq!(proposal.tally, MAJORITY);
+    assert_eq!(proposal.vote_counts, vec![0, 0, 0]);
+    assert_eq!(proposal.proposer, member.pubkey());
+    assert_eq!(lamports(&mut context, dao.main_treasury).await, treasury_before + LAMPORTS_PER_SOL / 100);
+    let stake: StakeState = account(&mut context, dao.stake_state(&member.pubkey())).await;
+    assert_eq!(stake.open_proposals, 1);
+
+    // Votes are rejected until the pre-voting period is over
+    assert_dao_error(
+        process(&mut context, &[dao.vote(&member.pubkey(), 1, 10, 0)], &[&member]).await,
+        DaoError::InvalidRequiredTime,
+    );
+
+    warp(&mut context, DaoParams::default().prevoting_period + 1).await;
+    // 60 of 100 staked is a majority even if the other 40 vote against
+    process(&mut context, &[dao.vote(&member.pubkey(), 1, 60, 0)], &[&member]).await.unwrap();
+
+    let proposal: Proposal = account(&mut context, dao.proposal(1)).await;
+    assert_eq!(proposal.result, ProposalStatus::Succeeded);
+    assert_eq!(proposal.staked_supply, 100);
+    assert_eq!(proposal.votes, 60);
+    assert_eq!(proposal.vote_counts, vec![60, 0, 0]);
+    let stake: StakeState = account(&mut context, dao.stake_state(&member.pubkey())).await;
+    assert_eq!(stake.accounts, 1);
+
+    // A succeeded proposal can't be cleaned up as failed
+    let initializer = context.payer.pubkey();
+    assert_dao_error(
+        process(&mut context, &[dao.cleanup_proposal(&initializer, &member.pubkey(), &payee, 1, "treasury")], &[])
+            .await,
+        DaoError::InvalidProposalStatus,
+    );
+
+    process(&mut context, &[dao.cleanup_vote(&member.pubkey(), 1)], &[&member]).await.unwrap();
+    assert!(!exists(&mut context, dao.vote_state(&member.pubkey(), &dao.proposal(1))).await);
+
+    warp(&mut context, DaoParams::default().execution_delay).await;
+    process(&mut context, &[dao.execute_proposal(&initializer, &member.pubkey(), &payee, 1, "treasury")], &[])
+        .await
+        .unwrap();
+    assert_eq!(lamports(&mut context, payee).await, BOUNTY);
+    assert!(!exists(&mut context, dao.proposal(1)).await);
+
+    // With no open vote accounts the stake can be withdrawn again
+    process(&mut context, &[dao.unstake(&member.pubkey(), 100)], &[&member]).await.unwrap();
+    let stake: StakeState = account(&mut context, dao.stake_state(&member.pubkey())).await;
+    assert_eq!(stake.accounts, 0);
+    assert_eq!(stake.open_proposals, 0);
+    assert_eq!(stake.amount, 0);
+}
+
+#[tokio::test]
+async fn vote_proposal_finalizes_without_payout() {
+    let mut context = start().await;
+    let (dao, member) = setup_staker(&mut context, &DaoParams::default(), 100, 100).await;
+
+    let mut params = ProposalParams::bounty(1, Pubkey::new_unique(), 0);
+    params.proposal = ProposalType::Vote;
+    open_proposal(&mut context, &dao, &member, &params).await;
+    process(&mut context, &[dao.vote(&member.pubkey(), 1, 100, 0)], &[&member]).await.unwrap();
+    warp(&mut context, DaoParams::default().execution_delay).await;
+
+    let initializer = context.payer.pubkey();
+    let treasury_before = lamports(&mut context, dao.main_treasury).await;
+    let proposal_rent = lamports(&mut context, dao.proposal(1)).await;
+    process(&mut context, &[dao.execute_proposal(&initializer, &member.pubkey(), &Pubkey::new_unique(), 1, "treasury")], &[])
+        .await
+        .unwrap();
+    assert_eq!(lamports(&mut context, dao.main_treasury).await, treasury_before + proposal_rent);
+}
+
+// Once the outstanding stake can't carry it any more, the proposal fails on the spot
+#[tokio::test]
+async fn failed_proposal_is_cleaned_up() {
+    let mut context = start().await;
+    let (dao, member) = setup_staker(&mut context, &DaoParams::default(), 100, 100).await;
+    let payee = Pubkey::new_unique();
+    open_proposal(&mut context, &dao, &member, &ProposalParams::bounty(1, payee, BOUNTY)).await;
+    process(&mut context, &[dao.vote(&member.pubkey(), 1, 50, 1)], &[&member]).await.unwrap();
+    let proposal: Proposal = account(&mut context, dao.proposal(1)).await;
+    assert_eq!(proposal.result, ProposalStatus::Failed);
+
+    // Votes can only be removed while the proposal is open
+    assert_dao_error(
+        process(&mut context, &[dao.remove_vote(&member.pubkey(), 1)], &[&member]).await,
+        DaoError::InvalidProposalStatus,
+    );
+
+    let initializer = context.payer.pubkey();
+    assert_dao_error(
+        process(&mut context, &[dao.execute_proposal(&initializer, &member.pubkey(), &payee, 1, "treasury")], &[])
+            .await,
+        DaoError::InvalidProposalStatus,
+    );
+
+    process(&mut context, &[dao.cleanup_vote(&member.pubkey(), 1)], &[&member]).await.unwrap();
+    process(&mut context, &[dao.cleanup_proposal(&initializer, &member.pubkey(), &payee, 1, "treasury")], &[])
+        .await
+        .unwrap();
+    assert!(!exists(&mut context, dao.proposal(1)).await);
+}
+
+#[tokio::test]
+async fn open_proposals_are_limited_per_member() {
+    let mut context = start().await;
+    let (dao, member) = setup_staker(&mut context, &DaoParams::default(), 100, 100).await;
+    let payee = Pubkey::new_unique();
+    for id in 1..=2 {
+        process(&mut context, &[dao.create_proposal(&member.pubkey(), &ProposalParams::bounty(id, payee, BOUNTY))], &[&member])
+            .await
+            .unwrap();
+    }
+    assert_dao_error(
+        process(&mut context, &[dao.create_proposal(&member.pubkey(), &ProposalParams::bounty(3, payee, BOUNTY))], &[&member])
+            .await,
+        DaoError::TooManyOpenProposals,
+    );
+
+    // Another member has their own allowance
+    let other = add_staker(&mut context, &dao, 10).await;
+    process(&mut context, &[dao.create_proposal(&other.pubkey(), &ProposalParams::bounty(3, payee, BOUNTY))], &[&other])
+        .await
+        .unwrap();
+
+    // Closing a proposal frees up the slot
+    warp(&mut context, ProposalParams::bounty(1, payee, BOUNTY).expiry + 1).await;
+    let initializer = context.payer.pubkey();
+    process(&mut context, &[dao.cleanup_proposal(&initializer, &member.pubkey(), &payee, 1, "treasury")], &[])
+        .await
+        .unwrap();
+    let stake: StakeState = account(&mut context, dao.stake_state(&member.pubkey())).await;
+    assert_eq!(stake.open_proposals, 1);
+    process(&mut context, &[dao.create_proposal(&member.pubkey(), &ProposalParams::bounty(4, payee, BOUNTY))], &[&member])
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn escalating_fee_grows_with_open_proposals() {
+    let mut context = start().await;
+    let params = DaoParams { escalate_proposal_fee: true, max_open_proposals_per_member: 3, ..Default::default() };
+    let (dao, member) = setup_staker(&mut context, &params, 100, 100).await;
+    let payee = Pubkey::new_unique();
+
+    let mut paid = vec![];
+    for id in 1..=3 {
+        let before = lamports(&mut context, dao.main_treasury).await;
+        process(&mut context, &[dao.create_proposal(&member.pubkey(), &ProposalParams::bounty(id, payee, BOUNTY))], &[&member])
+            .await
+            .unwrap();
+        paid.push(lamports(&mut context, dao.main_treasury).await - before);
+    }
+    assert_eq!(paid, vec![params.proposal_fee, 4 * params.proposal_fee, 9 * params.proposal_fee]);
+}
+
+#[tokio::test]
+async fn initialize_rejects_zero_proposal_limit() {
+    let mut context = start().await;
+    let params = DaoParams { max_open_proposals_per_member: 0, ..Default::default() };
+    let dao = Dao::new(1);
+    let payer = context.payer.pubkey();
+    assert_dao_error(
+        process(&mut context, &[dao.initialize(&payer, &params)], &[]).await,
+        DaoError::InvalidProposalLimit,
+    );
+}
+
+// Two members with 60 and 40 staked vote for and abstain on a bounty under `rule`
+async fn tally_for_and_abstain(rule: TallyRule) -> ProposalStatus {
+    let mut context = start().await;
+    let params = DaoParams { bounty_rule: rule, ..Default::default() };
+    let (dao, member) = setup_staker(&mut context, &params, 60, 60).await;
+    let other = add_staker(&mut context, &dao, 40).await;
+    open_proposal(&mut context, &dao, &member, &ProposalParams::bounty(1, Pubkey::new_unique(), BOUNTY)).await;
+
+    process(&mut context, &[dao.vote(&member.pubkey(), 1, 60, 0)], &[&member]).await.unwrap();
+    process(&mut context, &[dao.vote(&other.pubkey(), 1, 40, 2)], &[&other]).await.unwrap();
+
+    let proposal: Proposal = account(&mut context, dao.proposal(1)).await;
+    assert_eq!(proposal.staked_supply, 100);
+    proposal.result
+}
+
+#[tokio::test]
+async fn abstain_counts_toward_quorum_only() {
+    let rule = TallyRule { threshold: 6_666, ..MAJORITY };
+    assert_eq!(tally_for_and_abstain(rule).await, ProposalStatus::Succeeded);
+}
+
+#[tokio::test]
+async fn abstain_counts_against_supermajority() {
+    let rule = TallyRule { threshold: 6_666, abstain: AbstainRule::Against, ..MAJORITY };
+    assert_eq!(tally_for_and_abstain(rule).await, ProposalStatus::Failed);
+}
+
+#[tokio::test]
+async fn ignored_abstain_misses_quorum() {
+    let rule = TallyRule { quorum: 7_000, abstain: AbstainRule::Ignored, early_finalize: false, ..MAJORITY };
+    assert_eq!(tally_for_and_abstain(rule).await, ProposalStatus::Open);
+}
+
+#[tokio::test]
+async fn tied_vote_fails() {
+    let mut context = start().await;
+    let (dao, member) = setup_staker(&mut context, &DaoParams::default(), 50, 50).await;
+    let other = add_staker(&mut context, &dao, 50).await;
+    open_proposal(&mut context, &dao, &member, &ProposalParams::bounty(1, Pubkey::new_unique(), BOUNTY)).await;
+
+    process(&mut context, &[dao.vote(&member.pubkey(), 1, 50, 0)], &[&member]).await.unwrap();
+    let proposal: Proposal = account(&mut context, dao.proposal(1)).await;
+    assert_eq!(proposal.result, ProposalStatus::Open);
+
+    process(&mut context, &[dao.vote(&other.pubkey(), 1, 50, 1)], &[&other]).await.unwrap();
+    let proposal: Proposal = account(&mut context, dao.proposal(1)).await;
+    assert_eq!(proposal.result, ProposalStatus::Failed);
+}
+
+#[tokio::test]
+async fn proposal_below_quorum_fails_at_expiry() {
+    let mut context = start().await;
+    let (dao, member) = setup_staker(&mut context, &DaoParams::default(), 100, 100).await;
+    let payee = Pubkey::new_unique();
+    let params = ProposalParams::bounty(1, payee, BOUNTY);
+    open_proposal(&mut context, &dao, &member, &params).await;
+
+    // 5% turnout is unanimous but short of the 10% quorum
+    process(&mut context, &[dao.vote(&member.pubkey(), 1, 5, 0)], &[&member]).await.unwrap();
+    let proposal: Proposal = account(&mut context, dao.proposal(1)).await;
+    assert_eq!(proposal.result, ProposalStatus::Open);
+
+    let initializer = context.payer.pubkey();
+    assert_dao_error(
+        process(&mut context, &[dao.cleanup_proposal(&initializer, &member.pubkey(), &payee, 1, "treasury")], &[])
+            .await,
+        DaoError::InvalidProposalStatus,
+    );
+
+    warp(&mut context, params.expiry).await;
+    process(&mut context, &[dao.cleanup_vote(&member.pubkey(), 1)], &[&member]).await.unwrap();
+    process(&mut context, &[dao.cleanup_proposal(&initializer, &member.pubkey(), &payee, 1, "treasury")], &[])
+        .await
+        .unwrap();
+    assert!(!exists(&mut context, dao.proposal(1)).await);
+}
+
+#[tokio::test]
+async fn proposal_without_early_finalization_waits_for_expiry() {
+    let mut context = start().await;
+    let params = DaoParams { bounty_rule: TallyRule { early_finalize: false, ..MAJORITY }, ..Default::default() };
+    let (dao, member) = setup_staker(&mut context, &params, 100, 100).await;
+    fund_treasuries(&mut context, &dao).await;
+    let payee = Pubkey::new_unique();
+    let proposal_params = ProposalParams::bounty(1, payee, BOUNTY);
+    open_proposal(&mut context, &dao, &member, &proposal_params).await;
+
+    process(&mut context, &[dao.vote(&member.pubkey(), 1, 100, 0)], &[&member]).await.unwrap();
+    let proposal: Proposal = account(&mut context, dao.proposal(1)).await;
+    assert_eq!(proposal.result, ProposalStatus::Open);
+
+    let initializer = context.payer.pubkey();
+    assert_dao_error(
+        process(&mut context, &[dao.execute_proposal(&initializer, &member.pubkey(), &payee, 1, "treasury")], &[])
+            .await,
+        DaoError::InvalidProposalStatus,
+    );
+
+    warp(&mut context, proposal_params.expiry + params.execution_delay).await;
+    process(&mut context, &[dao.execute_proposal(&initializer, &member.pubkey(), &payee, 1, "treasury")], &[])
+        .await
+        .unwrap();
+    assert_eq!(lamports(&mut context, payee).await, BOUNTY);
+}
+
+#[tokio::test]
+async fn proposal_never_opened_fails_at_expiry() {
+    let mut context = start().await;
+    let (dao, member) = setup_staker(&mut context, &DaoParams::default(), 100, 100).await;
+    let payee = Pubkey::new_unique();
+    let params = ProposalParams::bounty(1, payee, BOUNTY);
+    process(&mut context, &[dao.create_proposal(&member.pubkey(), &params)], &[&member]).await.unwrap();
+
+    warp(&mut context, params.expiry + 1).await;
+    let initializer = context.payer.pubkey();
+    process(&mut context, &[dao.cleanup_proposal(&initializer, &member.pubkey(), &payee, 1, "treasury")], &[])
+        .await
+        .unwrap();
+    assert!(!exists(&mut context, dao.proposal(1)).await);
+}
+
+struct Contested {
+    dao: Dao,
+    council: Keypair,
+    yes: Keypair,
+    no: Keypair,
+}
+
+// A bounty passed 70 to 30, with a council that can update the whitelist
+async fn contested_bounty(context: &mut ProgramTestContext) -> Contested {
+    let council = Keypair::new();
+    let params = DaoParams { multisig_keys: vec![council.pubkey()], ..Default::default() };
+    let (dao, yes) = setup_staker(context, &params, 70, 70).await;
+    let no = add_staker(context, &dao, 30).await;
+    fund_treasuries(context, &dao).await;
+    open_proposal(context, &dao, &yes, &ProposalParams::bounty(1, Pubkey::new_unique(), BOUNTY)).await;
+    process(context, &[dao.vote(&no.pubkey(), 1, 30, 1)], &[&no]).await.unwrap();
+    process(context, &[dao.vote(&yes.pubkey(), 1, 70, 0)], &[&yes]).await.unwrap();
+    let proposal: Proposal = account(context, dao.proposal(1)).await;
+    assert_eq!(proposal.result, ProposalStatus::Succeeded);
+
+    Contested { dao, council, yes, no }
+}
+
+#[tokio::test]
+async fn rage_quit_pays_pro_rata_treasury_share() {
+    let mut context = start().await;
+    let Contested { dao, council, no, .. } = contested_bounty(&mut context).await;
+
+    // The main treasury also holds 1000 of a whitelisted token
+    let token_mint = Pubkey::new_unique();
+    let treasury_tokens = Pubkey::new_unique();
+    let member_tokens = Pubkey::new_unique();
+    set_mint_supply(&mut context, token_mint, 1_000).await;
+    set_token_account(&mut context, treasury_tokens, token_mint, dao.main_treasury, 1_000).await;
+    set_token_account(&mut context, member_tokens, token_mint, no.pubkey(), 0).await;
+    process(&mut context, &[dao.update_whitelist(&[council.pubkey()], vec![token_mint])], &[&council])
+        .await
+        .unwrap();
+
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let available = lamports(&mut context, dao.main_treasury).await - rent.minimum_balance(0);
+    let member_before = lamports(&mut context, no.pubkey()).await;
+    let vote_rent = lamports(&mut context, dao.vote_state(&no.pubkey(), &dao.proposal(1))).await;
+    let supply_before = mint_supply(&mut context, dao.mint).await;
+
+    process(
+        &mut context,
+        &[dao.rage_quit(&no.pubkey(), 1, &[(treasury_tokens, member_tokens)])],
+        &[&no],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(lamports(&mut context, no.pubkey()).await, member_before + available * 30 / 100 + vote_rent);
+    assert_eq!(token_amount(&mut context, member_tokens).await, 300);
+    assert_eq!(token_amount(&mut context, treasury_tokens).await, 700);
+    assert!(!exists(&mut context, dao.vote_state(&no.pubkey(), &dao.proposal(1))).await);
+
+    // The stake is burned
+    let stake: StakeState = account(&mut context, dao.stake_state(&no.pubkey())).await;
+    assert_eq!(stake.amount, 0);
+    assert_eq!(stake.accounts, 0);
+    assert_eq!(token_amount(&mut context, dao.stake_vault(&no.pubkey())).await, 0);
+    assert_eq!(mint_supply(&mut context, dao.mint).await, supply_before - 30);
+    let config: DaoConfig = account(&mut context, dao.config).await;
+    assert_eq!(config.staked_supply, 70);
+}
+
+#[tokio::test]
+async fn rage_quit_only_forfeits_the_objecting_stake() {
+    let mut context = start().await;
+    let (dao, yes) = setup_staker(&mut context, &DaoParams::default(), 70, 70).await;
+    let no = add_staker(&mut context, &dao, 30).await;
+    fund_treasuries(&mut context, &dao).await;
+    open_proposal(&mut context, &dao, &yes, &ProposalParams::bounty(1, Pubkey::new_unique(), BOUNTY)).await;
+    process(&mut context, &[dao.vote(&no.pubkey(), 1, 10, 1)], &[&no]).await.unwrap();
+    process(&mut context, &[dao.vote(&yes.pubkey(), 1, 70, 0)], &[&yes]).await.unwrap();
+
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let available = lamports(&mut context, dao.main_treasury).await - rent.minimum_balance(0);
+    let member_before = lamports(&mut context, no.pubkey()).await;
+    let vote_rent = lamports(&mut context, dao.vote_state(&no.pubkey(), &dao.proposal(1))).await;
+    process(&mut context, &[dao.rage_quit(&no.pubkey(), 1, &[])], &[&no]).await.unwrap();
+
+    // Paid and burned for the 10 that objected, the other 20 stay staked
+    assert_eq!(lamports(&mut context, no.pubkey()).await, member_before + available * 10 / 100 + vote_rent);
+    let stake: StakeState = account(&mut context, dao.stake_state(&no.pubkey())).await;
+    assert_eq!(stake.amount, 20);
+    assert_eq!(token_amount(&mut context, dao.stake_vault(&no.pubkey())).await, 20);
+    let config: DaoConfig = account(&mut context, dao.config).await;
+    assert_eq!(config.staked_supply, 90);
+}
+
+#[tokio::test]
+async fn execute_during_execution_delay_fails() {
+    let mut context = start().await;
+    let Contested { dao, yes, .. } = contested_bounty(&mut context).await;
+    let proposal: Proposal = account(&mut context, dao.proposal(1)).await;
+    let ProposalType::Bounty(payee, _) = proposal.proposal else { panic!("not a bounty") };
+
+    let initializer = context.payer.pubkey();
+    assert_dao_error(
+        process(&mut context, &[dao.execute_proposal(&initializer, &yes.pubkey(), &payee, 1, "treasury")], &[]).await,
+        DaoError::ExecutionDelay,
+    );
+}
+
+#[tokio::test]
+async fn rage_quit_after_execution_delay_fails() {
+    let mut context = start().await;
+    let Contested { dao, no, .. } = contested_bounty(&mut context).await;
+    warp(&mut context, DaoParams::default().execution_delay).await;
+    assert_dao_error(
+        process(&mut context, &[dao.rage_quit(&no.pubkey(), 1, &[])], &[&no]).await,
+        DaoError::RageQuitClosed,
+    );
+}
+
+#[tokio::test]
+async fn rage_quit_after_voting_for_fails() {
+    let mut context = start().await;
+    let Contested { dao, yes, .. } = contested_bounty(&mut context).await;
+    assert_dao_error(
+        process(&mut context, &[dao.rage_quit(&yes.pubkey(), 1, &[])], &[&yes]).await,
+        DaoError::RageQuitNotAllowed,
+    );
+}
+
+#[tokio::test]
+async fn rage_quit_while_paused() {
+    let mut context = start().await;
+    let Contested { dao, council, no, .. } = contested_bounty(&mut context).await;
+    process(&mut context, &[dao.pause(&[council.pubkey()], PAUSE_ALL)], &[&council]).await.unwrap();
+
+    // A pause can't be used to wait out the execution delay on members who want out
+    process(&mut context, &[dao.rage_quit(&no.pubkey(), 1, &[])], &[&no]).await.unwrap();
+    let stake: StakeState = account(&mut context, dao.stake_state(&no.pubkey())).await;
+    assert_eq!(stake.amount, 0);
+}
+
+#[tokio::test]
+async fn rage_quit_with_unlisted_mint_fails() {
+    let mut context = start().await;
+    let Contested { dao, no, .. } = contested_bounty(&mut context).await;
+
+    let token_mint = Pubkey::new_unique();
+    let treasury_tokens = Pubkey::new_unique();
+    let member_tokens = Pubkey::new_unique();
+    set_mint_supply(&mut context, token_mint, 1_000).await;
+    set_token_account(&mut context, treasury_tokens, token_mint, dao.main_treasury, 1_000).await;
+    set_token_account(&mut context, member_tokens, token_mint, no.pubkey(), 0).await;
+    assert_dao_error(
+        process(
+            &mut context,
+            &[dao.rage_quit(&no.pubkey(), 1, &[(treasury_tokens, member_tokens)])],
+            &[&no],
+        )
+        .await,
+        DaoError::MintNotWhitelisted,
+    );
+}
+
+#[tokio::test]
+async fn update_whitelist_without_council_fails() {
+    let mut context = start().await;
+    let dao = initialize(&mut context, &DaoParams::default()).await;
+    let outsider = Keypair::new();
+    assert_dao_error(
+        process(&mut context, &[dao.update_whitelist(&[outsider.pubkey()], vec![Pubkey::new_unique()])], &[&outsider])
+            .await,
+        DaoError::InvalidSigners,
+    );
+}
+
+// A consumable proposal that passed with the whole stake behind it
+async fn passed_consumable(context: &mut ProgramTestContext, consumer: &Pubkey) -> (Dao, Keypair) {
+    let (dao, member) = setup_staker(context, &DaoParams::default(), 100, 100).await;
+    let mut params = ProposalParams::bounty(1, Pubkey::new_unique(), 0);
+    params.proposal = ProposalType::Consumable(*consumer);
+    open_proposal(context, &dao, &member, &params).await;
+    process(context, &[dao.vote(&member.pubkey(), 1, 100, 0)], &[&member]).await.unwrap();
+    (dao, member)
+}
+
+// Run an interface helper against the account stored at `address`
+async fn with_account_info<T>(
+    context: &mut ProgramTestContext,
+    address: Pubkey,
+    f: impl FnOnce(&AccountInfo) -> T,
+) -> T {
+    let mut account = context.banks_client.get_account(address).await.unwrap().unwrap();
+    let info = AccountInfo::new(
+        &address,
+        false,
+        false,
+        &mut account.lamports,
+        &mut account.data,
+        &account.owner,
+        false,
+        account.rent_epoch,
+    );
+    f(&info)
+}
+
+#[tokio::test]
+async fn consumable_proposal_is_consumed_once() {
+    let mut context = start().await;
+    let consumer = Keypair::new();
+    let (dao, member) = passed_consumable(&mut context, &consumer.pubkey()).await;
+
+    let config = dao.config;
+    let passed = with_account_info(&mut context, dao.proposal(1), |info| {
+        interface::load_passed_proposal(info, &config).map(|proposal| proposal.id)
+    })
+    .await;
+    assert_eq!(passed.unwrap(), 1);
+
+    // Consumers wait out the execution delay like everybody else
+    let consume = dao.consume_proposal(&consumer.pubkey(), 1, interface::VERSION);
+    assert_dao_error(process(&mut context, &[consume.clone()], &[&consumer]).await, DaoError::ExecutionDelay);
+
+    warp(&mut context, DaoParams::default().execution_delay).await;
+    refresh_blockhash(&mut context).await;
+    process(&mut context, &[consume.clone()], &[&consumer]).await.unwrap();
+    let proposal: Proposal = account(&mut context, dao.proposal(1)).await;
+    assert_eq!(proposal.result, ProposalStatus::Consumed);
+
+    refresh_blockhash(&mut context).await;
+    assert_dao_error(process(&mut context, &[consume], &[&consumer]).await, DaoError::InvalidProposalStatus);
+
+    let initializer = context.payer.pubkey();
+    process(&mut context, &[dao.cleanup_proposal(&initializer, &member.pubkey(), &Pubkey::new_unique(), 1, "treasury")], &[])
+        .await
+        .unwrap();
+    assert!(!exists(&mut context, dao.proposal(1)).await);
+}
+
+#[tokio::test]
+async fn consume_by_other_signer_fails() {
+    let mut context = start().await;
+    let (dao, member) = passed_consumable(&mut context, &Pubkey::new_unique()).await;
+    warp(&mut context, DaoParams::default().execution_delay).await;
+    assert_dao_error(
+        process(&mut context, &[dao.consume_proposal(&member.pubkey(), 1, interface::VERSION)], &[&member]).await,
+        DaoError::InvalidConsumer,
+    );
+}
+
+#[tokio::test]
+async fn consume_with_other_interface_version_fails() {
+    let mut context = start().await;
+    let consumer = Keypair::new();
+    let (dao, _) = passed_consumable(&mut context, &consumer.pubkey()).await;
+    warp(&mut context, DaoParams::default().execution_delay).await;
+    assert_dao_error(
+        process(&mut context, &[dao.consume_proposal(&consumer.pubkey(), 1, interface::VERSION + 1)], &[&consumer])
+            .await,
+        DaoError::InvalidInterfaceVersion,
+    );
+}
+
+#[tokio::test]
+async fn execute_consumable_proposal_fails() {
+    let mut context = start().await;
+    let (dao, member) = passed_consumable(&mut context, &Pubkey::new_unique()).await;
+    warp(&mut context, DaoParams::default().execution_delay).await;
+    let initializer = context.payer.pubkey();
+    assert_dao_error(
+        process(&mut context, &[dao.execute_proposal(&initializer, &member.pubkey(), &Pubkey::new_unique(), 1, "treasury")], &[])
+            .await,
+        DaoError::InvalidConsumer,
+    );
+}
+
+#[tokio::test]
+async fn interface_rejects_proposal_of_another_dao() {
+    let mut context = start().await;
+    let (dao, member) = passed_consumable(&mut context, &Pubkey::new_unique()).await;
+    let other_config = Dao::new(2).config;
+    let error = with_account_info(&mut context, dao.proposal(1), |info| {
+        interface::load_proposal(info, &other_config).err()
+    })
+    .await;
+    assert_eq!(error, Some(DaoError::InvalidProposalAccount.into()));
+
+    // A stake state is a dao-2 account too, but not a proposal
+    let config = dao.config;
+    let error = with_account_info(&mut context, dao.stake_state(&member.pubkey()), |info| {
+        interface::load_proposal(info, &config).err()
+    })
+    .await;
+    assert!(error.is_some());
+}
+
+const PAUSE_ALL: PauseFlags = PauseFlags { issuance: true, proposals: true, voting: true, execution: true, staking: true };
+
+// A DAO with a one key council and a member with 100 staked
+async fn council_dao(context: &mut ProgramTestContext) -> (Dao, Keypair, Keypair) {
+    let council = Keypair::new();
+    let params = DaoParams { multisig_keys: vec![council.pubkey()], ..Default::default() };
+    let (dao, member) = setup_staker(context, &params, 100, 100).await;
+    (dao, council, member)
+}
+
+#[tokio::test]
+async fn pause_blocks_issuance_and_proposals() {
+    let mut context = start().await;
+    let (dao, council, member) = council_dao(&mut context).await;
+    let flags = PauseFlags { issuance: true, proposals: true, ..Default::default() };
+    process(&mut context, &[dao.pause(&[council.pubkey()], flags)], &[&council]).await.unwrap();
+
+    let config: DaoConfig = account(&mut context, dao.config).await;
+    assert_eq!(config.paused, flags);
+    assert_dao_error(issue(&mut context, &dao).await, DaoError::Paused);
+    assert_dao_error(
+        process(
+            &mut context,
+            &[dao.create_proposal(&member.pubkey(), &ProposalParams::bounty(1, Pubkey::new_unique(), BOUNTY))],
+            &[&member],
+        )
+        .await,
+        DaoError::Paused,
+    );
+}
+
+#[tokio::test]
+async fn pause_blocks_staking() {
+    let mut context = start().await;
+    let (dao, council, member) = council_dao(&mut context).await;
+    let flags = PauseFlags { staking: true, ..Default::default() };
+    process(&mut context, &[dao.pause(&[council.pubkey()], flags)], &[&council]).await.unwrap();
+
+    warp(&mut context, 1).await;
+    assert_dao_error(
+        process(&mut context, &[dao.unstake(&member.pubkey(), 10)], &[&member]).await,
+        DaoError::Paused,
+    );
+}
+
+#[tokio::test]
+async fn pause_issuance_leaves_staking_open() {
+    let mut context = start().await;
+    let (dao, council, member) = council_dao(&mut context).await;
+    let flags = PauseFlags { issuance: true, ..Default::default() };
+    process(&mut context, &[dao.pause(&[council.pubkey()], flags)], &[&council]).await.unwrap();
+
+    warp(&mut context, 1).await;
+    process(&mut context, &[dao.unstake(&member.pubkey(), 10)], &[&member]).await.unwrap();
+}
+
+#[tokio::test]
+async fn pause_blocks_voting_and_execution() {
+    let mut context = start().await;
+    let (dao, council, member) = council_dao(&mut context).await;
+    fund_treasuries(&mut context, &dao).await;
+    let payee = Pubkey::new_unique();
+    open_proposal(&mut context, &dao, &member, &ProposalParams::bounty(1, payee, BOUNTY)).await;
+    process(&mut context, &[dao.vote(&member.pubkey(), 1, 100, 0)], &[&member]).await.unwrap();
+    open_proposal(&mut context, &dao, &member, &ProposalParams::bounty(2, payee, BOUNTY)).await;
+
+    let flags = PauseFlags { voting: true, execution: true, ..Default::default() };
+    process(&mut context, &[dao.pause(&[council.pubkey()], flags)], &[&council]).await.unwrap();
+
+    assert_dao_error(
+        process(&mut context, &[dao.vote(&member.pubkey(), 2, 10, 0)], &[&member]).await,
+        DaoError::Paused,
+    );
+    let initializer = context.payer.pubkey();
+    assert_dao_error(
+        process(&mut context, &[dao.execute_proposal(&initializer, &member.pubkey(), &payee, 1, "treasury")], &[])
+            .await,
+        DaoError::Paused,
+    );
+}
+
+#[tokio::test]
+async fn pause_expires_on_its_own() {
+    let mut context = start().await;
+    let (dao, council, _) = council_dao(&mut context).await;
+    process(&mut context, &[dao.pause(&[council.pubkey()], PAUSE_ALL)], &[&council]).await.unwrap();
+    let config: DaoConfig = account(&mut context, dao.config).await;
+
+    // Pausing again adds nothing and doesn't push the deadline back
+    warp(&mut context, 50).await;
+    refresh_blockhash(&mut context).await;
+    process(&mut context, &[dao.pause(&[council.pubkey()], PAUSE_ALL)], &[&council]).await.unwrap();
+    let repaused: DaoConfig = account(&mut context, dao.config).await;
+    assert_eq!(repaused.paused_until, config.paused_until);
+
+    warp(&mut context, DaoParams::default().max_pause_duration).await;
+    issue(&mut context, &dao).await.unwrap();
+
+    // The council has to sit out a cooldown before pausing again
+    refresh_blockhash(&mut context).await;
+    assert_dao_error(
+        process(&mut context, &[dao.pause(&[council.pubkey()], PAUSE_ALL)], &[&council]).await,
+        DaoError::PauseCooldown,
+    );
+    warp(&mut context, DaoParams::default().max_pause_duration).await;
+    refresh_blockhash(&mut context).await;
+    process(&mut context, &[dao.pause(&[council.pubkey()], PAUSE_ALL)], &[&council]).await.unwrap();
+}
+
+#[tokio::test]
+async fn unpause_proposal_lifts_pause() {
+    let mut context = start().await;
+    let (dao, council, member) = council_dao(&mut context).await;
+    process(&mut context, &[dao.pause(&[council.pubkey()], PAUSE_ALL)], &[&council]).await.unwrap();
+
+    let mut params = ProposalParams::bounty(1, Pubkey::new_unique(), 0);
+    params.proposal = ProposalType::Unpause;
+    open_proposal(&mut context, &dao, &member, &params).await;
+    process(&mut context, &[dao.vote(&member.pubkey(), 1, 100, 0)], &[&member]).await.unwrap();
+    warp(&mut context, DaoParams::default().execution_delay).await;
+    let initializer = context.payer.pubkey();
+    process(&mut context, &[dao.execute_proposal(&initializer, &member.pubkey(), &Pubkey::new_unique(), 1, "treasury")], &[])
+        .await
+        .unwrap();
+
+    let config: DaoConfig = account(&mut context, dao.config).await;
+    assert_eq!(config.paused, PauseFlags::default());
+    issue(&mut context, &dao).await.unwrap();
+
+    // Lifting a pause starts the cooldown, so the council can't pause straight away again
+    refresh_blockhash(&mut context).await;
+    assert_dao_error(
+        process(&mut context, &[dao.pause(&[council.pubkey()], PAUSE_ALL)], &[&council]).await,
+        DaoError::PauseCooldown,
+    );
+}
+
+#[tokio::test]
+async fn pause_without_council_fails() {
+    let mut context = start().await;
+    let (dao, _, member) = council_dao(&mut context).await;
+    assert_dao_error(
+        process(&mut context, &[dao.pause(&[member.pubkey()], PAUSE_ALL)], &[&member]).await,
+        DaoError::InvalidSigners,
+    );
+}
+
+#[tokio::test]
+async fn optimistic_bounty_passes_without_votes() {
+    let mut context = start().await;
+    let (dao, member) = setup_staker(&mut context, &DaoParams::default(), 100, 100).await;
+    fund_treasuries(&mut context, &dao).await;
+
+    let payee = Pubkey::new_unique();
+    let params = ProposalParams::optimistic_bounty(1, payee, BOUNTY);
+    process(&mut context, &[dao.create_proposal(&member.pubkey(), &params)], &[&member]).await.unwrap();
+    warp(&mut context, params.expiry + DaoParams::default().execution_delay).await;
+
+    let initializer = context.payer.pubkey();
+    process(&mut context, &[dao.execute_proposal(&initializer, &member.pubkey(), &payee, 1, "ops-treasury")], &[])
+        .await
+        .unwrap();
+    assert_eq!(lamports(&mut context, payee).await, BOUNTY);
+}
+
+#[tokio::test]
+async fn objections_block_optimistic_bounty() {
+    let mut context = start().await;
+    let (dao, member) = setup_staker(&mut context, &DaoParams::default(), 100, 100).await;
+    let objector = add_staker(&mut context, &dao, 30).await;
+    let params = ProposalParams::optimistic_bounty(1, Pubkey::new_unique(), BOUNTY);
+    open_proposal(&mut context, &dao, &member, &params).await;
+
+    // Only objections count
+    assert_dao_error(
+        process(&mut context, &[dao.vote(&member.pubkey(), 1, 100, 0)], &[&member]).await,
+        DaoError::InvalidChoice,
+    );
+
+    // 30 of 130 staked is past the 20% objection threshold
+    process(&mut context, &[dao.vote(&objector.pubkey(), 1, 30, 1)], &[&objector])
+        .await
+        .unwrap();
+    let proposal: Proposal = account(&mut context, dao.proposal(1)).await;
+    assert_eq!(proposal.result, ProposalStatus::Failed);
+}
+
+#[tokio::test]
+async fn optimistic_bounty_from_main_treasury_fails() {
+    let mut params = ProposalParams::optimistic_bounty(1, Pubkey::new_unique(), BOUNTY);
+    params.treasury = "treasury".to_string();
+    assert_create_fails(params, DaoError::OptimisticNotAllowed).await;
+}
+
+#[tokio::test]
+async fn optimistic_bounty_above_cap_fails() {
+    let params = ProposalParams::optimistic_bounty(1, Pubkey::new_unique(), OPS_BOUNTIES.max_bounty + 1);
+    assert_create_fails(params, DaoError::OptimisticNotAllowed).await;
+}
+
+fn ballot(dao: &Dao, id: u64, choice: u8, amount: u64, nonce: u64) -> Ballot {
+    Ballot { proposal: dao.proposal(id), choice, amount, nonce }
+}
+
+#[tokio::test]
+async fn relayed_ballots_count_without_vote_accounts() {
+    let mut context = start().await;
+    let (dao, member) = setup_staker(&mut context, &DaoParams::default(), 100, 100).await;
+    let other = add_staker(&mut context, &dao, 30).await;
+    open_proposal(&mut context, &dao, &member, &ProposalParams::bounty(1, Pubkey::new_unique(), BOUNTY)).await;
+
+    let ballots = ed25519_ballots(&[(&member, &ballot(&dao, 1, 0, 60, 0)), (&other, &ballot(&dao, 1, 1, 30, 0))]);
+    let relayer = context.payer.pubkey();
+    let relay = dao.relay_votes(&relayer, 1, &[member.pubkey(), other.pubkey()]);
+    process(&mut context, &[ballots, relay], &[]).await.unwrap();
+
+    let proposal: Proposal = account(&mut context, dao.proposal(1)).await;
+    assert_eq!(proposal.vote_counts, vec![60, 30, 0]);
+    let stake: StakeState = account(&mut context, dao.stake_state(&member.pubkey())).await;
+    assert_eq!(stake.nonce, 1);
+    assert_eq!(stake.relayed_until, proposal.expiry);
+    // Relaying is the first time `other` needed a reputation account
+    let reputation: Reputation = account(&mut context, dao.reputation(&other.pubkey())).await;
+    assert_eq!(reputation.owner, other.pubkey());
+
+    // There is no vote account to close, so the stake stays locked until the proposal expires
+    assert_dao_error(
+        process(&mut context, &[dao.unstake(&member.pubkey(), 10)], &[&member]).await,
+        DaoError::VotesLocked,
+    );
+}
+
+async fn relay_once(context: &mut ProgramTestContext) -> (Dao, Keypair) {
+    let (dao, member) = setup_staker(context, &DaoParams::default(), 100, 100).await;
+    add_staker(context, &dao, 100).await;
+    open_proposal(context, &dao, &member, &ProposalParams::bounty(1, Pubkey::new_unique(), BOUNTY)).await;
+    let relayer = context.payer.pubkey();
+    let ballots = ed25519_ballots(&[(&member, &ballot(&dao, 1, 0, 10, 0))]);
+    process(context, &[ballots, dao.relay_votes(&relayer, 1, &[member.pubkey()])], &[]).await.unwrap();
+    (dao, member)
+}
+
+#[tokio::test]
+async fn replayed_ballot_fails() {
+    let mut context = start().await;
+    let (dao, member) = relay_once(&mut context).await;
+    refresh_blockhash(&mut context).await;
+    let relayer = context.payer.pubkey();
+    let ballots = ed25519_ballots(&[(&member, &ballot(&dao, 1, 0, 10, 0))]);
+    assert_dao_error(
+        process(&mut context, &[ballots, dao.relay_votes(&relayer, 1, &[member.pubkey()])], &[]).await,
+        DaoError::InvalidNonce,
+    );
+}
+
+#[tokio::test]
+async fn second_relayed_ballot_on_proposal_fails() {
+    let mut context = start().await;
+    let (dao, member) = relay_once(&mut context).await;
+    let relayer = context.payer.pubkey();
+    let ballots = ed25519_ballots(&[(&member, &ballot(&dao, 1, 0, 10, 1))]);
+    assert_dao_error(
+        process(&mut context, &[ballots, dao.relay_votes(&relayer, 1, &[member.pubkey()])], &[]).await,
+        DaoError::AlreadyVoted,
+    );
+}
+
+#[tokio::test]
+async fn vote_after_relayed_ballot_fails() {
+    let mut context = start().await;
+    let (dao, member) = relay_once(&mut context).await;
+    assert_dao_error(
+        process(&mut context, &[dao.vote(&member.pubkey(), 1, 10, 0)], &[&member]).await,
+        DaoError::AlreadyVoted,
+    );
+}
+
+#[tokio::test]
+async fn relayed_ballot_after_vote_fails() {
+    let mut context = start().await;
+    let (dao, member) = setup_staker(&mut context, &DaoParams::default(), 100, 100).await;
+    add_staker(&mut context, &dao, 100).await;
+    open_proposal(&mut context, &dao, &member, &ProposalParams::bounty(1, Pubkey::new_unique(), BOUNTY)).await;
+    process(&mut context, &[dao.vote(&member.pubkey(), 1, 10, 0)], &[&member]).await.unwrap();
+
+    let relayer = context.payer.pubkey();
+    let ballots = ed25519_ballots(&[(&member, &ballot(&dao, 1, 0, 10, 0))]);
+    assert_dao_error(
+        process(&mut context, &[ballots, dao.relay_votes(&relayer, 1, &[member.pubkey()])], &[]).await,
+        DaoError::AlreadyVoted,
+    );
+}
+
+// A member with 40 staked before the proposal opened tops up another 60 once it's open
+async fn stake_after_open(context: &mut ProgramTestContext) -> (Dao, Keypair) {
+    let (dao, member) = setup_staker(context, &DaoParams::default(), 100, 100).await;
+    let late = common::member(context, &dao, 100).await;
+    process(context, &[dao.init_stake(&late.pubkey()), dao.stake(&late.pubkey(), 40)], &[&late]).await.unwrap();
+    open_proposal(context, &dao, &member, &ProposalParams::bounty(1, Pubkey::new_unique(), BOUNTY)).await;
+    process(context, &[dao.vote(&member.pubkey(), 1, 10, 0)], &[&member]).await.unwrap();
+    warp(context, 1).await;
+    process(context, &[dao.stake(&late.pubkey(), 60)], &[&late]).await.unwrap();
+    (dao, late)
+}
+
+#[tokio::test]
+async fn vote_with_stake_deposited_after_open_fails() {
+    let mut context = start().await;
+    let (dao, late) = stake_after_open(&mut context).await;
+    assert_dao_error(
+        process(&mut context, &[dao.vote(&late.pubkey(), 1, 100, 0)], &[&late]).await,
+        DaoError::StakeAfterOpen,
+    );
+
+    // The stake held before the proposal opened still counts
+    process(&mut context, &[dao.vote(&late.pubkey(), 1, 40, 0)], &[&late]).await.unwrap();
+    let proposal: Proposal = account(&mut context, dao.proposal(1)).await;
+    assert_eq!(proposal.stake_voted, 50);
+    assert!(proposal.stake_voted <= proposal.staked_supply);
+}
+
+#[tokio::test]
+async fn relayed_ballot_with_stake_deposited_after_open_fails() {
+    let mut context = start().await;
+    let (dao, late) = stake_after_open(&mut context).await;
+    let relayer = context.payer.pubkey();
+    let ballots = ed25519_ballots(&[(&late, &ballot(&dao, 1, 0, 100, 0))]);
+    assert_dao_error(
+        process(&mut context, &[ballots, dao.relay_votes(&relayer, 1, &[late.pubkey()])], &[]).await,
+        DaoError::StakeAfterOpen,
+    );
+}
+
+#[tokio::test]
+async fn ballot_signed_by_someone_else_fails() {
+    let mut context = start().await;
+    let (dao, member) = setup_staker(&mut context, &DaoParams::default(), 100, 100).await;
+    open_proposal(&mut context, &dao, &member, &ProposalParams::bounty(1, Pubkey::new_unique(), BOUNTY)).await;
+    let relayer = context.payer.pubkey();
+    let ballots = ed25519_ballots(&[(&Keypair::new(), &ballot(&dao, 1, 0, 100, 0))]);
+    assert_dao_error(
+        process(&mut context, &[ballots, dao.relay_votes(&relayer, 1, &[member.pubkey()])], &[]).await,
+        DaoError::InvalidBallot,
+    );
+}
+
+// A DAO whose council switched it to the soulbound membership mint, and a member
+// who was issued 100 membership tokens and staked 60 of them
+async fn membership_dao(context: &mut ProgramTestContext) -> (Dao, Keypair, Keypair) {
+    let council = Keypair::new();
+    let params = DaoParams { multisig_keys: vec![council.pubkey()], ..Default::default() };
+    let mut dao = initialize(context, &params).await;
+    let payer = context.payer.pubkey();
+    process(context, &[dao.init_membership(&payer, &[council.pubkey()])], &[&council]).await.unwrap();
+    dao.use_membership();
+
+    let member = Keypair::new();
+    context.set_account(&member.pubkey(), &Account::new(10 * LAMPORTS_PER_SOL, 0, &system_program::ID).into());
+    process(
+        context,
+        &[dao.issue_membership(&payer, &[council.pubkey()], &member.pubkey(), 100)],
+        &[&council],
+    )
+    .await
+    .unwrap();
+    process(context, &[dao.init_stake(&member.pubkey()), dao.stake(&member.pubkey(), 60)], &[&member])
+        .await
+        .unwrap();
+    (dao, council, member)
+}
+
+#[tokio::test]
+async fn soulbound_membership_stakes_in_place() {
+    let mut context = start().await;
+    let (dao, _, member) = membership_dao(&mut context).await;
+
+    let stake: StakeState = account(&mut context, dao.stake_state(&member.pubkey())).await;
+    assert_eq!(stake.amount, 60);
+    assert_eq!(token_state(&mut context, dao.stake_owner_ata(&member.pubkey())).await, (100, true));
+    assert_eq!(token_state(&mut context, dao.stake_vault(&member.pubkey())).await, (0, false));
+    let config: DaoConfig = account(&mut context, dao.config).await;
+    assert_eq!(config.staked_supply, 60);
+
+    warp(&mut context, 1).await;
+    process(&mut context, &[dao.unstake(&member.pubkey(), 60)], &[&member]).await.unwrap();
+
+    assert_eq!(token_state(&mut context, dao.stake_owner_ata(&member.pubkey())).await, (100, false));
+    let config: DaoConfig = account(&mut context, dao.config).await;
+    assert_eq!(config.staked_supply, 0);
+}
+
+#[tokio::test]
+async fn soulbound_stake_votes() {
+    let mut context = start().await;
+    let (dao, _, member) = membership_dao(&mut context).await;
+    open_proposal(&mut context, &dao, &member, &ProposalParams::bounty(1, Pubkey::new_unique(), BOUNTY)).await;
+    process(&mut context, &[dao.vote(&member.pubkey(), 1, 60, 0)], &[&member])
+        .await
+        .unwrap();
+
+    let proposal: Proposal = account(&mut context, dao.proposal(1)).await;
+    assert_eq!(proposal.vote_counts, vec![60, 0, 0]);
+}
+
+#[tokio::test]
+async fn issue_membership_to_staked_member() {
+    let mut context = start().await;
+    let (dao, council, member) = membership_dao(&mut context).await;
+    let payer = context.payer.pubkey();
+    process(
+        &mut context,
+        &[dao.issue_membership(&payer, &[council.pubkey()], &member.pubkey(), 50)],
+        &[&council],
+    )
+    .await
+    .unwrap();
+
+    // Still frozen, since 60 of them are staked
+    assert_eq!(token_state(&mut context, dao.stake_owner_ata(&member.pubkey())).await, (150, true));
+}
+
+#[tokio::test]
+async fn init_membership_with_stake_outstanding_fails() {
+    let mut context = start().await;
+    let (dao, council, _) = council_dao(&mut context).await;
+    let payer = context.payer.pubkey();
+    assert_dao_error(
+        process(&mut context, &[dao.init_membership(&payer, &[council.pubkey()])], &[&council]).await,
+        DaoError::GovernanceMintInUse,
+    );
+}
+
+// 10 points per vote, 50 for a passed proposal and 20 for a completed bounty
+const EARNING: ReputationRule = ReputationRule { vote_points: 10, pass_points: 50, bounty_points: 20, ..NO_REPUTATION };
+
+#[tokio::test]
+async fn reputation_rewards_votes_passed_proposals_and_bounties() {
+    let mut context = start().await;
+    let params = DaoParams { reputation_rule: EARNING, ..Default::default() };
+    let (dao, member) = setup_staker(&mut context, &params, 100, 100).await;
+    fund_treasuries(&mut context, &dao).await;
+    let payee = add_staker(&mut context, &dao, 40).await;
+    open_proposal(&mut context, &dao, &member, &ProposalParams::bounty(1, payee.pubkey(), BOUNTY)).await;
+    process(&mut context, &[dao.vote(&payee.pubkey(), 1, 10, 0)], &[&payee])
+        .await
+        .unwrap();
+    process(&mut context, &[dao.vote(&member.pubkey(), 1, 100, 0)], &[&member])
+        .await
+        .unwrap();
+
+    warp(&mut context, params.execution_delay).await;
+    let initializer = context.payer.pubkey();
+    process(&mut context, &[dao.execute_bounty_to_member(&initializer, &member.pubkey(), &payee.pubkey(), 1)], &[])
+        .await
+        .unwrap();
+
+    let reputation: Reputation = account(&mut context, dao.reputation(&member.pubkey())).await;
+    assert_eq!(reputation.owner, member.pubkey());
+    assert_eq!(reputation.score, 60);
+    let reputation: Reputation = account(&mut context, dao.reputation(&payee.pubkey())).await;
+    assert_eq!(reputation.score, 30);
+}
+
+#[tokio::test]
+async fn revoting_does_not_earn_vote_points_twice() {
+    let mut context = start().await;
+    let params = DaoParams { reputation_rule: EARNING, ..Default::default() };
+    let (dao, member) = setup_staker(&mut context, &params, 100, 100).await;
+    open_proposal(&mut context, &dao, &member, &ProposalParams::bounty(1, Pubkey::new_unique(), BOUNTY)).await;
+    for _ in 0..3 {
+        process(&mut context, &[dao.vote(&member.pubkey(), 1, 10, 0)], &[&member]).await.unwrap();
+        process(&mut context, &[dao.remove_vote(&member.pubkey(), 1)], &[&member]).await.unwrap();
+        refresh_blockhash(&mut context).await;
+    }
+    let reputation: Reputation = account(&mut context, dao.reputation(&member.pubkey())).await;
+    assert_eq!(reputation.score, 0);
+
+    process(&mut context, &[dao.vote(&member.pubkey(), 1, 10, 0)], &[&member]).await.unwrap();
+    let reputation: Reputation = account(&mut context, dao.reputation(&member.pubkey())).await;
+    assert_eq!(reputation.score, 10);
+}
+
+#[tokio::test]
+async fn reputation_adds_capped_voting_weight() {
+    let mut context = start().await;
+    let rule = ReputationRule { vote_points: 1_000, max_weight_bonus: 500, ..NO_REPUTATION };
+    let params = DaoParams { reputation_rule: rule, ..Default::default() };
+    let (dao, member) = setup_staker(&mut context, &params, 100, 100).await;
+    process(
+        &mut context,
+        &[
+            dao.create_proposal(&member.pubkey(), &ProposalParams::bounty(1, Pubkey::new_unique(), BOUNTY)),
+            dao.create_proposal(&member.pubkey(), &ProposalParams::bounty(2, Pubkey::new_unique(), BOUNTY)),
+        ],
+        &[&member],
+    )
+    .await
+    .unwrap();
+    warp(&mut context, params.prevoting_period + 1).await;
+
+    process(&mut context, &[dao.vote(&member.pubkey(), 1, 40, 0)], &[&member])
+        .await
+        .unwrap();
+    process(&mut context, &[dao.vote(&member.pubkey(), 2, 40, 0)], &[&member]).await.unwrap();
+
+    // 1000 points, capped at a 5% bonus
+    let proposal: Proposal = account(&mut context, dao.proposal(2)).await;
+    assert_eq!(proposal.vote_counts, vec![42, 0, 0]);
+    assert_eq!(proposal.stake_counts, [40, 0, 0]);
+    let vote: VoteState = account(&mut context, dao.vote_state(&member.pubkey(), &dao.proposal(2))).await;
+    assert_eq!((vote.amount, vote.weight), (40, 42));
+}
+
+#[tokio::test]
+async fn reputation_weight_does_not_count_toward_quorum() {
+    let mut context = start().await;
+    let rule = ReputationRule { vote_points: 5_000, max_weight_bonus: 5_000, ..NO_REPUTATION };
+    let bounty_rule = TallyRule { quorum: 6_000, ..MAJORITY };
+    let params = DaoParams { reputation_rule: rule, bounty_rule, ..Default::default() };
+    let (dao, member) = setup_staker(&mut context, &params, 100, 100).await;
+    add_staker(&mut context, &dao, 100).await;
+    process(
+        &mut context,
+        &[
+            dao.create_proposal(&member.pubkey(), &ProposalParams::bounty(1, Pubkey::new_unique(), BOUNTY)),
+            dao.create_proposal(&member.pubkey(), &ProposalParams::bounty(2, Pubkey::new_unique(), BOUNTY)),
+        ],
+        &[&member],
+    )
+    .await
+    .unwrap();
+    warp(&mut context, params.prevoting_period + 1).await;
+    process(&mut context, &[dao.vote(&member.pubkey(), 1, 10, 0)], &[&member]).await.unwrap();
+    process(&mut context, &[dao.vote(&member.pubkey(), 2, 100, 0)], &[&member]).await.unwrap();
+
+    // Weighs 150, but only 100 of the 200 staked took part, short of the 60% quorum
+    let proposal: Proposal = account(&mut context, dao.proposal(2)).await;
+    assert_eq!(proposal.vote_counts, vec![150, 0, 0]);
+    assert_eq!(proposal.stake_counts, [100, 0, 0]);
+    assert_eq!(proposal.result, ProposalStatus::Open);
+}
+
+#[tokio::test]
+async fn relayed_ballot_is_weighted_by_reputation() {
+    let mut context = start().await;
+    let rule = ReputationRule { vote_points: 1_000, max_weight_bonus: 500, ..NO_REPUTATION };
+    let params = DaoParams { reputation_rule: rule, ..Default::default() };
+    let (dao, member) = setup_staker(&mut context, &params, 100, 100).await;
+    add_staker(&mut context, &dao, 900).await;
+    process(
+        &mut context,
+        &[
+            dao.create_proposal(&member.pubkey(), &ProposalParams::bounty(1, Pubkey::new_unique(), BOUNTY)),
+            dao.create_proposal(&member.pubkey(), &ProposalParams::bounty(2, Pubkey::new_unique(), BOUNTY)),
+        ],
+        &[&member],
+    )
+    .await
+    .unwrap();
+    warp(&mut context, params.prevoting_period + 1).await;
+    process(&mut context, &[dao.vote(&member.pubkey(), 1, 40, 0)], &[&member]).await.unwrap();
+
+    let relayer = context.payer.pubkey();
+    let ballots = ed25519_ballots(&[(&member, &ballot(&dao, 2, 0, 40, 0))]);
+    process(&mut context, &[ballots, dao.relay_votes(&relayer, 2, &[member.pubkey()])], &[]).await.unwrap();
+
+    let proposal: Proposal = account(&mut context, dao.proposal(2)).await;
+    assert_eq!(proposal.vote_counts, vec![42, 0, 0]);
+    assert_eq!(proposal.stake_counts, [40, 0, 0]);
+    let reputation: Reputation = account(&mut context, dao.reputation(&member.pubkey())).await;
+    assert_eq!(reputation.score, 2_000);
+}
+
+#[tokio::test]
+async fn reputation_halves_every_half_life() {
+    let mut context = start().await;
+    let rule = ReputationRule { vote_points: 100, half_life: 10, ..NO_REPUTATION };
+    let params = DaoParams { reputation_rule: rule, ..Default::default() };
+    let (dao, member) = setup_staker(&mut context, &params, 100, 100).await;
+    open_proposal(&mut context, &dao, &member, &ProposalParams::bounty(1, Pubkey::new_unique(), BOUNTY)).await;
+    process(&mut context, &[dao.vote(&member.pubkey(), 1, 10, 0)], &[&member])
+        .await
+        .unwrap();
+
+    // Creating a proposal brings the score up to date
+    warp(&mut context, 25).await;
+    process(
+        &mut context,
+        &[dao.create_proposal(&member.pubkey(), &ProposalParams::bounty(2, Pubkey::new_unique(), BOUNTY))],
+        &[&member],
+    )
+    .await
+    .unwrap();
+
+    let reputation: Reputation = account(&mut context, dao.reputation(&member.pubkey())).await;
+    assert_eq!(reputation.score, 25);
+}
+
+// Pass a parent proposal with the whole stake behind it and wait out the execution delay
+async fn pass_proposal(context: &mut ProgramTestContext, dao: &Dao, member: &Keypair, id: u64, proposal: ProposalType) {
+    let mut params = ProposalParams::bounty(id, Pubkey::new_unique(), 0);
+    params.proposal = proposal;
+    open_proposal(context, dao, member, &params).await;
+    process(context, &[dao.vote(&member.pubkey(), id, 100, 0)], &[member]).await.unwrap();
+    warp(context, DaoParams::default().execution_delay).await;
+}
+
+// A funded parent DAO and a sub-DAO it spawned with 0.1 SOL
+async fn spawned_sub_dao(context: &mut ProgramTestContext) -> (Dao, Keypair, Dao) {
+    let (dao, member) = setup_staker(context, &DaoParams::default(), 100, 100).await;
+    fund_treasuries(context, &dao).await;
+    pass_proposal(context, &dao, &member, 1, ProposalType::SubDao(member.pubkey(), LAMPORTS_PER_SOL / 10)).await;
+    let sub_dao = Dao::new(2);
+    process(context, &[dao.spawn_sub_dao(&member.pubkey(), 1, &sub_dao, &[member.pubkey()])], &[&member])
+        .await
+        .unwrap();
+    (dao, member, sub_dao)
+}
+
+#[tokio::test]
+async fn sub_dao_is_spawned_funded_and_clawed_back() {
+    let mut context = start().await;
+    let (dao, member, sub_dao) = spawned_sub_dao(&mut context).await;
+
+    let config: DaoConfig = account(&mut context, sub_dao.config).await;
+    assert_eq!(config.parent, dao.config);
+    assert_eq!(config.seed, 2);
+    assert_eq!(config.staked_supply, 0);
+    assert_eq!(config.bounty_rule, MAJORITY);
+    let multi_sig: MultiSig = account(&mut context, sub_dao.multi_sig).await;
+    assert_eq!(multi_sig.keys, vec![member.pubkey()]);
+    assert_eq!(lamports(&mut context, sub_dao.main_treasury).await, LAMPORTS_PER_SOL / 10);
+    let proposal: Proposal = account(&mut context, dao.proposal(1)).await;
+    assert_eq!(proposal.result, ProposalStatus::Consumed);
+
+    pass_proposal(&mut context, &dao, &member, 2, ProposalType::ClawBack(sub_dao.config, LAMPORTS_PER_SOL / 20)).await;
+    let treasury_before = lamports(&mut context, dao.main_treasury).await;
+    let initializer = context.payer.pubkey();
+    process(&mut context, &[dao.claw_back(&initializer, 2, &sub_dao)], &[]).await.unwrap();
+
+    assert_eq!(lamports(&mut context, sub_dao.main_treasury).await, LAMPORTS_PER_SOL / 20);
+    assert_eq!(lamports(&mut context, dao.main_treasury).await, treasury_before + LAMPORTS_PER_SOL / 20);
+}
+
+#[tokio::test]
+async fn dissolved_sub_dao_returns_treasury_and_stops() {
+    let mut context = start().await;
+    let (dao, member, sub_dao) = spawned_sub_dao(&mut context).await;
+    pass_proposal(&mut context, &dao, &member, 2, ProposalType::Dissolve(sub_dao.config)).await;
+    let treasury_before = lamports(&mut context, dao.main_treasury).await;
+    let initializer = context.payer.pubkey();
+    process(&mut context, &[dao.dissolve_sub_dao(&initializer, 2, &sub_dao)], &[]).await.unwrap();
+
+    assert!(!exists(&mut context, sub_dao.main_treasury).await);
+    assert_eq!(lamports(&mut context, dao.main_treasury).await, treasury_before + LAMPORTS_PER_SOL / 10);
+    let config: DaoConfig = account(&mut context, sub_dao.config).await;
+    assert!(config.dissolved);
+
+    // Its council can no longer bring in members
+    let payer = context.payer.pubkey();
+    process(&mut context, &[sub_dao.init_membership(&payer, &[member.pubkey()])], &[&member]).await.unwrap();
+    assert_dao_error(
+        process(
+            &mut context,
+            &[sub_dao.issue_membership(&payer, &[member.pubkey()], &member.pubkey(), 1)],
+            &[&member],
+        )
+        .await,
+        DaoError::Dissolved,
+    );
+}
+
+#[tokio::test]
+async fn spawn_by_other_creator_fails() {
+    let mut context = start().await;
+    let (dao, member) = setup_staker(&mut context, &DaoParams::default(), 100, 100).await;
+    fund_treasuries(&mut context, &dao).await;
+    let proposal = ProposalType::SubDao(Pubkey::new_unique(), LAMPORTS_PER_SOL / 10);
+    pass_proposal(&mut context, &dao, &member, 1, proposal).await;
+    assert_dao_error(
+        process(&mut context, &[dao.spawn_sub_dao(&member.pubkey(), 1, &Dao::new(2), &[member.pubkey()])], &[&member])
+            .await,
+        DaoError::InvalidConsumer,
+    );
+}
+
+#[tokio::test]
+async fn claw_back_from_unrelated_dao_fails() {
+    let mut context = start().await;
+    let (dao, member) = setup_staker(&mut context, &DaoParams::default(), 100, 100).await;
+    let other = Dao::new(2);
+    let payer = context.payer.pubkey();
+    process(&mut context, &[other.initialize(&payer, &DaoParams::default())], &[]).await.unwrap();
+    pass_proposal(&mut context, &dao, &member, 1, ProposalType::ClawBack(other.config, 1)).await;
+    assert_dao_error(
+        process(&mut context, &[dao.claw_back(&payer, 1, &other)], &[]).await,
+        DaoError::NotSubDao,
+    );
+}
+
+#[tokio::test]
+async fn execute_sub_dao_proposal_fails() {
+    let mut context = start().await;
+    let (dao, member) = setup_staker(&mut context, &DaoParams::default(), 100, 100).await;
+    pass_proposal(&mut context, &dao, &member, 1, ProposalType::SubDao(member.pubkey(), 0)).await;
+    let initializer = context.payer.pubkey();
+    assert_dao_error(
+        process(
+            &mut context,
+            &[dao.execute_proposal(&initializer, &member.pubkey(), &Pubkey::new_unique(), 1, "treasury")],
+            &[],
+        )
+        .await,
+        DaoError::InvalidProposalType,
+    );
+}
+
+const SALARY: u64 = LAMPORTS_PER_SOL / 100;
+const PAY_PERIOD: u64 = 20;
+
+// A funded DAO whose member drafted payroll 1, paying two payees a salary twice
+async fn drafted_payroll(context: &mut ProgramTestContext) -> (Dao, Keypair, Pubkey, Vec<Pubkey>) {
+    let (dao, member) = setup_staker(context, &DaoParams::default(), 100, 100).await;
+    fund_treasuries(context, &dao).await;
+    let payees = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+    let entries = vec![
+        PayrollEntry { payee: payees[0], amount: SALARY },
+        PayrollEntry { payee: payees[1], amount: 2 * SALARY },
+    ];
+    process(context, &[dao.create_payroll(&member.pubkey(), 1, PAY_PERIOD, 2, entries, None)], &[&member])
+        .await
+        .unwrap();
+    (dao, member, dao.payroll(&member.pubkey(), 1), payees)
+}
+
+// The same payroll, activated by proposal 1
+async fn active_payroll(context: &mut ProgramTestContext) -> (Dao, Keypair, Pubkey, Vec<Pubkey>) {
+    let (dao, member, payroll, payees) = drafted_payroll(context).await;
+    pass_proposal(context, &dao, &member, 1, ProposalType::Payroll(payroll)).await;
+    let initializer = context.payer.pubkey();
+    process(context, &[dao.activate_payroll(&initializer, 1, &payroll, None)], &[]).await.unwrap();
+    (dao, member, payroll, payees)
+}
+
+#[tokio::test]
+async fn payroll_pays_what_is_due_until_completed() {
+    let mut context = start().await;
+    let (dao, _, payroll, payees) = active_payroll(&mut context).await;
+    let state: Payroll = account(&mut context, payroll).await;
+    assert_eq!(state.status, PayrollStatus::Active);
+
+    // Paid in arrears
+    assert_dao_error(process(&mut context, &[dao.pay_payroll(&payroll, &payees)], &[]).await, DaoError::NothingDue);
+
+    warp(&mut context, PAY_PERIOD).await;
+    refresh_blockhash(&mut context).await;
+    process(&mut context, &[dao.pay_payroll(&payroll, &payees)], &[]).await.unwrap();
+    assert_eq!(lamports(&mut context, payees[0]).await, SALARY);
+    assert_eq!(lamports(&mut context, payees[1]).await, 2 * SALARY);
+
+    // Missed periods are caught up on, but never past the last payment
+    warp(&mut context, 3 * PAY_PERIOD).await;
+    refresh_blockhash(&mut context).await;
+    process(&mut context, &[dao.pay_payroll(&payroll, &payees)], &[]).await.unwrap();
+    assert_eq!(lamports(&mut context, payees[0]).await, 2 * SALARY);
+    assert_eq!(lamports(&mut context, payees[1]).await, 4 * SALARY);
+    let state: Payroll = account(&mut context, payroll).await;
+    assert_eq!(state.paid, 2);
+    assert_eq!(state.status, PayrollStatus::Completed);
+}
+
+#[tokio::test]
+async fn amended_payroll_terminates_the_one_it_replaces() {
+    let mut context = start().await;
+    let (dao, member, payroll, payees) = active_payroll(&mut context).await;
+    let entries = vec![PayrollEntry { payee: payees[0], amount: 3 * SALARY }];
+    process(&mut context, &[dao.create_payroll(&member.pubkey(), 2, PAY_PERIOD, 12, entries, Some(payroll))], &[&member])
+        .await
+        .unwrap();
+    let amended = dao.payroll(&member.pubkey(), 2);
+    pass_proposal(&mut context, &dao, &member, 2, ProposalType::Payroll(amended)).await;
+
+    // The payroll it replaces has to come along
+    let initializer = context.payer.pubkey();
+    assert_dao_error(
+        process(&mut context, &[dao.activate_payroll(&initializer, 2, &amended, None)], &[]).await,
+        DaoError::InvalidPayroll,
+    );
+    process(&mut context, &[dao.activate_payroll(&initializer, 2, &amended, Some(payroll))], &[])
+        .await
+        .unwrap();
+
+    let state: Payroll = account(&mut context, payroll).await;
+    assert_eq!(state.status, PayrollStatus::Terminated);
+    let state: Payroll = account(&mut context, amended).await;
+    assert_eq!(state.status, PayrollStatus::Active);
+}
+
+#[tokio::test]
+async fn terminated_payroll_stops_paying() {
+    let mut context = start().await;
+    let (dao, member, payroll, payees) = active_payroll(&mut context).await;
+    pass_proposal(&mut context, &dao, &member, 2, ProposalType::TerminatePayroll(payroll)).await;
+    let initializer = context.payer.pubkey();
+    process(&mut context, &[dao.terminate_payroll(&initializer, 2, &payroll)], &[]).await.unwrap();
+
+    warp(&mut context, PAY_PERIOD).await;
+    assert_dao_error(
+        process(&mut context, &[dao.pay_payroll(&payroll, &payees)], &[]).await,
+        DaoError::InvalidPayrollStatus,
+    );
+}
+
+#[tokio::test]
+async fn payroll_to_other_payee_fails() {
+    let mut context = start().await;
+    let (dao, _, payroll, payees) = active_payroll(&mut context).await;
+    warp(&mut context, PAY_PERIOD).await;
+    assert_dao_error(
+        process(&mut context, &[dao.pay_payroll(&payroll, &[payees[0], Pubkey::new_unique()])], &[]).await,
+        DaoError::InvalidPayee,
+    );
+}
+
+#[tokio::test]
+async fn draft_payroll_pays_nothing() {
+    let mut context = start().await;
+    let (dao, _, payroll, payees) = drafted_payroll(&mut context).await;
+    assert_dao_error(
+        process(&mut context, &[dao.pay_payroll(&payroll, &payees)], &[]).await,
+        DaoError::InvalidPayrollStatus,
+    );
+}
+
+// Treasurers pay out up to `BOUNTY` at once, and guardians can pause
+const ROLES: RolePolicy = RolePolicy { proposer_required: false, treasurer_cap: BOUNTY, guardian_pause: true };
+
+// A funded DAO whose member was given a role by proposal 1
+async fn member_with_role(context: &mut ProgramTestContext, role: Role, allowance: u64) -> (Dao, Keypair) {
+    let params = DaoParams { role_policy: ROLES, ..Default::default() };
+    let (dao, member) = setup_staker(context, &params, 100, 100).await;
+    fund_treasuries(context, &dao).await;
+    pass_proposal(context, &dao, &member, 1, ProposalType::GrantRole(member.pubkey(), role, allowance)).await;
+    let initializer = context.payer.pubkey();
+    process(context, &[dao.grant_role(&initializer, 1, &member.pubkey())], &[]).await.unwrap();
+    (dao, member)
+}
+
+#[tokio::test]
+async fn treasurer_pays_out_within_cap_and_allowance() {
+    let mut context = start().await;
+    let (dao, member) = member_with_role(&mut context, Role::Treasurer, 3 * BOUNTY / 2).await;
+    let roles: MemberRoles = account(&mut context, dao.roles(&member.pubkey())).await;
+    assert!(roles.treasurer && !roles.proposer && !roles.guardian);
+
+    let payee = Pubkey::new_unique();
+    assert_dao_error(
+        process(&mut context, &[dao.treasurer_payout(&member.pubkey(), &payee, "ops-treasury", BOUNTY + 1)], &[&member])
+            .await,
+        DaoError::OverSpendLimit,
+    );
+    process(&mut context, &[dao.treasurer_payout(&member.pubkey(), &payee, "ops-treasury", BOUNTY)], &[&member])
+        .await
+        .unwrap();
+    assert_eq!(lamports(&mut context, payee).await, BOUNTY);
+
+    // The second payout is under the cap but over what's left of the allowance
+    assert_dao_error(
+        process(&mut context, &[dao.treasurer_payout(&member.pubkey(), &payee, "ops-treasury", BOUNTY)], &[&member])
+            .await,
+        DaoError::OverSpendLimit,
+    );
+    let roles: MemberRoles = account(&mut context, dao.roles(&member.pubkey())).await;
+    assert_eq!(roles.allowance, BOUNTY / 2);
+}
+
+#[tokio::test]
+async fn treasurer_payout_from_main_treasury_fails() {
+    let mut context = start().await;
+    let (dao, member) = member_with_role(&mut context, Role::Treasurer, BOUNTY).await;
+    let payee = Pubkey::new_unique();
+    assert_dao_error(
+        process(&mut context, &[dao.treasurer_payout(&member.pubkey(), &payee, "treasury", BOUNTY)], &[&member]).await,
+        DaoError::InvalidTreasury,
+    );
+}
+
+#[tokio::test]
+async fn revoked_guardian_cannot_pause() {
+    let mut context = start().await;
+    let (dao, member) = member_with_role(&mut context, Role::Guardian, 0).await;
+    let flags = PauseFlags { issuance: true, ..Default::default() };
+    process(&mut context, &[dao.guardian_pause(&member.pubkey(), flags)], &[&member]).await.unwrap();
+    let config: DaoConfig = account(&mut context, dao.config).await;
+    assert_eq!(config.paused, flags);
+
+    pass_proposal(&mut context, &dao, &member, 2, ProposalType::RevokeRole(member.pubkey(), Role::Guardian)).await;
+    let initializer = context.payer.pubkey();
+    process(&mut context, &[dao.revoke_role(&initializer, 2, &member.pubkey())], &[]).await.unwrap();
+    assert_dao_error(
+        process(&mut context, &[dao.guardian_pause(&member.pubkey(), PAUSE_ALL)], &[&member]).await,
+        DaoError::MissingRole,
+    );
+}
+
+#[tokio::test]
+async fn treasurer_cannot_pause() {
+    let mut context = start().await;
+    let (dao, member) = member_with_role(&mut context, Role::Treasurer, BOUNTY).await;
+    assert_dao_error(
+        process(&mut context, &[dao.guardian_pause(&member.pubkey(), PAUSE_ALL)], &[&member]).await,
+        DaoError::MissingRole,
+    );
+}
+
+#[tokio::test]
+async fn proposer_role_gates_proposals() {
+    let mut context = start().await;
+    let params = DaoParams { role_policy: RolePolicy { proposer_required: true, ..ROLES }, ..Default::default() };
+    let (dao, member) = setup_staker(&mut context, &params, 100, 100).await;
+    let bounty = ProposalParams::bounty(1, Pubkey::new_unique(), BOUNTY);
+    assert_dao_error(
+        process(&mut context, &[dao.create_proposal(&member.pubkey(), &bounty)], &[&member]).await,
+        DaoError::MissingRole,
+    );
+
+    // Asking for the role is always allowed
+    pass_proposal(&mut context, &dao, &member, 1, ProposalType::GrantRole(member.pubkey(), Role::Proposer, 0)).await;
+    let initializer = context.payer.pubkey();
+    process(&mut context, &[dao.grant_role(&initializer, 1, &member.pubkey())], &[]).await.unwrap();
+
+    let roles = Some(dao.roles(&member.pubkey()));
+    let bounty = ProposalParams::bounty(2, Pubkey::new_unique(), BOUNTY);
+    process(&mut context, &[dao.create_proposal_with_roles(&member.pubkey(), &bounty, roles)], &[&member])
+        .await
+        .unwrap();
+}
+
+const SWAP_IN: u64 = LAMPORTS_PER_SOL / 2;
+
+// A `Swap` proposal, passed, for an order selling `SWAP_IN` lamports from the main treasury
+async fn passed_swap(context: &mut ProgramTestContext) -> (Dao, Keypair, Pubkey, Pool) {
+    let (dao, member) = setup_staker(context, &DaoParams::default(), 100, 100).await;
+    fund_treasuries(context, &dao).await;
+    let pool = setup_pool(context, 100 * LAMPORTS_PER_SOL, 10_000_000).await;
+    let clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    process(context, &[dao.create_swap(&member.pubkey(), 1, &pool, SWAP_IN, 40_000, clock.unix_timestamp + 3_600)], &[&member])
+        .await
+        .unwrap();
+    let order = dao.swap_order(&member.pubkey(), 1);
+    pass_proposal(context, &dao, &member, 1, ProposalType::Swap(order)).await;
+    (dao, member, order, pool)
+}
+
+#[tokio::test]
+async fn pools_sharing_a_mint_keep_their_own_vaults() {
+    let mut context = start().await;
+    let mint_y = Pubkey::new_unique();
+    set_mint_supply(&mut context, spl_token::native_mint::ID, 0).await;
+    set_mint_supply(&mut context, mint_y, 0).await;
+    let (first, second) = (Pool::new(1, mint_y), Pool::new(2, mint_y));
+    let payer = context.payer.pubkey();
+    process(&mut context, &[first.initialize(&payer, 1, 30), second.initialize(&payer, 2, 30)], &[]).await.unwrap();
+
+    assert_ne!(first.vault_x, second.vault_x);
+    assert_ne!(first.vault_y, second.vault_y);
+    for vault in [first.vault_x, first.vault_y, second.vault_x, second.vault_y] {
+        assert_eq!(token_amount(&mut context, vault).await, 0);
+    }
+}
+
+#[tokio::test]
+async fn migrate_moves_old_pool_to_its_own_authority() {
+    let mut context = start().await;
+    let pool = setup_old_pool(&mut context, 100 * LAMPORTS_PER_SOL, 10_000_000).await;
+    let payer = context.payer.pubkey();
+    process(&mut context, &[pool.migrate(&payer)], &[]).await.unwrap();
+
+    assert_eq!(token_amount(&mut context, pool.vault_x).await, 100 * LAMPORTS_PER_SOL);
+    assert_eq!(token_amount(&mut context, pool.vault_y).await, 10_000_000);
+    let old_auth = Pool::old_auth();
+    assert!(!exists(&mut context, get_associated_token_address(&old_auth, &pool.mint_x)).await);
+    assert!(!exists(&mut context, get_associated_token_address(&old_auth, &pool.mint_y)).await);
+    let mint_lp = context.banks_client.get_account(pool.mint_lp).await.unwrap().unwrap();
+    assert_eq!(spl_token::state::Mint::unpack(&mint_lp.data).unwrap().mint_authority, COption::Some(pool.auth));
+
+    // Its vaults can only be moved once
+    refresh_blockhash(&mut context).await;
+    assert!(process(&mut context, &[pool.migrate(&payer)], &[]).await.is_err());
+}
+
+#[tokio::test]
+async fn swap_proposal_trades_treasury_lamports_through_pool() {
+    let mut context = start().await;
+    let (dao, _, order, pool) = passed_swap(&mut context).await;
+    let before = lamports(&mut context, dao.main_treasury).await;
+
+    let initializer = context.payer.pubkey();
+    process(&mut context, &[dao.execute_swap(&initializer, 1, &order, &pool)], &[]).await.unwrap();
+
+    // 0.5 SOL into 100 SOL against 10M tokens, less the 0.3% fee
+    let bought = token_amount(&mut context, get_associated_token_address(&dao.main_treasury, &pool.mint_y)).await;
+    assert!(bought >= 40_000 && bought < 50_000);
+    assert_eq!(token_amount(&mut context, pool.vault_x).await, 100 * LAMPORTS_PER_SOL + SWAP_IN);
+    // The wrapped SOL account is closed and its rent goes to the treasury
+    assert!(!exists(&mut context, get_associated_token_address(&dao.main_treasury, &pool.mint_x)).await);
+    assert!(lamports(&mut context, dao.main_treasury).await > before - SWAP_IN);
+
+    let state: SwapOrder = account(&mut context, order).await;
+    assert!(state.executed);
+    let proposal: Proposal = account(&mut context, dao.proposal(1)).await;
+    assert_eq!(proposal.result, ProposalStatus::Consumed);
+}
+
+#[tokio::test]
+async fn swap_after_expiration_fails() {
+    let mut context = start().await;
+    let (dao, _, order, pool) = passed_swap(&mut context).await;
+    let state: SwapOrder = account(&mut context, order).await;
+    let mut clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp = state.expiration + 1;
+    context.set_sysvar(&clock);
+
+    let initializer = context.payer.pubkey();
+    assert_dao_error(
+        process(&mut context, &[dao.execute_swap(&initializer, 1, &order, &pool)], &[]).await,
+        DaoError::SwapExpired,
+    );
+}
+
+#[tokio::test]
+async fn swap_of_other_order_fails() {
+    let mut context = start().await;
+    let (dao, member, _, pool) = passed_swap(&mut context).await;
+    let clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    process(&mut context, &[dao.create_swap(&member.pubkey(), 2, &pool, 2 * SWAP_IN, 1, clock.unix_timestamp + 3_600)], &[&member])
+        .await
+        .unwrap();
+
+    let initializer = context.payer.pubkey();
+    let other = dao.swap_order(&member.pubkey(), 2);
+    assert_dao_error(
+        process(&mut context, &[dao.execute_swap(&initializer, 1, &other, &pool)], &[]).await,
+        DaoError::InvalidSwap,
+    );
+}
+
+// Ops treasury requests need 10% of the staked supply behind them, growing towards half of the pool
+const CONVICTION: ConvictionRule =
+    ConvictionRule { pool: TreasuryType::Ops, half_life: 100, max_share: 5_000, min_threshold: 1_000 };
+
+// A funded DAO whose member has asked the ops pool for a tenth of what it holds
+async fn conviction_request(context: &mut ProgramTestContext) -> (Dao, Keypair, Pubkey, Pubkey, u64) {
+    let params = DaoParams { conviction_rule: CONVICTION, ..Default::default() };
+    let (dao, member) = setup_staker(context, &params, 100, 100).await;
+    fund_treasuries(context, &dao).await;
+    let amount = lamports(context, dao.ops_treasury).await / 10;
+    let payee = Pubkey::new_unique();
+    process(context, &[dao.create_conviction(&member.pubkey(), 1, &payee, amount)], &[&member]).await.unwrap();
+    (dao, member, dao.conviction(&member.pubkey(), 1), payee, amount)
+}
+
+#[tokio::test]
+async fn conviction_builds_until_proposal_is_funded() {
+    let mut context = start().await;
+    let (dao, member, proposal, payee, amount) = conviction_request(&mut context).await;
+    process(&mut context, &[dao.allocate(&member.pubkey(), &proposal, 100)], &[&member]).await.unwrap();
+
+    // 10% of the pool against a 50% cap needs 100 * 10% * (5/4)^2 = 15 conviction
+    warp(&mut context, 10).await;
+    assert_dao_error(
+        process(&mut context, &[dao.execute_conviction(&proposal, &payee, "ops-treasury")], &[]).await,
+        DaoError::ConvictionTooLow,
+    );
+
+    // Half of the allocated stake after one half-life
+    warp(&mut context, CONVICTION.half_life).await;
+    process(&mut context, &[dao.execute_conviction(&proposal, &payee, "ops-treasury")], &[]).await.unwrap();
+    assert_eq!(lamports(&mut context, payee).await, amount);
+    let state: ConvictionProposal = account(&mut context, proposal).await;
+    assert!(state.executed && state.conviction >= 50);
+
+    refresh_blockhash(&mut context).await;
+    assert_dao_error(
+        process(&mut context, &[dao.execute_conviction(&proposal, &payee, "ops-treasury")], &[]).await,
+        DaoError::InvalidConvictionRequest,
+    );
+}
+
+#[tokio::test]
+async fn allocated_stake_stays_locked_until_deallocated() {
+    let mut context = start().await;
+    let (dao, member, proposal, _, _) = conviction_request(&mut context).await;
+    assert_dao_error(
+        process(&mut context, &[dao.allocate(&member.pubkey(), &proposal, 101)], &[&member]).await,
+        DaoError::InsufficientStake,
+    );
+    process(&mut context, &[dao.allocate(&member.pubkey(), &proposal, 60)], &[&member]).await.unwrap();
+    let stake: StakeState = account(&mut context, dao.stake_state(&member.pubkey())).await;
+    assert_eq!((stake.allocated, stake.accounts), (60, 1));
+
+    warp(&mut context, 1).await;
+    assert_dao_error(
+        process(&mut context, &[dao.unstake(&member.pubkey(), 10)], &[&member]).await,
+        DaoError::AccountsOpen,
+    );
+
+    process(&mut context, &[dao.deallocate(&member.pubkey(), &proposal)], &[&member]).await.unwrap();
+    assert!(!exists(&mut context, dao.allocation(&proposal, &member.pubkey())).await);
+    let state: ConvictionProposal = account(&mut context, proposal).await;
+    assert_eq!(state.staked, 0);
+    process(&mut context, &[dao.unstake(&member.pubkey(), 10)], &[&member]).await.unwrap();
+}
+
+#[tokio::test]
+async fn conviction_request_above_max_share_fails() {
+    let mut context = start().await;
+    let (dao, member, _, payee, amount) = conviction_request(&mut context).await;
+    process(&mut context, &[dao.create_conviction(&member.pubkey(), 2, &payee, 5 * amount)], &[&member])
+        .await
+        .unwrap();
+    let proposal = dao.conviction(&member.pubkey(), 2);
+    process(&mut context, &[dao.allocate(&member.pubkey(), &proposal, 100)], &[&member]).await.unwrap();
+
+    warp(&mut context, 10 * CONVICTION.half_life).await;
+    assert_dao_error(
+        process(&mut context, &[dao.execute_conviction(&proposal, &payee, "ops-treasury")], &[]).await,
+        DaoError::InvalidConvictionRequest,
+    );
+}
+
+#[tokio::test]
+async fn conviction_from_other_pool_fails() {
+    let mut context = start().await;
+    let (dao, member, proposal, payee, _) = conviction_request(&mut context).await;
+    process(&mut context, &[dao.allocate(&member.pubkey(), &proposal, 100)], &[&member]).await.unwrap();
+    warp(&mut context, CONVICTION.half_life).await;
+    assert_dao_error(
+        process(&mut context, &[dao.execute_conviction(&proposal, &payee, "treasury")], &[]).await,
+        DaoError::InvalidTreasury,
+    );
+}
+
+#[tokio::test]
+async fn conviction_disabled_by_default() {
+    let mut context = start().await;
+    let (dao, member) = setup_staker(&mut context, &DaoParams::default(), 100, 100).await;
+    assert_dao_error(
+        process(&mut context, &[dao.create_conviction(&member.pubkey(), 1, &Pubkey::new_unique(), 1)], &[&member]).await,
+        DaoError::InvalidConvictionRule,
+    );
+}
+
+// Quorum is half the average turnout, kept between 5% and 25%
+const ADAPTIVE: QuorumRule = QuorumRule { weight: 5_000, ratio: 5_000, min_quorum: 500, max_quorum: 2_500 };
+
+// Open proposal `id`, have each voter put their whole stake against it and close it once it fails
+async fn reject_proposal(context: &mut ProgramTestContext, dao: &Dao, voters: &[(&Keypair, u64)], id: u64) {
+    let (proposer, _) = voters[0];
+    let payee = Pubkey::new_unique();
+    open_proposal(context, dao, proposer, &ProposalParams::bounty(id, payee, 0)).await;
+    for (voter, stake) in voters {
+        process(context, &[dao.vote(&voter.pubkey(), id, *stake, 1)], &[voter]).await.unwrap();
+    }
+    warp(context, 100).await;
+    let initializer = context.payer.pubkey();
+    process(context, &[dao.cleanup_proposal(&initializer, &proposer.pubkey(), &payee, id, "treasury")], &[])
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn quorum_follows_recent_turnout() {
+    let mut context = start().await;
+    let params = DaoParams { quorum_rule: ADAPTIVE, ..Default::default() };
+    let (dao, member) = setup_staker(&mut context, &params, 100, 100).await;
+    let other = add_staker(&mut context, &dao, 900).await;
+
+    // 10% turnout seeds the average, and half of it is under the floor
+    reject_proposal(&mut context, &dao, &[(&member, 100)], 1).await;
+    let config: DaoConfig = account(&mut context, dao.config).await;
+    assert_eq!((config.average_turnout, config.turnout_samples), (1_000, 1));
+    process(&mut context, &[dao.create_proposal(&member.pubkey(), &ProposalParams::bounty(2, Pubkey::new_unique(), 0))], &[&member])
+        .await
+        .unwrap();
+    let proposal: Proposal = account(&mut context, dao.proposal(2)).await;
+    assert_eq!(proposal.tally.quorum, 500);
+
+    // Full turnout moves the average halfway, to 55%, and half of that is over the cap
+    reject_proposal(&mut context, &dao, &[(&member, 100), (&other, 900)], 3).await;
+    let config: DaoConfig = account(&mut context, dao.config).await;
+    assert_eq!((config.average_turnout, config.turnout_samples), (5_500, 2));
+    process(&mut context, &[dao.create_proposal(&other.pubkey(), &ProposalParams::bounty(4, Pubkey::new_unique(), 0))], &[&other])
+        .await
+        .unwrap();
+    let proposal: Proposal = account(&mut context, dao.proposal(4)).await;
+    assert_eq!(proposal.tally.quorum, 2_500);
+}
+
+#[tokio::test]
+async fn quorum_is_fixed_by_default() {
+    let mut context = start().await;
+    let (dao, member) = setup_staker(&mut context, &DaoParams::default(), 100, 100).await;
+    reject_proposal(&mut context, &dao, &[(&member, 100)], 1).await;
+    let config: DaoConfig = account(&mut context, dao.config).await;
+    assert_eq!(config.turnout_samples, 0);
+    process(&mut context, &[dao.create_proposal(&member.pubkey(), &ProposalParams::bounty(2, Pubkey::new_unique(), 0))], &[&member])
+        .await
+        .unwrap();
+    let proposal: Proposal = account(&mut context, dao.proposal(2)).await;
+    assert_eq!(proposal.tally.quorum, MAJORITY.quorum);
+}
+
+#[tokio::test]
+async fn initialize_rejects_inverted_quorum_bounds() {
+    let mut context = start().await;
+    let params = DaoParams { quorum_rule: QuorumRule { min_quorum: 3_000, ..ADAPTIVE }, ..Default::default() };
+    let dao = Dao::new(1);
+    let payer = context.payer.pubkey();
+    assert_dao_error(process(&mut context, &[dao.initialize(&payer, &params)], &[]).await, DaoError::InvalidQuorum);
+}
+
+// A stake account written before the last field of its layout existed
+#[tokio::test]
+async fn migrate_grows_old_account_to_current_layout() {
+    let mut context = start().await;
+    let (dao, member) = setup_staker(&mut context, &DaoParams::default(), 100, 60).await;
+    let address = dao.stake_state(&member.pubkey());
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let mut old = context.banks_client.get_account(address).await.unwrap().unwrap();
+    old.data[8] = 0;
+    old.data.truncate(StakeState::LEN - 1);
+    old.lamports = rent.minimum_balance(old.data.len());
+    context.set_account(&address, &old.into());
+
+    let payer = context.payer.pubkey();
+    process(&mut context, &[dao.migrate(&payer, &address)], &[]).await.unwrap();
+
+    let migrated = context.banks_client.get_account(address).await.unwrap().unwrap();
+    assert_eq!(migrated.data.len(), StakeState::LEN);
+    assert!(rent.is_exempt(migrated.lamports, StakeState::LEN));
+    let stake: StakeState = account(&mut context, address).await;
+    assert_eq!(stake.version, ACCOUNT_VERSION);
+    assert_eq!(stake.owner, member.pubkey());
+    assert_eq!(stake.amount, 60);
+}
+
+// A stake account written before accounts carried a version byte
+#[tokio::test]
+async fn migrate_unversioned_account_fails() {
+    let mut context = start().await;
+    let (dao, member) = setup_staker(&mut context, &DaoParams::default(), 100, 60).await;
+    let address = dao.stake_state(&member.pubkey());
+    let mut old = context.banks_client.get_account(address).await.unwrap().unwrap();
+    old.data.remove(8);
+    old.data.truncate(75);
+    context.set_account(&address, &old.into());
+
+    let payer = context.payer.pubkey();
+    assert_dao_error(
+        process(&mut context, &[dao.migrate(&payer, &address)], &[]).await,
+        DaoError::UnversionedAccount,
+    );
+}
+
+#[tokio::test]
+async fn migrate_config_inserts_staking_pause_flag() {
+    let mut context = start().await;
+    let (dao, council, _) = council_dao(&mut context).await;
+    let flags = PauseFlags { execution: true, ..Default::default() };
+    process(&mut context, &[dao.pause(&[council.pubkey()], flags)], &[&council]).await.unwrap();
+    let before: DaoConfig = account(&mut context, dao.config).await;
+
+    // The flags ended with execution, at offset 120, before the staking flag came after it
+    let mut old = context.banks_client.get_account(dao.config).await.unwrap().unwrap();
+    assert_eq!(old.data[120], 1);
+    old.data.remove(121);
+    old.data[8] = ACCOUNT_VERSION - 1;
+    context.set_account(&dao.config, &old.into());
+
+    let payer = context.payer.pubkey();
+    process(&mut context, &[dao.migrate(&payer, &dao.config)], &[]).await.unwrap();
+
+    let config: DaoConfig = account(&mut context, dao.config).await;
+    assert_eq!(config.version, ACCOUNT_VERSION);
+    assert_eq!(config.paused, flags);
+    assert_eq!(config.paused_until, before.paused_until);
+    assert_eq!(config.max_pause_duration, before.max_pause_duration);
+    assert_eq!(config.reputation_rule, before.reputation_rule);
+    assert_eq!(config.quorum_rule, before.quorum_rule);
+}
+
+#[tokio::test]
+async fn migrate_current_account_fails() {
+    let mut context = start().await;
+    let dao = initialize(&mut context, &DaoParams::default()).await;
+    let payer = context.payer.pubkey();
+    assert_dao_error(
+        process(&mut context, &[dao.migrate(&payer, &dao.config)], &[]).await,
+        DaoError::AccountUpToDate,
+    );
+}
+
+// Negative cases, one per reachable `DaoError`. `DefaultError`, `BumpError`, `Overflow`,
+// `Underflow`, `ProposalClosed`, `InvalidVoteType`, `SingleChoice` and
+// `InvalidInstructionData` can't currently be triggered through the instruction set.
+
+#[tokio::test]
+async fn initialize_rejects_empty_multisig() {
+    let mut context = start().await;
+    let params = DaoParams { multisig_keys: vec![], ..Default::default() };
+    let dao = Dao::new(1);
+    let payer = context.payer.pubkey();
+    assert_dao_error(
+        process(&mut context, &[dao.initialize(&payer, &params)], &[]).await,
+        DaoError::InvalidKeysLen,
+    );
+}
+
+#[tokio::test]
+async fn initialize_rejects_too_many_signers() {
+    let mut context = start().await;
+    let params = DaoParams { min_signers: 2, ..Default::default() };
+    let dao = Dao::new(1);
+    let payer = context.payer.pubkey();
+    assert_dao_error(
+        process(&mut context, &[dao.initialize(&payer, &params)], &[]).await,
+        DaoError::InvalidSigners,
+    );
+}
+
+#[tokio::test]
+async fn initialize_rejects_signers_met_only_by_duplicate_keys() {
+    let mut context = start().await;
+    let council = Pubkey::new_unique();
+    let params = DaoParams { multisig_keys: vec![council, council], min_signers: 2, ..Default::default() };
+    let dao = Dao::new(1);
+    let payer = context.payer.pubkey();
+    assert_dao_error(
+        process(&mut context, &[dao.initialize(&payer, &params)], &[]).await,
+        DaoError::InvalidSigners,
+    );
+}
+
+#[tokio::test]
+async fn unstake_in_same_slot_fails() {
+    let mut context = start().await;
+    let (dao, member) = setup_staker(&mut context, &DaoParams::default(), 100, 0).await;
+    assert_dao_error(
+        process(
+            &mut context,
+            &[dao.stake(&member.pubkey(), 10), dao.unstake(&member.pubkey(), 10)],
+            &[&member],
+        )
+        .await,
+        DaoError::InvalidSlot,
+    );
+}
+
+#[tokio::test]
+async fn unstake_with_open_votes_fails() {
+    let mut context = start().await;
+    let (dao, member) = setup_staker(&mut context, &DaoParams::default(), 100, 100).await;
+    open_proposal(&mut context, &dao, &member, &ProposalParams::bounty(1, Pubkey::new_unique(), BOUNTY)).await;
+    process(&mut context, &[dao.vote(&member.pubkey(), 1, 10, 0)], &[&member])
+        .await
+        .unwrap();
+    assert_dao_error(
+        process(&mut context, &[dao.unstake(&member.pubkey(), 100)], &[&member]).await,
+        DaoError::AccountsOpen,
+    );
+}
+
+#[tokio::test]
+async fn initialize_rejects_quorum_above_full_turnout() {
+    let mut context = start().await;
+    let params = DaoParams { vote_rule: TallyRule { quorum: 10_001, ..MAJORITY }, ..Default::default() };
+    let dao = Dao::new(1);
+    let payer = context.payer.pubkey();
+    assert_dao_error(
+        process(&mut context, &[dao.initialize(&payer, &params)], &[]).await,
+        DaoError::InvalidQuorum,
+    );
+}
+
+#[tokio::test]
+async fn initialize_rejects_unreachable_threshold() {
+    let mut context = start().await;
+    let params = DaoParams { bounty_rule: TallyRule { threshold: 10_000, ..MAJORITY }, ..Default::default() };
+    let dao = Dao::new(1);
+    let payer = context.payer.pubkey();
+    assert_dao_error(
+        process(&mut context, &[dao.initialize(&payer, &params)], &[]).await,
+        DaoError::InvalidThreshold,
+    );
+}
+
+async fn assert_create_fails(params: ProposalParams, error: DaoError) {
+    let mut context = start().await;
+    let (dao, member) = setup_staker(&mut context, &DaoParams::default(), 100, 100).await;
+    assert_dao_error(
+        process(&mut context, &[dao.create_proposal(&member.pubkey(), &params)], &[&member]).await,
+        error,
+    );
+}
+
+#[tokio::test]
+async fn initialize_rejects_weight_bonus_above_double() {
+    let mut context = start().await;
+    let rule = ReputationRule { max_weight_bonus: 10_001, ..NO_REPUTATION };
+    let params = DaoParams { reputation_rule: rule, ..Default::default() };
+    let dao = Dao::new(1);
+    let payer = context.payer.pubkey();
+    assert_dao_error(
+        process(&mut context, &[dao.initialize(&payer, &params)], &[]).await,
+        DaoError::InvalidReputationRule,
+    );
+}
+
+#[tokio::test]
+async fn create_proposal_without_reputation_fails() {
+    let mut context = start().await;
+    let rule = ReputationRule { min_to_propose: 1, ..NO_REPUTATION };
+    let params = DaoParams { reputation_rule: rule, ..Default::default() };
+    let (dao, member) = setup_staker(&mut context, &params, 100, 100).await;
+    assert_dao_error(
+        process(
+            &mut context,
+            &[dao.create_proposal(&member.pubkey(), &ProposalParams::bounty(1, Pubkey::new_unique(), BOUNTY))],
+            &[&member],
+        )
+        .await,
+        DaoError::InsufficientReputation,
+    );
+}
+
+#[tokio::test]
+async fn create_proposal_without_min_stake_fails() {
+    let mut context = start().await;
+    let (dao, member) = setup_staker(&mut context, &DaoParams::default(), 100, 0).await;
+    let params = ProposalParams::bounty(1, Pubkey::new_unique(), BOUNTY);
+    assert_dao_error(
+        process(&mut context, &[dao.create_proposal(&member.pubkey(), &params)], &[&member]).await,
+        DaoError::InvalidStakeAmount,
+    );
+}
+
+#[tokio::test]
+async fn create_proposal_with_wrong_id_fails() {
+    assert_create_fails(ProposalParams::bounty(2, Pubkey::new_unique(), BOUNTY), DaoError::InvalidProposalSeed).await;
+}
+
+#[tokio::test]
+async fn create_proposal_with_too_few_choices_fails() {
+    let mut params = ProposalParams::bounty(1, Pubkey::new_unique(), BOUNTY);
+    params.choices = 2;
+    assert_create_fails(params, DaoError::InvalidChoicesAmount).await;
+}
+
+#[tokio::test]
+async fn create_proposal_above_max_expiry_fails() {
+    let mut params = ProposalParams::bounty(1, Pubkey::new_unique(), BOUNTY);
+    params.expiry = 1_001;
+    assert_create_fails(params, DaoError::InvalidExpiry).await;
+}
+
+#[tokio::test]
+async fn create_proposal_with_long_name_fails() {
+    let mut params = ProposalParams::bounty(1, Pubkey::new_unique(), BOUNTY);
+    params.name = "n".repeat(33);
+    assert_create_fails(params, DaoError::InvalidName).await;
+}
+
+#[tokio::test]
+async fn create_proposal_with_long_gist_fails() {
+    let mut params = ProposalParams::bounty(1, Pubkey::new_unique(), BOUNTY);
+    params.gist = "g".repeat(73);
+    assert_create_fails(params, DaoError::InvalidGist).await;
+}
+
+#[tokio::test]
+async fn create_proposal_with_unknown_treasury_fails() {
+    let mut params = ProposalParams::bounty(1, Pubkey::new_unique(), BOUNTY);
+    params.treasury = "not-a-treasury".to_string();
+    assert_create_fails(params, DaoError::InvalidTreasury).await;
+}
+
+#[tokio::test]
+async fn execute_from_other_treasury_fails() {
+    let mut context = start().await;
+    let (dao, member) = setup_staker(&mut context, &DaoParams::default(), 100, 100).await;
+    let payee = Pubkey::new_unique();
+    open_proposal(&mut context, &dao, &member, &ProposalParams::bounty(1, payee, BOUNTY)).await;
+    process(&mut context, &[dao.vote(&member.pubkey(), 1, 10, 0)], &[&member])
+        .await
+        .unwrap();
+
+    let initializer = context.payer.pubkey();
+    assert_dao_error(
+        process(&mut context, &[dao.execute_proposal(&initializer, &member.pubkey(), &payee, 1, "dev-treasury")], &[])
+            .await,
+        DaoError::InvalidTreasury,
+    );
+}
+
+#[tokio::test]
+async fn create_executable_proposal_is_unsupported() {
+    let mut context = start().await;
+    let (dao, member) = setup_staker(&mut context, &DaoParams::default(), 100, 100).await;
+    let mut params = ProposalParams::bounty(1, Pubkey::new_unique(), 0);
+    params.proposal = ProposalType::Executable;
+    assert_dao_error(
+        process(&mut context, &[dao.create_proposal(&member.pubkey(), &params)], &[&member]).await,
+        DaoError::ExecutableNotSupported,
+    );
+}
+
+async fn assert_vote_fails(amount: u64, choice: u8, error: DaoError) {
+    let mut context = start().await;
+    let (dao, member) = setup_staker(&mut context, &DaoParams::default(), 100, 100).await;
+    open_proposal(&mut context, &dao, &member, &ProposalParams::bounty(1, Pubkey::new_unique(), BOUNTY)).await;
+    assert_dao_error(
+        process(&mut context, &[dao.vote(&member.pubkey(), 1, amount, choice)], &[&member])
+            .await,
+        error,
+    );
+}
+
+#[tokio::test]
+async fn vote_zero_fails() {
+    assert_vote_fails(0, 0, DaoError::InvalidVoteAmount).await;
+}
+
+#[tokio::test]
+async fn vote_more_than_stake_fails() {
+    assert_vote_fails(101, 0, DaoError::InsufficientStake).await;
+}
+
+#[tokio::test]
+async fn vote_on_unknown_choice_fails() {
+    assert_vote_fails(10, 3, DaoError::InvalidChoice).await;
+}
+
+#[tokio::test]
+async fn vote_after_expiry_fails() {
+    let mut context = start().await;
+    let (dao, member) = setup_staker(&mut context, &DaoParams::default(), 100, 100).await;
+    let params = ProposalParams::bounty(1, Pubkey::new_unique(), BOUNTY);
+    open_proposal(&mut context, &dao, &member, &params).await;
+    warp(&mut context, params.expiry).await;
+    assert_dao_error(
+        process(&mut context, &[dao.vote(&member.pubkey(), 1, 10, 0)], &[&member]).await,
+        DaoError::Expired,
+    );
+}
+
+#[tokio::test]
+async fn second_vote_on_same_proposal_fails() {
+    let mut context = start().await;
+    let (dao, member) = setup_staker(&mut context, &DaoParams::default(), 100, 100).await;
+    open_proposal(&mut context, &dao, &member, &ProposalParams::bounty(1, Pubkey::new_unique(), BOUNTY)).await;
+    process(&mut context, &[dao.vote(&member.pubkey(), 1, 10, 1)], &[&member]).await.unwrap();
+
+    // Votes are keyed by owner, so there's no second vote account to split the stake into
+    refresh_blockhash(&mut context).await;
+    assert!(process(&mut context, &[dao.vote(&member.pubkey(), 1, 90, 0)], &[&member]).await.is_err());
+    let proposal: Proposal = account(&mut context, dao.proposal(1)).await;
+    assert_eq!(proposal.vote_counts, vec![0, 10, 0]);
+}
+
+#[tokio::test]
+async fn vote_by_second_member_after_success_fails() {
+    let mut context = start().await;
+    let (dao, member) = setup_staker(&mut context, &DaoParams::default(), 100, 100).await;
+    open_proposal(&mut context, &dao, &member, &ProposalParams::bounty(1, Pubkey::new_unique(), BOUNTY)).await;
+    process(&mut context, &[dao.vote(&member.pubkey(), 1, 100, 0)], &[&member])
+        .await
+        .unwrap();
+
+    let other: Keypair = common::member(&mut context, &dao, 10).await;
+    process(&mut context, &[dao.init_stake(&other.pubkey()), dao.stake(&other.pubkey(), 10)], &[&other])
+        .await
+        .unwrap();
+    assert_dao_error(
+        process(&mut context, &[dao.vote(&other.pubkey(), 1, 10, 0)], &[&other]).await,
+        DaoError::InvalidProposalStatus,
+    );
+}
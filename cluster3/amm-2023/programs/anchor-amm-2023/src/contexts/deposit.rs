@@ -51,7 +51,7 @@ pub struct Deposit<'info> {
     pub user_lp: Box<Account<'info, TokenAccount>>,
     
     /// CHECK: just a pda for signing
-    #[account(seeds = [b"auth"], bump = config.auth_bump)]
+    #[account(seeds = [b"auth", config.key().as_ref()], bump = config.auth_bump)]
     pub auth: UncheckedAccount<'info>,
     #[account(
         has_one = mint_x,
@@ -131,6 +131,7 @@ impl<'info> Deposit<'info> {
 
         let seeds = &[
             &b"auth"[..],
+            &self.config.key().to_bytes()[..],
             &[self.config.auth_bump],
         ];
 
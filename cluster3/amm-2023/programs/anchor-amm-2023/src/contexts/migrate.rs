@@ -0,0 +1,153 @@
+use std::collections::BTreeMap;
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount, Transfer, SetAuthority, CloseAccount, transfer, set_authority, close_account};
+use anchor_spl::token::spl_token::instruction::AuthorityType;
+use anchor_spl::associated_token::AssociatedToken;
+use crate::state::config::Config;
+use crate::errors::AmmError;
+
+// Pools created while every pool shared the `[b"auth"]` vault authority keep their vaults and
+// LP mint under it. This moves them over to the pool's own authority, after which the pool works
+// again. Anyone can run it, since the tokens never leave the pool. Running it twice fails, as the
+// old vaults are closed and the new ones already exist
+#[derive(Accounts)]
+pub struct Migrate<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub mint_x: Box<Account<'info, Mint>>,
+    pub mint_y: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        seeds = [b"lp", config.key().as_ref()],
+        bump = config.lp_bump
+    )]
+    pub mint_lp: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = old_auth,
+    )]
+    pub old_vault_x: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = old_auth,
+    )]
+    pub old_vault_y: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = mint_x,
+        associated_token::authority = auth,
+    )]
+    pub vault_x: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = mint_y,
+        associated_token::authority = auth,
+    )]
+    pub vault_y: Box<Account<'info, TokenAccount>>,
+    /// CHECK: just a pda for signing, the authority every pool used to share
+    #[account(seeds = [b"auth"], bump)]
+    pub old_auth: UncheckedAccount<'info>,
+    /// CHECK: just a pda for signing
+    #[account(seeds = [b"auth", config.key().as_ref()], bump)]
+    pub auth: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [
+            b"config",
+            config.seed.to_le_bytes().as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> Migrate<'info> {
+    pub fn migrate(
+        &mut self,
+        bumps: &BTreeMap<String, u8>
+    ) -> Result<()> {
+        let (old_auth_bump, auth_bump) = (
+            *bumps.get("old_auth").ok_or(AmmError::BumpError)?,
+            *bumps.get("auth").ok_or(AmmError::BumpError)?
+        );
+        // The old authority has to be the one the LP mint was created under
+        require_keys_eq!(
+            self.mint_lp.mint_authority.ok_or(AmmError::InvalidAuthority)?,
+            self.old_auth.key(),
+            AmmError::InvalidAuthority
+        );
+
+        let seeds = &[
+            &b"auth"[..],
+            &[old_auth_bump],
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        self.move_vault(true, signer_seeds)?;
+        self.move_vault(false, signer_seeds)?;
+
+        let cpi_accounts = SetAuthority {
+            current_authority: self.old_auth.to_account_info(),
+            account_or_mint: self.mint_lp.to_account_info(),
+        };
+
+        let ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            cpi_accounts,
+            signer_seeds
+        );
+        set_authority(ctx, AuthorityType::MintTokens, Some(self.auth.key()))?;
+
+        self.config.auth_bump = auth_bump;
+        Ok(())
+    }
+
+    // Empty an old vault into the new one and close it
+    pub fn move_vault(
+        &self,
+        is_x: bool,
+        signer_seeds: &[&[&[u8]]]
+    ) -> Result<()> {
+        let (from, to, amount) = match is_x {
+            true => (self.old_vault_x.to_account_info(), self.vault_x.to_account_info(), self.old_vault_x.amount),
+            false => (self.old_vault_y.to_account_info(), self.vault_y.to_account_info(), self.old_vault_y.amount)
+        };
+
+        let cpi_accounts = Transfer {
+            from: from.clone(),
+            to,
+            authority: self.old_auth.to_account_info(),
+        };
+
+        let ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            cpi_accounts,
+            signer_seeds
+        );
+        transfer(ctx, amount)?;
+
+        let cpi_accounts = CloseAccount {
+            account: from,
+            destination: self.payer.to_account_info(),
+            authority: self.old_auth.to_account_info(),
+        };
+
+        let ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            cpi_accounts,
+            signer_seeds
+        );
+        close_account(ctx)
+    }
+}
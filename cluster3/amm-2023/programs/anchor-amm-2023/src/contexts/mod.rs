@@ -3,8 +3,10 @@ pub mod deposit;
 pub mod withdraw;
 pub mod swap;
 pub mod update;
+pub mod migrate;
 pub use initialize::*;
 pub use deposit::*;
 pub use withdraw::*;
 pub use swap::*;
-pub use update::*;
\ No newline at end of file
+pub use update::*;
+pub use migrate::*;
\ No newline at end of file
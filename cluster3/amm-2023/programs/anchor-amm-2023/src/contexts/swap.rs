@@ -39,7 +39,7 @@ pub struct Swap<'info> {
     )]
     pub vault_y: Box<Account<'info, TokenAccount>>,
     ///CHECKED: This is not dangerous. It's just used for signing.
-    #[account(seeds = [b"auth"], bump = config.auth_bump)]
+    #[account(seeds = [b"auth", config.key().as_ref()], bump = config.auth_bump)]
     pub auth: UncheckedAccount<'info>,
     #[account(
         has_one = mint_x,
@@ -131,6 +131,7 @@ impl<'info> Swap<'info> {
 
         let seeds = &[
             &b"auth"[..],
+            &self.config.key().to_bytes()[..],
             &[self.config.auth_bump],
         ];
 
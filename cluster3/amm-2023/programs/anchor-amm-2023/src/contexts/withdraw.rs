@@ -53,7 +53,7 @@ pub struct Withdraw<'info> {
     pub user_lp: Box<Account<'info, TokenAccount>>,
     
     /// CHECK: just a pda for signing
-    #[account(seeds = [b"auth"], bump = config.auth_bump)]
+    #[account(seeds = [b"auth", config.key().as_ref()], bump = config.auth_bump)]
     pub auth: UncheckedAccount<'info>,
     #[account(
         has_one = mint_x,
@@ -116,6 +116,7 @@ impl<'info> Withdraw<'info> {
         
         let seeds = &[
             &b"auth"[..],
+            &self.config.key().to_bytes()[..],
             &[self.config.auth_bump],
         ];
 
@@ -67,4 +67,11 @@ pub mod anchor_amm_2023 {
     ) -> Result<()> {
         ctx.accounts.unlock()
     }
+
+    pub fn migrate(
+        ctx: Context<Migrate>,
+    ) -> Result<()> {
+        // Move a pool's vaults and LP mint over from the shared authority to its own
+        ctx.accounts.migrate(&ctx.bumps)
+    }
 }
\ No newline at end of file
@@ -9,7 +9,7 @@ pub struct Config {
     pub mint_y: Pubkey,           // Token Y Mint
     pub fee: u16,                 // Swap fee in basis points
     pub locked: bool,
-    pub auth_bump: u8,
+    pub auth_bump: u8,            // Vault authority, seeded by this config
     pub config_bump: u8,
     pub lp_bump: u8
 }
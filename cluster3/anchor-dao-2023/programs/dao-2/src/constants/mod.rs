@@ -4,4 +4,6 @@ pub const U64_L: usize = 8;
 pub const U16_L: usize = 2;
 pub const BOOL_L: usize = 1;
 pub const OPTION_L: usize = 1;
-pub const U8_L: usize = 1;
\ No newline at end of file
+pub const U8_L: usize = 1;
+pub const MAX_BPS: u64 = 10_000;
+pub const MAX_PERCENT: u64 = 100;
\ No newline at end of file
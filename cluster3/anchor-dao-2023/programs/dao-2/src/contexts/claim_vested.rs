@@ -0,0 +1,80 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{token::{Token, TokenAccount, Transfer as TransferSpl, transfer as transfer_spl, Mint}, associated_token::AssociatedToken};
+
+use crate::state::{config::DaoConfig, Vesting};
+
+#[derive(Accounts)]
+pub struct ClaimVested<'info> {
+    #[account(mut)]
+    beneficiary: Signer<'info>,
+    #[account(
+        init_if_needed,
+        payer = beneficiary,
+        associated_token::mint = mint,
+        associated_token::authority = beneficiary
+    )]
+    beneficiary_ata: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds=[b"vesting", config.key().as_ref()],
+        bump = vesting.bump
+    )]
+    vesting: Account<'info, Vesting>,
+    #[account(
+        mut,
+        seeds=[b"vesting-vault", config.key().as_ref()],
+        bump = vesting.vault_bump,
+        token::mint = mint,
+        token::authority = auth
+    )]
+    vault: Account<'info, TokenAccount>,
+    #[account(
+        seeds=[b"auth", config.key().as_ref()],
+        bump = config.auth_bump
+    )]
+    ///CHECK: This is safe. It's just used to sign things
+    auth: UncheckedAccount<'info>,
+    #[account(
+        seeds=[b"mint", config.key().as_ref()],
+        bump = config.mint_bump
+    )]
+    mint: Account<'info, Mint>,
+    #[account(
+        seeds=[b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    config: Account<'info, DaoConfig>,
+    token_program: Program<'info, Token>,
+    associated_token_program: Program<'info, AssociatedToken>,
+    system_program: Program<'info, System>
+}
+
+impl<'info> ClaimVested<'info> {
+    pub fn claim_vested(
+        &mut self
+    ) -> Result<()> {
+        let amount = self.vesting.claim(self.beneficiary.key())?;
+
+        let accounts = TransferSpl {
+            from: self.vault.to_account_info(),
+            to: self.beneficiary_ata.to_account_info(),
+            authority: self.auth.to_account_info()
+        };
+
+        let seeds = &[
+            &b"auth"[..],
+            &self.config.key().to_bytes()[..],
+            &[self.config.auth_bump],
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            accounts,
+            signer_seeds
+        );
+
+        transfer_spl(ctx, amount)
+    }
+}
@@ -1,6 +1,6 @@
-use anchor_lang::{prelude::*, system_program::{Transfer, transfer}};
+use anchor_lang::{prelude::*, system_program::{Transfer, transfer}, solana_program::{instruction::Instruction, program::invoke_signed}};
 
-use crate::{state::{config::DaoConfig, Proposal, ProposalType}, errors::DaoError};
+use crate::state::{config::DaoConfig, Proposal, ProposalType};
 
 #[derive(Accounts)]
 pub struct CleanupProposal<'info> {
@@ -16,11 +16,13 @@ pub struct CleanupProposal<'info> {
     )]
     proposal: Account<'info, Proposal>,
     #[account(
+        mut,
         seeds=[b"treasury", config.key().as_ref()],
         bump = config.treasury_bump
     )]
     treasury: SystemAccount<'info>,
     #[account(
+        mut,
         seeds=[b"config", config.seed.to_le_bytes().as_ref()],
         bump = config.config_bump
     )]
@@ -39,7 +41,8 @@ impl<'info> CleanupProposal<'info> {
     }
 
     pub fn execute_proposal(
-        &mut self
+        &mut self,
+        remaining_accounts: &[AccountInfo<'info>]
     ) -> Result<()> {
         // Try finalize proposal
         self.proposal.try_finalize();
@@ -47,13 +50,14 @@ impl<'info> CleanupProposal<'info> {
         self.proposal.is_succeeded()?;
         match self.proposal.proposal {
             ProposalType::Bounty(payee, payout) => self.payout_bounty(payee, payout),
-            ProposalType::Executable => self.execute_tx(),
+            ProposalType::Executable => self.execute_tx(remaining_accounts),
             ProposalType::Vote => self.finalize_vote(),
+            ProposalType::Redemption(fee, paused) => self.config.set_redemption(fee, paused),
         }
     }
 
     pub fn finalize_vote(&self) -> Result<()> {
-        msg!("Vote result: {} / {}", self.proposal.votes, self.proposal.quorum);
+        msg!("Vote result: {} yes / {} no of {} staked", self.proposal.yes, self.proposal.no, self.proposal.staked_supply);
         msg!("Vote has {:?}", self.proposal.result);
         Ok(())
     }
@@ -87,15 +91,24 @@ impl<'info> CleanupProposal<'info> {
         transfer(ctx, payout)
     }
 
+    // Replay the stored instructions signed by the treasury
     pub fn execute_tx(
-        &self
+        &self,
+        remaining_accounts: &[AccountInfo<'info>]
     ) -> Result<()> {
-        // Instruction
+        let instruction_accounts = self.proposal.instruction_accounts(remaining_accounts)?;
 
-        // Accounts
+        let seeds = &[
+            &b"treasury"[..],
+            &self.config.key().to_bytes()[..],
+            &[self.config.treasury_bump],
+        ];
+
+        let signer_seeds = &[&seeds[..]];
 
-        // Signers
-        
+        for (data, ix_accounts) in self.proposal.instructions.iter().zip(instruction_accounts) {
+            invoke_signed(&Instruction::from(data), &ix_accounts, signer_seeds)?;
+        }
         Ok(())
     }
-}
\ No newline at end of file
+}
@@ -1,9 +1,9 @@
 use anchor_lang::{prelude::*, system_program::{Transfer, transfer}};
 
-use crate::{state::{config::DaoConfig, Proposal, StakeState, ProposalType}, errors::DaoError};
+use crate::state::{config::DaoConfig, InstructionData, Proposal, StakeState, ProposalType};
 
 #[derive(Accounts)]
-#[instruction(id: u64)]
+#[instruction(id: u64, name: String, gist: String, proposal: ProposalType, quorum: u64, threshold: u64, expiry: u64, instructions: Vec<InstructionData>)]
 pub struct CreateProposal<'info> {
     #[account(mut)]
     owner: Signer<'info>,
@@ -18,7 +18,7 @@ pub struct CreateProposal<'info> {
         payer = owner,
         seeds=[b"proposal", config.key().as_ref(), id.to_le_bytes().as_ref()],
         bump,
-        space = Proposal::LEN
+        space = Proposal::space(&instructions)
     )]
     proposal: Account<'info, Proposal>,
     #[account(
@@ -43,7 +43,9 @@ impl<'info> CreateProposal<'info> {
         gist: String,
         proposal: ProposalType,
         quorum: u64,
+        threshold: u64,
         expiry: u64,
+        instructions: Vec<InstructionData>,
         bump: u8
     ) -> Result<()> {
         // Make sure user has staked
@@ -61,7 +63,11 @@ impl<'info> CreateProposal<'info> {
             gist, // 72 bytes (39 bytes + / + 32 byte ID)
             proposal,
             quorum,
+            threshold,
+            self.config.staked_supply,
+            self.config.max_age_bonus,
             expiry,
+            instructions,
             bump
         )
     }
@@ -1,12 +1,12 @@
 use std::collections::BTreeMap;
 
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Mint, Token};
+use anchor_spl::token::{Mint, Token, TokenAccount, MintTo, mint_to};
 
-use crate::{errors::DaoError, state::DaoConfig};
+use crate::{errors::DaoError, state::{DaoConfig, Vesting, VestingSchedule}};
 
 #[derive(Accounts)]
-#[instruction(seed: u64)]
+#[instruction(seed: u64, issue_price: u64, issue_amount: u64, proposal_fee: u64, max_supply: u64, min_quorum: u64, max_expiry: u64, max_age_bonus: u16, max_age: u64, vesting_schedules: Vec<VestingSchedule>)]
 pub struct Initialize<'info> {
     #[account(mut)]
     initializer: Signer<'info>,
@@ -38,6 +38,23 @@ pub struct Initialize<'info> {
         space = DaoConfig::LEN
     )]
     config: Account<'info, DaoConfig>,
+    #[account(
+        init,
+        payer = initializer,
+        seeds=[b"vesting", config.key().as_ref()],
+        bump,
+        space = Vesting::space(vesting_schedules.len())
+    )]
+    vesting: Account<'info, Vesting>,
+    #[account(
+        init,
+        payer = initializer,
+        seeds=[b"vesting-vault", config.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = auth
+    )]
+    vault: Box<Account<'info, TokenAccount>>,
     token_program: Program<'info, Token>,
     system_program: Program<'info, System>
 }
@@ -53,6 +70,9 @@ impl<'info> Initialize<'info> {
         max_supply: u64,
         min_quorum: u64,
         max_expiry: u64,
+        max_age_bonus: u16,
+        max_age: u64,
+        vesting_schedules: Vec<VestingSchedule>
     ) -> Result<()> {
         let (
             auth_bump,
@@ -66,6 +86,12 @@ impl<'info> Initialize<'info> {
             *bumps.get("treasury").ok_or(DaoError::BumpError)?,
         );
 
+        let reserved = self.vesting.init(
+            vesting_schedules,
+            *bumps.get("vesting").ok_or(DaoError::BumpError)?,
+            *bumps.get("vault").ok_or(DaoError::BumpError)?
+        )?;
+
         self.config.init(
             seed,
             issue_price,
@@ -74,10 +100,48 @@ impl<'info> Initialize<'info> {
             max_supply,
             min_quorum,
             max_expiry,
+            max_age_bonus,
+            max_age,
             auth_bump,
             config_bump,
             mint_bump,
             treasury_bump
-        )
+        )?;
+
+        // Mint the vesting reserve up front so it counts toward the max supply
+        self.config.check_max_supply(0, reserved)?;
+        self.mint_reserve(reserved, auth_bump)
+    }
+
+    pub fn mint_reserve(
+        &self,
+        amount: u64,
+        auth_bump: u8
+    ) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let accounts = MintTo {
+            mint: self.mint.to_account_info(),
+            to: self.vault.to_account_info(),
+            authority: self.auth.to_account_info()
+        };
+
+        let seeds = &[
+            &b"auth"[..],
+            &self.config.key().to_bytes()[..],
+            &[auth_bump],
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            accounts,
+            signer_seeds
+        );
+
+        mint_to(ctx, amount)
     }
 }
\ No newline at end of file
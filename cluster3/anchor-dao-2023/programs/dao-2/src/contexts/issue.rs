@@ -21,6 +21,7 @@ pub struct IssueTokens<'info> {
     ///CHECK: This is safe. It's just used to sign things
     auth: UncheckedAccount<'info>,
     #[account(
+        mut,
         seeds=[b"treasury", config.key().as_ref()],
         bump = config.treasury_bump
     )]
@@ -61,6 +62,9 @@ impl<'info> IssueTokens<'info> {
     pub fn issue_tokens(
         &self
     ) -> Result<()> {
+        // Make sure the sale stays under the max supply
+        self.config.check_max_supply(self.mint.supply, self.config.issue_amount)?;
+
         let accounts = MintTo {
             mint: self.mint.to_account_info(),
             to: self.initializer_ata.to_account_info(),
@@ -76,11 +80,11 @@ impl<'info> IssueTokens<'info> {
         let signer_seeds = &[&seeds[..]];
 
         let ctx = CpiContext::new_with_signer(
-            self.system_program.to_account_info(),
+            self.token_program.to_account_info(),
             accounts,
             signer_seeds
         );
 
-        mint_to(ctx, self.config.issue_price)
+        mint_to(ctx, self.config.issue_amount)
     }
 }
\ No newline at end of file
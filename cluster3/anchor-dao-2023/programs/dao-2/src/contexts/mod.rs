@@ -7,6 +7,8 @@ pub mod create_proposal;
 pub mod cleanup_proposal;
 pub mod vote;
 pub mod unvote;
+pub mod redeem;
+pub mod claim_vested;
 
 pub use initialize::*;
 pub use issue::*;
@@ -17,3 +19,5 @@ pub use create_proposal::*;
 pub use cleanup_proposal::*;
 pub use vote::*;
 pub use unvote::*;
+pub use redeem::*;
+pub use claim_vested::*;
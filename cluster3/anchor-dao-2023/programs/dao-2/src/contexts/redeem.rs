@@ -0,0 +1,104 @@
+use anchor_lang::{prelude::*, system_program::{Transfer, transfer}};
+use anchor_spl::{token::{Token, TokenAccount, Burn, burn, Mint}, associated_token::AssociatedToken};
+
+use crate::{state::DaoConfig, errors::DaoError};
+
+#[derive(Accounts)]
+pub struct Redeem<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = owner
+    )]
+    owner_ata: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds=[b"treasury", config.key().as_ref()],
+        bump = config.treasury_bump
+    )]
+    treasury: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds=[b"mint", config.key().as_ref()],
+        bump = config.mint_bump
+    )]
+    mint: Account<'info, Mint>,
+    #[account(
+        seeds=[b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    config: Account<'info, DaoConfig>,
+    token_program: Program<'info, Token>,
+    associated_token_program: Program<'info, AssociatedToken>,
+    system_program: Program<'info, System>
+}
+
+impl<'info> Redeem<'info> {
+    pub fn redeem(
+        &self,
+        amount: u64
+    ) -> Result<()> {
+        // Make sure redemptions are open
+        self.config.check_redemption_open()?;
+        require!(amount > 0, DaoError::InvalidRedeemAmount);
+        // Work out the share before burning shrinks the supply. The supply includes the vesting
+        // reserve on purpose, so its share of the treasury is left for the beneficiaries
+        let payout = self.config.redemption_payout(amount, self.available_lamports()?, self.mint.supply)?;
+        self.burn_tokens(amount)?;
+        self.withdraw_sol(payout)
+    }
+
+    // Lamports the treasury can pay out and stay rent exempt
+    pub fn available_lamports(
+        &self
+    ) -> Result<u64> {
+        let rent = Rent::get()?.minimum_balance(0);
+        Ok(self.treasury.lamports().saturating_sub(rent))
+    }
+
+    pub fn burn_tokens(
+        &self,
+        amount: u64
+    ) -> Result<()> {
+        let accounts = Burn {
+            mint: self.mint.to_account_info(),
+            from: self.owner_ata.to_account_info(),
+            authority: self.owner.to_account_info()
+        };
+
+        let ctx = CpiContext::new(
+            self.token_program.to_account_info(),
+            accounts
+        );
+
+        burn(ctx, amount)
+    }
+
+    pub fn withdraw_sol(
+        &self,
+        amount: u64
+    ) -> Result<()> {
+        let accounts = Transfer {
+            from: self.treasury.to_account_info(),
+            to: self.owner.to_account_info()
+        };
+
+        let seeds = &[
+            &b"treasury"[..],
+            &self.config.key().to_bytes()[..],
+            &[self.config.treasury_bump],
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let ctx = CpiContext::new_with_signer(
+            self.system_program.to_account_info(),
+            accounts,
+            signer_seeds
+        );
+
+        transfer(ctx, amount)
+    }
+}
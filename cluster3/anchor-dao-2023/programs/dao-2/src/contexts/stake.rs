@@ -40,6 +40,7 @@ pub struct Stake<'info> {
     )]
     stake_state: Account<'info, StakeState>,
     #[account(
+        mut,
         seeds=[b"config", config.seed.to_le_bytes().as_ref()],
         bump = config.config_bump
     )]
@@ -54,7 +55,8 @@ impl<'info> Stake<'info> {
         &mut self,
         amount: u64
     ) -> Result<()> {
-        self.stake_state.stake(amount)?;
+        self.stake_state.stake(amount, self.config.max_expiry)?;
+        self.config.add_stake(amount)?;
 
         let accounts = TransferSpl {
             from: self.owner_ata.to_account_info(),
@@ -70,10 +72,11 @@ impl<'info> Stake<'info> {
     }
 
     pub fn withdraw_tokens(
-        &self,
+        &mut self,
         amount: u64
     ) -> Result<()> {
         self.stake_state.unstake(amount)?;
+        self.config.remove_stake(amount)?;
 
         let accounts = TransferSpl {
             from: self.stake_ata.to_account_info(),
@@ -54,7 +54,7 @@ impl<'info> Unvote<'info> {
     ) -> Result<()> {
         self.proposal.is_open()?;
         self.proposal.check_expiry()?;
-        self.proposal.remove_vote(self.vote.amount)?;
+        self.proposal.remove_vote(self.vote.stake, self.vote.amount, self.vote.approve)?;
         self.stake_state.remove_account()
     }
 }
\ No newline at end of file
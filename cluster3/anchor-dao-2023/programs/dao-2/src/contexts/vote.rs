@@ -38,6 +38,7 @@ impl<'info> Vote<'info> {
     pub fn vote(
         &mut self,
         amount: u64,
+        approve: bool,
         bump: u8
     ) -> Result<()> {
         // Check proposal is open
@@ -46,16 +47,22 @@ impl<'info> Vote<'info> {
         self.proposal.check_expiry()?;
         // Ensure vote amount > 0
         require!(amount > 0, DaoError::InvalidVoteAmount);
-        // Add vote to proposal
-        self.proposal.add_vote(amount)?;
         // Make sure user has staked
         self.stake_state.check_stake_amount(amount)?;
+        // Only stake held before the snapshot counts toward it
+        self.stake_state.check_votable_amount(amount, self.proposal.created)?;
+        // Older stake counts for more
+        let weight = self.stake_state.weight(amount, &self.config)?;
+        // Add vote to proposal
+        self.proposal.add_vote(amount, weight, approve)?;
         // Add a vote account to the stake state
         self.stake_state.add_account()?;
         // Initialize vote
         self.vote.init(
             self.owner.key(),
+            weight,
             amount,
+            approve,
             bump
         )
     }
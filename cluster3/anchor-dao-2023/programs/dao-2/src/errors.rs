@@ -26,6 +26,8 @@ pub enum DaoError {
     InvalidProposalSeed,
     #[msg("Invalid quorum")]
     InvalidQuorum,
+    #[msg("Invalid threshold")]
+    InvalidThreshold,
     #[msg("Invalid expiry")]
     InvalidExpiry,
     #[msg("Proposal closed")]
@@ -35,5 +37,23 @@ pub enum DaoError {
     #[msg("Invalid proposal status")]
     InvalidProposalStatus,
     #[msg("Invalid stake amount")]
-    InvalidStakeAmount
+    InvalidStakeAmount,
+    #[msg("Invalid redeem amount")]
+    InvalidRedeemAmount,
+    #[msg("Invalid redemption fee")]
+    InvalidRedemptionFee,
+    #[msg("Redemptions are paused")]
+    RedemptionPaused,
+    #[msg("Invalid instruction data")]
+    InvalidInstructionData,
+    #[msg("Invalid stake age bonus")]
+    InvalidAgeBonus,
+    #[msg("Max supply reached")]
+    MaxSupplyReached,
+    #[msg("Invalid vesting schedule")]
+    InvalidVestingSchedule,
+    #[msg("Nothing to claim")]
+    NothingToClaim,
+    #[msg("Stake deposited after the proposal's snapshot can't vote on it")]
+    StakeAfterSnapshot
 }
\ No newline at end of file
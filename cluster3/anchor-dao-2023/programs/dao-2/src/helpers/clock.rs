@@ -0,0 +1,24 @@
+use anchor_lang::solana_program::{clock::Clock, entrypoint::SUCCESS, program_stubs::{set_syscall_stubs, SyscallStubs}};
+use std::cell::Cell;
+
+// There's no clock sysvar off-chain, so unit tests stand one in at the slot they need
+
+thread_local! {
+    static SLOT: Cell<u64> = const { Cell::new(0) };
+}
+
+struct TestClock;
+
+impl SyscallStubs for TestClock {
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = Clock { slot: SLOT.with(|slot| slot.get()), ..Clock::default() };
+        unsafe { *(var_addr as *mut Clock) = clock };
+        SUCCESS
+    }
+}
+
+// Tests run on their own threads, so each one gets its own slot
+pub fn set_slot(slot: u64) {
+    set_syscall_stubs(Box::new(TestClock));
+    SLOT.with(|current| current.set(slot));
+}
@@ -0,0 +1,103 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{config::DaoConfig, InstructionAccount, InstructionData, Proposal, ProposalStatus, ProposalType, StakeState, VestingSchedule};
+
+use super::clock::set_slot;
+
+// Accounts for unit tests, with everything a test doesn't set left at zero
+
+pub fn config(redemption_fee: u16, max_age_bonus: u16, max_age: u64) -> DaoConfig {
+    DaoConfig {
+        seed: 0,
+        issue_price: 0,
+        issue_amount: 0,
+        proposal_fee: 0,
+        max_supply: u64::MAX,
+        min_quorum: 0,
+        max_expiry: u64::MAX,
+        proposal_count: 0,
+        auth_bump: 0,
+        config_bump: 0,
+        mint_bump: 0,
+        treasury_bump: 0,
+        redemption_fee,
+        redemption_paused: false,
+        max_age_bonus,
+        max_age,
+        staked_supply: 0
+    }
+}
+
+pub fn proposal() -> Proposal {
+    Proposal {
+        id: 1,
+        name: String::new(),
+        gist: String::new(),
+        proposal: ProposalType::Vote,
+        result: ProposalStatus::Open,
+        quorum: 0,
+        threshold: 0,
+        staked_supply: 0,
+        yes: 0,
+        no: 0,
+        expiry: 0,
+        bump: 0,
+        instructions: vec![],
+        stake_voted: 0,
+        created: 0,
+        max_age_bonus: 0
+    }
+}
+
+// Open until slot 1000 with a snapshot of 100 staked, a 10% quorum and a majority threshold.
+// Fully aged stake weighs double
+pub fn open_proposal() -> Proposal {
+    set_slot(10);
+    Proposal { quorum: 10, threshold: 50, staked_supply: 100, expiry: 1_000, created: 5, max_age_bonus: 10_000, ..proposal() }
+}
+
+pub fn stake_state(amount: u64, staked_at: u64) -> StakeState {
+    StakeState {
+        owner: Pubkey::default(),
+        amount,
+        accounts: 0,
+        updated: staked_at,
+        vault_bump: 0,
+        auth_bump: 0,
+        state_bump: 0,
+        staked_at,
+        deposits: [0; 2],
+        deposit_slots: [0; 2]
+    }
+}
+
+pub fn schedule(beneficiary: Pubkey, amount: u64, cliff: u64, duration: u64) -> VestingSchedule {
+    VestingSchedule { beneficiary, amount, cliff, duration, claimed: 0 }
+}
+
+pub fn instruction(program_id: Pubkey, keys: &[Pubkey]) -> InstructionData {
+    InstructionData {
+        program_id,
+        data: vec![],
+        keys: keys.iter().map(|pubkey| InstructionAccount { pubkey: *pubkey, is_signer: false, is_writable: true }).collect()
+    }
+}
+
+// Owns what an `AccountInfo` borrows
+pub struct TestAccount {
+    pub key: Pubkey,
+    pub lamports: u64,
+    pub data: Vec<u8>,
+    pub owner: Pubkey,
+    pub executable: bool
+}
+
+impl TestAccount {
+    pub fn new(key: Pubkey, executable: bool) -> Self {
+        TestAccount { key, lamports: 0, data: vec![], owner: Pubkey::default(), executable }
+    }
+
+    pub fn info(&mut self) -> AccountInfo<'_> {
+        AccountInfo::new(&self.key, false, true, &mut self.lamports, &mut self.data, &self.owner, self.executable, 0)
+    }
+}
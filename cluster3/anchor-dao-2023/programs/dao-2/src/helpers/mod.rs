@@ -0,0 +1,4 @@
+#[cfg(test)]
+pub mod clock;
+#[cfg(test)]
+pub mod fixtures;
@@ -4,13 +4,15 @@ mod contexts;
 use contexts::*;
 mod constants;
 mod state;
+use state::{InstructionData, ProposalType, VestingSchedule};
 mod errors;
+mod helpers;
 
 declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
 
 #[program]
 pub mod dao_2 {
-    use crate::{errors::DaoError, state::ProposalType};
+    use crate::errors::DaoError;
 
     use super::*;
 
@@ -23,9 +25,12 @@ pub mod dao_2 {
         proposal_fee: u64,
         max_supply: u64,
         min_quorum: u64,
-        max_expiry: u64
+        max_expiry: u64,
+        max_age_bonus: u16,
+        max_age: u64,
+        vesting_schedules: Vec<VestingSchedule>
     ) -> Result<()> {
-        ctx.accounts.init(seed, &ctx.bumps, issue_price, issue_amount, proposal_fee, max_supply, min_quorum, max_expiry)
+        ctx.accounts.init(seed, &ctx.bumps, issue_price, issue_amount, proposal_fee, max_supply, min_quorum, max_expiry, max_age_bonus, max_age, vesting_schedules)
     }
 
     // Handle token issuance
@@ -34,6 +39,18 @@ pub mod dao_2 {
         ctx.accounts.issue_tokens()
     }
 
+    // Burn DAO tokens for their share of the treasury
+    pub fn redeem_tokens(ctx: Context<Redeem>, amount: u64) -> Result<()> {
+        // Pay out lamports pro rata, less the redemption fee
+        ctx.accounts.redeem(amount)
+    }
+
+    // Claim founder or contributor tokens that have vested
+    pub fn claim_vested(ctx: Context<ClaimVested>) -> Result<()> {
+        // Transfer them out of the vesting vault
+        ctx.accounts.claim_vested()
+    }
+
     // Initialize a stake account for adding DAO tokens
     pub fn init_stake(ctx: Context<InitializeStake>) -> Result<()> {
         // Create a stake account
@@ -65,9 +82,10 @@ pub mod dao_2 {
         name: String, 
         gist: String, 
         proposal: ProposalType, 
+        quorum: u64, 
         threshold: u64, 
-        amount: u64, 
-        data: Vec<u8>
+        expiry: u64, 
+        instructions: Vec<InstructionData>
     ) -> Result<()> {
         // Pay a proposal fee to DAO treasury
         ctx.accounts.pay_proposal_fee()?;
@@ -78,8 +96,10 @@ pub mod dao_2 {
             name, 
             gist,
             proposal,
+            quorum, 
             threshold, 
-            amount,
+            expiry,
+            instructions,
             *ctx.bumps.get("proposal").ok_or(DaoError::BumpError)?
         )
     }
@@ -93,17 +113,17 @@ pub mod dao_2 {
     }
 
      // Cleanup a proposal
-     pub fn execute_proposal(
-        ctx: Context<CleanupProposal>, 
+     pub fn execute_proposal<'info>(
+        ctx: Context<'_, '_, '_, 'info, CleanupProposal<'info>>, 
     ) -> Result<()> {
-        // Pay a proposal fee to DAO treasury
-        ctx.accounts.execute_proposal()
+        // Pay out, or replay the stored instructions with the accounts passed in
+        ctx.accounts.execute_proposal(ctx.remaining_accounts)
     }
 
     // Vote on a proposal
-    pub fn vote(ctx: Context<Vote>, amount: u64) -> Result<()> {
-        // Increment total number of votes in the proposal
-        ctx.accounts.vote(amount, *ctx.bumps.get("vote").ok_or(DaoError::BumpError)?)
+    pub fn vote(ctx: Context<Vote>, amount: u64, approve: bool) -> Result<()> {
+        // Add to the yes or no tally of the proposal
+        ctx.accounts.vote(amount, approve, *ctx.bumps.get("vote").ok_or(DaoError::BumpError)?)
     }
 
     // Close a voting position after a proposal has passed/expired
@@ -14,11 +14,16 @@ pub struct DaoConfig {
     pub auth_bump: u8,
     pub config_bump: u8,
     pub mint_bump: u8,
-    pub treasury_bump: u8
+    pub treasury_bump: u8,
+    pub redemption_fee: u16, // Kept in the treasury out of every redemption, in bps
+    pub redemption_paused: bool,
+    pub max_age_bonus: u16, // Extra vote weight fully aged stake gets, in bps. 0 weights every token the same
+    pub max_age: u64, // Slots it takes stake to earn the full bonus
+    pub staked_supply: u64 // Tokens staked across all members, which quorums are measured against
 }
 
 impl DaoConfig {
-    pub const LEN: usize = 8 + 6 * U64_L + 4 * U8_L;
+    pub const LEN: usize = 8 + 10 * U64_L + 4 * U8_L + 2 * U16_L + BOOL_L;
 
     pub fn init(
         &mut self,
@@ -29,11 +34,15 @@ impl DaoConfig {
         max_supply: u64,
         min_quorum: u64,
         max_expiry: u64,
+        max_age_bonus: u16,
+        max_age: u64,
         auth_bump: u8,
         config_bump: u8,
         mint_bump: u8,
         treasury_bump: u8        
     ) -> Result<()> {
+        require!(max_age_bonus == 0 || max_age > 0, DaoError::InvalidAgeBonus);
+        require!(min_quorum <= MAX_PERCENT, DaoError::InvalidQuorum);
         self.seed = seed;
         self.issue_price = issue_price;
         self.issue_amount = issue_amount;
@@ -46,6 +55,11 @@ impl DaoConfig {
         self.config_bump = config_bump;
         self.mint_bump = mint_bump;
         self.treasury_bump = treasury_bump;
+        self.redemption_fee = 0;
+        self.redemption_paused = false;
+        self.max_age_bonus = max_age_bonus;
+        self.max_age = max_age;
+        self.staked_supply = 0;
         Ok(())
     }
 
@@ -55,13 +69,107 @@ impl DaoConfig {
         Ok(())
     }
 
+    pub fn add_stake(&mut self, amount: u64) -> Result<()> {
+        self.staked_supply = self.staked_supply.checked_add(amount).ok_or(DaoError::Overflow)?;
+        Ok(())
+    }
+
+    pub fn remove_stake(&mut self, amount: u64) -> Result<()> {
+        self.staked_supply = self.staked_supply.checked_sub(amount).ok_or(DaoError::Underflow)?;
+        Ok(())
+    }
+
     pub fn check_min_quorum(&self, quorum: u64) -> Result<()> {
         require!(self.min_quorum <= quorum, DaoError::InvalidQuorum);
         Ok(())
     }
 
+    // Only governance can change how redemptions work
+    pub fn set_redemption(&mut self, fee: u16, paused: bool) -> Result<()> {
+        require!(fee as u64 <= MAX_BPS, DaoError::InvalidRedemptionFee);
+        self.redemption_fee = fee;
+        self.redemption_paused = paused;
+        Ok(())
+    }
+
+    pub fn check_redemption_open(&self) -> Result<()> {
+        require!(!self.redemption_paused, DaoError::RedemptionPaused);
+        Ok(())
+    }
+
+    // Pro-rata share of the treasury for `amount` of `supply` tokens, less the fee
+    pub fn redemption_payout(&self, amount: u64, treasury: u64, supply: u64) -> Result<u64> {
+        require!(amount <= supply, DaoError::InvalidRedeemAmount);
+        let share = (treasury as u128)
+            .checked_mul(amount as u128).ok_or(DaoError::Overflow)?
+            .checked_div(supply as u128).ok_or(DaoError::InvalidRedeemAmount)?;
+        let fee = share * self.redemption_fee as u128 / MAX_BPS as u128;
+        Ok((share - fee) as u64)
+    }
+
+    // The bonus grows linearly with age until it's capped at `max_age`
+    pub fn age_bonus(&self, age: u64) -> u64 {
+        if self.max_age_bonus == 0 {
+            return 0;
+        }
+        self.max_age_bonus as u64 * age.min(self.max_age) / self.max_age
+    }
+
+    // Issuing `amount` more can't take the supply, vesting reserve included, over the cap
+    pub fn check_max_supply(&self, supply: u64, amount: u64) -> Result<()> {
+        let supply = supply.checked_add(amount).ok_or(DaoError::Overflow)?;
+        require!(supply <= self.max_supply, DaoError::MaxSupplyReached);
+        Ok(())
+    }
+
     pub fn check_max_expiry(&self, expiry: u64) -> Result<()> {
         require!(self.max_expiry >= expiry, DaoError::InvalidExpiry);
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::helpers::fixtures::config;
+
+    #[test]
+    fn redemption_pays_pro_rata_share_less_fee() {
+        // A quarter of the supply gets a quarter of the treasury, less 2.5%
+        assert_eq!(config(250, 0, 0).redemption_payout(25, 1_000_000, 100).unwrap(), 243_750);
+        assert_eq!(config(0, 0, 0).redemption_payout(100, 1_000_000, 100).unwrap(), 1_000_000);
+        assert_eq!(config(10_000, 0, 0).redemption_payout(100, 1_000_000, 100).unwrap(), 0);
+    }
+
+    #[test]
+    fn redemption_rounds_share_and_fee_down() {
+        // A third of 1000 is 333, and 30% of that is 99.9
+        assert_eq!(config(3_000, 0, 0).redemption_payout(1, 1_000, 3).unwrap(), 234);
+        // A fee too small to be worth a lamport isn't charged
+        assert_eq!(config(1, 0, 0).redemption_payout(1, 1_000, 1).unwrap(), 1_000);
+    }
+
+    #[test]
+    fn redemption_over_supply_fails() {
+        assert!(config(0, 0, 0).redemption_payout(101, 1_000, 100).is_err());
+        assert!(config(0, 0, 0).redemption_payout(0, 1_000, 0).is_err());
+    }
+
+    #[test]
+    fn age_bonus_grows_linearly_until_max_age() {
+        let config = config(0, 5_000, 100);
+        assert_eq!(config.age_bonus(0), 0);
+        assert_eq!(config.age_bonus(50), 2_500);
+        assert_eq!(config.age_bonus(100), 5_000);
+        assert_eq!(config.age_bonus(1_000), 5_000);
+    }
+
+    #[test]
+    fn age_bonus_rounds_down() {
+        assert_eq!(config(0, 100, 3).age_bonus(1), 33);
+    }
+
+    #[test]
+    fn age_bonus_off_without_max_bonus() {
+        assert_eq!(config(0, 0, 0).age_bonus(1_000), 0);
+    }
+}
@@ -2,7 +2,9 @@ pub mod config;
 pub mod proposal;
 pub mod stake;
 pub mod vote;
+pub mod vesting;
 pub use config::*;
 pub use proposal::*;
 pub use stake::*;
-pub use vote::*;
\ No newline at end of file
+pub use vote::*;
+pub use vesting::*;
\ No newline at end of file
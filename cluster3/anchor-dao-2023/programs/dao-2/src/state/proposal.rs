@@ -1,5 +1,5 @@
 use crate::{constants::*, errors::DaoError};
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, solana_program::instruction::Instruction};
 
 #[account]
 pub struct Proposal {
@@ -8,14 +8,27 @@ pub struct Proposal {
     pub gist: String, // 72 bytes (39 bytes + / + 32 char ID)
     pub proposal: ProposalType,
     pub result: ProposalStatus,
-    pub quorum: u64,
-    pub votes: u64,
+    pub quorum: u64, // Share of the staked supply that has to vote, in percent
+    pub threshold: u64, // Share of the votes cast that has to be yes, in percent. Ties fail
+    pub staked_supply: u64, // Snapshot of the staked supply when the proposal was created
+    pub yes: u64, // Vote weights, stake age bonus included
+    pub no: u64,
     pub expiry: u64,
     pub bump: u8,
+    pub instructions: Vec<InstructionData>, // Replayed by the treasury when an executable proposal passes
+    pub stake_voted: u64, // Stake behind the votes cast, which quorum is measured on
+    pub created: u64, // Slot of the snapshot. Stake deposited from then on can't vote
+    pub max_age_bonus: u16 // Snapshot of the age bonus cap, which bounds what the outstanding stake could weigh
 }
 
 impl Proposal {
-    pub const LEN: usize = 8 + 32 + 72 + ENUM_L * 2 + U8_L * 2 + 3 * U64_L + U8_L;
+    pub const LEN: usize = 8 + U64_L + (4 + 32) + (4 + 72) + (ENUM_L + PUBKEY_L + U64_L) + ENUM_L + 8 * U64_L + U8_L + 4 + U16_L;
+
+    // Room for the stored instructions on top of the fixed fields
+    pub fn space(instructions: &[InstructionData]) -> usize {
+        Self::LEN + instructions.iter().map(|ix| ix.space()).sum::<usize>()
+    }
+
     pub fn init(
         &mut self,
         id: u64,
@@ -23,33 +36,78 @@ impl Proposal {
         gist: String,
         proposal: ProposalType,
         quorum: u64,
+        threshold: u64,
+        staked_supply: u64,
+        max_age_bonus: u16,
         expiry: u64,
+        instructions: Vec<InstructionData>,
         bump: u8  
     ) -> Result<()> {
+        require!(quorum <= MAX_PERCENT, DaoError::InvalidQuorum);
+        // A proposal needs more than `threshold`, so 100% could never pass
+        require!(threshold < MAX_PERCENT, DaoError::InvalidThreshold);
         require!(name.len() < 33, DaoError::InvalidName);
         require!(gist.len() < 73, DaoError::InvalidGist);
+        // Only executable proposals carry instructions, and they need at least one
+        require!((proposal == ProposalType::Executable) != instructions.is_empty(), DaoError::InvalidInstructionData);
+        // A fee over 100% could never be set, so the proposal could never be executed or cleaned up
+        if let ProposalType::Redemption(fee, _) = proposal {
+            require!(fee as u64 <= MAX_BPS, DaoError::InvalidRedemptionFee);
+        }
         self.id = id;
         self.proposal = proposal;
         self.name = name;
         self.gist = gist;
         self.result = ProposalStatus::Open;
         self.quorum = quorum;
-        self.votes = 0;
+        self.threshold = threshold;
+        self.staked_supply = staked_supply;
+        self.yes = 0;
+        self.no = 0;
         self.bump = bump;
-        self.expiry = Clock::get()?.slot.checked_add(expiry).ok_or(DaoError::Overflow)?;
+        self.created = Clock::get()?.slot;
+        self.expiry = self.created.checked_add(expiry).ok_or(DaoError::Overflow)?;
+        self.instructions = instructions;
+        self.stake_voted = 0;
+        self.max_age_bonus = max_age_bonus;
         Ok(())
     }
 
+    // Succeeds early once the yes votes would carry it even if all the other stake voted no
+    // with the full age bonus. Otherwise it's decided at expiry
     pub fn try_finalize(
         &mut self
     ) {
-        if self.votes >= self.quorum && self.check_expiry().is_ok() {
+        if self.result != ProposalStatus::Open {
+            return;
+        }
+        let cast = self.yes as u128 + self.no as u128;
+        if self.check_expiry().is_ok() {
+            if self.quorum_met() && self.approved(cast + self.outstanding_weight()) {
+                self.result = ProposalStatus::Succeeded
+            }
+        } else if self.quorum_met() && self.approved(cast) {
             self.result = ProposalStatus::Succeeded
-        } else if self.votes < self.quorum && self.check_expiry().is_err() {
+        } else {
             self.result = ProposalStatus::Failed
         }
     }
 
+    fn quorum_met(&self) -> bool {
+        self.stake_voted as u128 * MAX_PERCENT as u128 >= self.quorum as u128 * self.staked_supply as u128
+    }
+
+    // The most the snapshotted stake that hasn't voted yet could weigh
+    fn outstanding_weight(&self) -> u128 {
+        let outstanding = self.staked_supply.saturating_sub(self.stake_voted) as u128;
+        outstanding * (MAX_BPS + self.max_age_bonus as u64) as u128 / MAX_BPS as u128
+    }
+
+    // More than `threshold` of `counted` has to be yes
+    fn approved(&self, counted: u128) -> bool {
+        counted > 0 && self.yes as u128 * MAX_PERCENT as u128 > self.threshold as u128 * counted
+    }
+
     pub fn check_expiry(
         &mut self
     ) -> Result<()> {
@@ -78,20 +136,60 @@ impl Proposal {
         Ok(())
     }
 
+    // Split the remaining accounts into each stored instruction's program followed by its
+    // accounts, checking they're the ones stored and in the order they were stored
+    pub fn instruction_accounts<'info>(
+        &self,
+        remaining_accounts: &[AccountInfo<'info>]
+    ) -> Result<Vec<Vec<AccountInfo<'info>>>> {
+        let remaining_accounts = &mut remaining_accounts.iter();
+        let mut instruction_accounts = Vec::with_capacity(self.instructions.len());
+
+        for data in self.instructions.iter() {
+            let program = next_account_info(remaining_accounts)?;
+            require_keys_eq!(program.key(), data.program_id, DaoError::InvalidInstructionData);
+            require!(program.executable, DaoError::InvalidInstructionData);
+
+            let mut ix_accounts = vec![program.clone()];
+            for key in data.keys.iter() {
+                let ix_account = next_account_info(remaining_accounts)?;
+                require_keys_eq!(ix_account.key(), key.pubkey, DaoError::InvalidInstructionData);
+                ix_accounts.push(ix_account.clone());
+            }
+            instruction_accounts.push(ix_accounts);
+        }
+
+        // Every account passed in has to belong to a stored instruction
+        require!(remaining_accounts.next().is_none(), DaoError::InvalidInstructionData);
+        Ok(instruction_accounts)
+    }
+
     pub fn add_vote(
         &mut self,
-        amount: u64
+        amount: u64,
+        weight: u64,
+        approve: bool
     ) -> Result<()> {
-        self.votes = self.votes.checked_add(amount).ok_or(DaoError::Overflow)?;
+        match approve {
+            true => self.yes = self.yes.checked_add(weight).ok_or(DaoError::Overflow)?,
+            false => self.no = self.no.checked_add(weight).ok_or(DaoError::Overflow)?
+        }
+        self.stake_voted = self.stake_voted.checked_add(amount).ok_or(DaoError::Overflow)?;
         self.try_finalize();
         Ok(())
     }
 
     pub fn remove_vote(
         &mut self,
-        amount: u64
+        amount: u64,
+        weight: u64,
+        approve: bool
     ) -> Result<()> {
-        self.votes = self.votes.checked_sub(amount).ok_or(DaoError::Underflow)?;
+        match approve {
+            true => self.yes = self.yes.checked_sub(weight).ok_or(DaoError::Underflow)?,
+            false => self.no = self.no.checked_sub(weight).ok_or(DaoError::Underflow)?
+        }
+        self.stake_voted = self.stake_voted.checked_sub(amount).ok_or(DaoError::Underflow)?;
         Ok(())
     }
 }
@@ -100,7 +198,8 @@ impl Proposal {
 pub enum ProposalType {
     Bounty(Pubkey, u64), // Pay an address some amount of SOL
     Executable, // Sign some kind of instruction(s) with an accounts struct, etc
-    Vote // We just want to know what people think. No money involved
+    Vote, // We just want to know what people think. No money involved
+    Redemption(u16, bool) // Set the redemption fee in bps and whether redemptions are paused
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, Debug, PartialEq, Eq)]
@@ -108,4 +207,169 @@ pub enum ProposalStatus {
     Open,
     Succeeded,
     Failed
-}
\ No newline at end of file
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct InstructionData {
+    pub program_id: Pubkey,
+    pub data: Vec<u8>,
+    pub keys: Vec<InstructionAccount>
+}
+
+impl InstructionData {
+    pub fn space(&self) -> usize {
+        PUBKEY_L + 4 + self.data.len() + 4 + self.keys.len() * InstructionAccount::LEN
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct InstructionAccount {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool
+}
+
+impl InstructionAccount {
+    pub const LEN: usize = PUBKEY_L + 2 * BOOL_L;
+}
+
+impl From<&InstructionData> for Instruction {
+    fn from(instruction: &InstructionData) -> Self {
+        Instruction {
+            program_id: instruction.program_id,
+            accounts: instruction.keys.iter().map(|account| AccountMeta {
+                pubkey: account.pubkey,
+                is_signer: account.is_signer,
+                is_writable: account.is_writable
+            }).collect(),
+            data: instruction.data.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::{clock::set_slot, fixtures::{instruction, open_proposal, proposal, TestAccount}};
+
+    #[test]
+    fn early_finalize_allows_for_outstanding_stake_at_full_bonus() {
+        let mut proposal = open_proposal();
+        // 75 of the weight, but the other 50 staked could still vote no with 100
+        proposal.add_vote(50, 75, true).unwrap();
+        assert_eq!(proposal.result, ProposalStatus::Open);
+        proposal.add_vote(50, 50, false).unwrap();
+        assert_eq!(proposal.result, ProposalStatus::Succeeded);
+    }
+
+    #[test]
+    fn early_finalize_once_no_votes_cant_catch_up() {
+        let mut proposal = open_proposal();
+        // 90 of the stake at full bonus is more than the other 10 could ever weigh
+        proposal.add_vote(90, 180, true).unwrap();
+        assert_eq!(proposal.result, ProposalStatus::Succeeded);
+    }
+
+    #[test]
+    fn quorum_is_measured_on_stake_not_weight() {
+        let mut proposal = Proposal { quorum: 60, ..open_proposal() };
+        // Weighs 80 of the 100 staked, but only 40 of the stake voted
+        proposal.add_vote(40, 80, true).unwrap();
+        assert!(!proposal.quorum_met());
+        set_slot(1_000);
+        proposal.try_finalize();
+        assert_eq!(proposal.result, ProposalStatus::Failed);
+    }
+
+    #[test]
+    fn expired_proposal_is_decided_on_votes_cast() {
+        let mut proposal = open_proposal();
+        proposal.add_vote(20, 30, true).unwrap();
+        proposal.add_vote(20, 20, false).unwrap();
+        assert_eq!(proposal.result, ProposalStatus::Open);
+        set_slot(1_000);
+        proposal.try_finalize();
+        assert_eq!(proposal.result, ProposalStatus::Succeeded);
+    }
+
+    #[test]
+    fn tie_fails() {
+        let mut proposal = open_proposal();
+        proposal.add_vote(20, 20, true).unwrap();
+        proposal.add_vote(20, 20, false).unwrap();
+        assert!(proposal.quorum_met());
+        assert!(!proposal.approved(40));
+        set_slot(1_000);
+        proposal.try_finalize();
+        assert_eq!(proposal.result, ProposalStatus::Failed);
+    }
+
+    #[test]
+    fn removing_a_vote_takes_back_its_stake_and_weight() {
+        let mut proposal = open_proposal();
+        proposal.add_vote(20, 30, true).unwrap();
+        proposal.remove_vote(20, 30, true).unwrap();
+        assert_eq!((proposal.yes, proposal.stake_voted), (0, 0));
+    }
+
+    // Two stored instructions, and the accounts they were stored with in order
+    fn executable() -> (Proposal, Vec<TestAccount>) {
+        let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (a, b, c) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let proposal = Proposal {
+            proposal: ProposalType::Executable,
+            instructions: vec![instruction(first, &[a, b]), instruction(second, &[c])],
+            ..proposal()
+        };
+        let accounts = vec![
+            TestAccount::new(first, true),
+            TestAccount::new(a, false),
+            TestAccount::new(b, false),
+            TestAccount::new(second, true),
+            TestAccount::new(c, false)
+        ];
+        (proposal, accounts)
+    }
+
+    fn instruction_accounts(proposal: &Proposal, accounts: &mut [TestAccount]) -> Result<Vec<Vec<Pubkey>>> {
+        let infos: Vec<AccountInfo> = accounts.iter_mut().map(|account| account.info()).collect();
+        let instruction_accounts = proposal.instruction_accounts(&infos)?;
+        Ok(instruction_accounts.iter().map(|accounts| accounts.iter().map(|account| account.key()).collect()).collect())
+    }
+
+    #[test]
+    fn instruction_accounts_split_by_instruction() {
+        let (proposal, mut accounts) = executable();
+        let keys: Vec<Pubkey> = accounts.iter().map(|account| account.key).collect();
+        let split = instruction_accounts(&proposal, &mut accounts).unwrap();
+        assert_eq!(split, vec![keys[..3].to_vec(), keys[3..].to_vec()]);
+    }
+
+    #[test]
+    fn instruction_accounts_out_of_order_fail() {
+        let (proposal, mut accounts) = executable();
+        accounts.swap(1, 2);
+        assert_eq!(instruction_accounts(&proposal, &mut accounts).unwrap_err(), DaoError::InvalidInstructionData.into());
+    }
+
+    #[test]
+    fn instruction_accounts_with_program_that_isnt_executable_fail() {
+        let (proposal, mut accounts) = executable();
+        accounts[3].executable = false;
+        assert_eq!(instruction_accounts(&proposal, &mut accounts).unwrap_err(), DaoError::InvalidInstructionData.into());
+    }
+
+    #[test]
+    fn instruction_accounts_missing_or_left_over_fail() {
+        let (proposal, mut accounts) = executable();
+        assert!(instruction_accounts(&proposal, &mut accounts[..4]).is_err());
+        accounts.push(TestAccount::new(Pubkey::new_unique(), false));
+        assert_eq!(instruction_accounts(&proposal, &mut accounts).unwrap_err(), DaoError::InvalidInstructionData.into());
+    }
+
+    #[test]
+    fn redemption_proposal_over_max_fee_fails() {
+        let result = proposal().init(1, String::new(), String::new(), ProposalType::Redemption(10_001, false), 50, 50, 100, 0, 10, vec![], 0);
+        assert_eq!(result.unwrap_err(), DaoError::InvalidRedemptionFee.into());
+    }
+}
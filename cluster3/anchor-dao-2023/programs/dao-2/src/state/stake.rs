@@ -1,6 +1,8 @@
 use crate::{constants::*, errors::DaoError};
 use anchor_lang::prelude::*;
 
+use super::DaoConfig;
+
 #[account]
 pub struct StakeState {
     pub owner: Pubkey,
@@ -10,10 +12,13 @@ pub struct StakeState {
     pub vault_bump: u8,
     pub auth_bump: u8,
     pub state_bump: u8,
+    pub staked_at: u64, // Average slot the staked tokens were deposited at, weighted by amount
+    pub deposits: [u64; 2], // Stake deposited in the last two slots anything was deposited in, newest last
+    pub deposit_slots: [u64; 2], // Proposals created in or before these slots can't count those deposits
 }
 
 impl StakeState {
-    pub const LEN: usize = 8 + PUBKEY_L + 3 * U64_L + 3 * U8_L;
+    pub const LEN: usize = 8 + PUBKEY_L + 8 * U64_L + 3 * U8_L;
 
     pub fn init(
         &mut self,  
@@ -25,30 +30,73 @@ impl StakeState {
         self.owner = owner;
         self.amount = 0;
         self.accounts = 0;
+        self.deposits = [0; 2];
+        self.deposit_slots = [0; 2];
         self.state_bump = state_bump;
         self.vault_bump = vault_bump;
         self.auth_bump = auth_bump;
+        self.staked_at = Clock::get()?.slot;
         self.update()
     }
 
     pub fn stake(
         &mut self,
-        amount: u64
+        amount: u64,
+        max_expiry: u64
     ) -> Result<()> {
-        self.amount.checked_add(amount).ok_or(DaoError::Overflow)?;
+        // New tokens start with no age and pull the average towards now
+        let slot = Clock::get()?.slot;
+        let total = self.amount.checked_add(amount).ok_or(DaoError::Overflow)?;
+        if total > 0 {
+            let weighted = self.staked_at as u128 * self.amount as u128 + slot as u128 * amount as u128;
+            self.staked_at = (weighted / total as u128) as u64;
+        }
+        self.amount = total;
+        self.add_deposit(amount, max_expiry)?;
         self.update()
     }
 
+    fn add_deposit(&mut self, amount: u64, max_expiry: u64) -> Result<()> {
+        let slot = Clock::get()?.slot;
+        // Every proposal created by a deposit's slot has expired once `max_expiry` has passed
+        for (deposit, deposit_slot) in self.deposits.iter_mut().zip(self.deposit_slots) {
+            if deposit_slot.saturating_add(max_expiry) <= slot {
+                *deposit = 0;
+            }
+        }
+        if self.deposit_slots[1] != slot {
+            // Only two slots are kept apart, so the older deposit waits as long as the newer one
+            self.deposits = [self.deposits[0].checked_add(self.deposits[1]).ok_or(DaoError::Overflow)?, 0];
+            self.deposit_slots = [self.deposit_slots[1], slot];
+        }
+        self.deposits[1] = self.deposits[1].checked_add(amount).ok_or(DaoError::Overflow)?;
+        Ok(())
+    }
+
     pub fn unstake(
         &mut self,
-        amount: u64
+        mut amount: u64
     ) -> Result<()> {
         self.check_accounts()?;
         self.check_slot()?; // Don't allow staking and unstaking in the same slot
         self.amount = self.amount.checked_sub(amount).ok_or(DaoError::Underflow)?;
+        // The newest deposits go first
+        for deposit in self.deposits.iter_mut().rev() {
+            let removed = amount.min(*deposit);
+            *deposit -= removed;
+            amount -= removed;
+        }
         self.update()
     }
 
+    // Votes `amount` tokens are worth once their age bonus is added
+    pub fn weight(&self, amount: u64, config: &DaoConfig) -> Result<u64> {
+        let age = Clock::get()?.slot.saturating_sub(self.staked_at);
+        let bonus = config.age_bonus(age) as u128;
+        let weight = amount as u128 * (MAX_BPS as u128 + bonus) / MAX_BPS as u128;
+        u64::try_from(weight).map_err(|_| DaoError::Overflow.into())
+    }
+
     pub fn add_account(&mut self) -> Result<()> {
         self.accounts = self.accounts.checked_add(1).ok_or(DaoError::Overflow)?;
         Ok(())
@@ -92,4 +140,87 @@ impl StakeState {
         require!(self.amount >= amount, DaoError::InsufficientStake);
         Ok(())
     }
-}
\ No newline at end of file
+
+    // Stake deposited in or after the snapshot slot isn't part of the staked supply it's measured against
+    pub fn check_votable_amount(&self, amount: u64, snapshot: u64) -> Result<()> {
+        let votable = self.deposits.iter().zip(self.deposit_slots)
+            .filter(|(_, slot)| *slot >= snapshot)
+            .fold(self.amount, |votable, (deposit, _)| votable.saturating_sub(*deposit));
+        require!(votable >= amount, DaoError::StakeAfterSnapshot);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::{clock::set_slot, fixtures::{config, stake_state}};
+
+    #[test]
+    fn weight_adds_age_bonus() {
+        let config = config(0, 5_000, 100);
+        set_slot(100);
+        assert_eq!(stake_state(100, 100).weight(100, &config).unwrap(), 100);
+        assert_eq!(stake_state(100, 50).weight(100, &config).unwrap(), 125);
+        assert_eq!(stake_state(100, 0).weight(100, &config).unwrap(), 150);
+        // A partial vote gets the same bonus
+        assert_eq!(stake_state(100, 0).weight(10, &config).unwrap(), 15);
+    }
+
+    #[test]
+    fn weight_without_age_bonus_is_amount() {
+        set_slot(1_000);
+        assert_eq!(stake_state(100, 0).weight(100, &config(0, 0, 0)).unwrap(), 100);
+    }
+
+    #[test]
+    fn stake_averages_deposit_slots() {
+        let mut stake = stake_state(100, 0);
+        set_slot(100);
+        stake.stake(100, u64::MAX).unwrap();
+        assert_eq!((stake.amount, stake.staked_at), (200, 50));
+        // Half aged, so half of the full bonus
+        assert_eq!(stake.weight(200, &config(0, 10_000, 100)).unwrap(), 300);
+    }
+
+    // 40 deposited in slot 3 and 60 in slot 6
+    fn topped_up(max_expiry: u64) -> StakeState {
+        let mut stake = stake_state(0, 0);
+        set_slot(3);
+        stake.stake(40, max_expiry).unwrap();
+        set_slot(6);
+        stake.stake(60, max_expiry).unwrap();
+        stake
+    }
+
+    #[test]
+    fn only_stake_deposited_after_the_snapshot_is_left_out() {
+        let stake = topped_up(u64::MAX);
+        assert!(stake.check_votable_amount(40, 5).is_ok());
+        assert_eq!(stake.check_votable_amount(41, 5).unwrap_err(), DaoError::StakeAfterSnapshot.into());
+        // Deposited in the snapshot slot itself
+        assert_eq!(stake.check_votable_amount(1, 3).unwrap_err(), DaoError::StakeAfterSnapshot.into());
+        assert!(stake.check_votable_amount(100, 7).is_ok());
+    }
+
+    #[test]
+    fn deposits_settle_once_older_proposals_have_expired() {
+        let mut stake = topped_up(10);
+        // Proposals created by slot 3 have expired by 13, so that deposit counts everywhere now
+        set_slot(13);
+        stake.stake(10, 10).unwrap();
+        assert_eq!(stake.deposits, [60, 10]);
+        assert!(stake.check_votable_amount(40, 5).is_ok());
+        assert_eq!(stake.check_votable_amount(41, 5).unwrap_err(), DaoError::StakeAfterSnapshot.into());
+        assert!(stake.check_votable_amount(100, 7).is_ok());
+    }
+
+    #[test]
+    fn unstaking_takes_the_newest_deposits_first() {
+        let mut stake = topped_up(u64::MAX);
+        set_slot(7);
+        stake.unstake(70).unwrap();
+        assert_eq!(stake.deposits, [30, 0]);
+        assert!(stake.check_votable_amount(30, 5).is_ok());
+    }
+}
@@ -0,0 +1,141 @@
+use crate::{constants::*, errors::DaoError};
+use anchor_lang::prelude::*;
+
+// Founder and contributor allocations, reserved at initialize and vested from then on
+#[account]
+pub struct Vesting {
+    pub start: u64, // Slot the cliffs and durations count from
+    pub schedules: Vec<VestingSchedule>,
+    pub bump: u8,
+    pub vault_bump: u8
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct VestingSchedule {
+    pub beneficiary: Pubkey,
+    pub amount: u64,
+    pub cliff: u64, // Nothing vests before this many slots
+    pub duration: u64, // Fully vested after this many slots, linearly from the start
+    pub claimed: u64
+}
+
+impl VestingSchedule {
+    pub const LEN: usize = PUBKEY_L + 4 * U64_L;
+
+    pub fn vested(&self, elapsed: u64) -> u64 {
+        if elapsed < self.cliff {
+            0
+        } else if elapsed >= self.duration {
+            self.amount
+        } else {
+            (self.amount as u128 * elapsed as u128 / self.duration as u128) as u64
+        }
+    }
+}
+
+impl Vesting {
+    pub fn space(schedules: usize) -> usize {
+        8 + U64_L + 4 + schedules * VestingSchedule::LEN + 2 * U8_L
+    }
+
+    // Returns the total reserved, which gets minted into the vault
+    pub fn init(
+        &mut self,
+        schedules: Vec<VestingSchedule>,
+        bump: u8,
+        vault_bump: u8
+    ) -> Result<u64> {
+        let mut reserved: u64 = 0;
+        for schedule in schedules.iter() {
+            require!(schedule.amount > 0 && schedule.cliff <= schedule.duration, DaoError::InvalidVestingSchedule);
+            reserved = reserved.checked_add(schedule.amount).ok_or(DaoError::Overflow)?;
+        }
+        self.start = Clock::get()?.slot;
+        self.schedules = schedules.into_iter().map(|schedule| VestingSchedule { claimed: 0, ..schedule }).collect();
+        self.bump = bump;
+        self.vault_bump = vault_bump;
+        Ok(reserved)
+    }
+
+    // Mark everything vested for `beneficiary` as claimed and return how much that is
+    pub fn claim(
+        &mut self,
+        beneficiary: Pubkey
+    ) -> Result<u64> {
+        let elapsed = Clock::get()?.slot.saturating_sub(self.start);
+        let mut amount: u64 = 0;
+        for schedule in self.schedules.iter_mut().filter(|schedule| schedule.beneficiary == beneficiary) {
+            let claimable = schedule.vested(elapsed).checked_sub(schedule.claimed).ok_or(DaoError::Underflow)?;
+            schedule.claimed = schedule.claimed.checked_add(claimable).ok_or(DaoError::Overflow)?;
+            amount = amount.checked_add(claimable).ok_or(DaoError::Overflow)?;
+        }
+        require!(amount > 0, DaoError::NothingToClaim);
+        Ok(amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::{clock::set_slot, fixtures::schedule};
+
+    #[test]
+    fn nothing_vests_before_the_cliff() {
+        let schedule = schedule(Pubkey::default(), 1_000, 100, 400);
+        assert_eq!(schedule.vested(0), 0);
+        assert_eq!(schedule.vested(99), 0);
+        // Vesting counts from the start, so the cliff releases everything vested so far
+        assert_eq!(schedule.vested(100), 250);
+    }
+
+    #[test]
+    fn fully_vested_at_the_duration() {
+        let schedule = schedule(Pubkey::default(), 1_000, 100, 400);
+        assert_eq!(schedule.vested(399), 997);
+        assert_eq!(schedule.vested(400), 1_000);
+        assert_eq!(schedule.vested(u64::MAX), 1_000);
+    }
+
+    #[test]
+    fn no_cliff_or_duration_vests_at_once() {
+        assert_eq!(schedule(Pubkey::default(), 1_000, 0, 0).vested(0), 1_000);
+        assert_eq!(schedule(Pubkey::default(), 1_000, 400, 400).vested(399), 0);
+        assert_eq!(schedule(Pubkey::default(), 1_000, 400, 400).vested(400), 1_000);
+    }
+
+    #[test]
+    fn claim_takes_what_vested_since_the_last_claim() {
+        let (founder, contributor) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut vesting = Vesting {
+            start: 1_000,
+            schedules: vec![
+                schedule(founder, 1_000, 100, 400),
+                schedule(contributor, 500, 0, 100),
+                schedule(founder, 100, 0, 0)
+            ],
+            bump: 0,
+            vault_bump: 0
+        };
+
+        // Only the founder's schedule without a cliff has vested
+        set_slot(1_099);
+        assert_eq!(vesting.claim(founder).unwrap(), 100);
+        assert_eq!(vesting.claim(founder).unwrap_err(), DaoError::NothingToClaim.into());
+
+        set_slot(1_200);
+        assert_eq!(vesting.claim(founder).unwrap(), 500);
+        set_slot(1_400);
+        assert_eq!(vesting.claim(founder).unwrap(), 500);
+        set_slot(2_000);
+        assert_eq!(vesting.claim(founder).unwrap_err(), DaoError::NothingToClaim.into());
+        assert_eq!(vesting.claim(contributor).unwrap(), 500);
+        assert!(vesting.schedules.iter().all(|schedule| schedule.claimed == schedule.amount));
+    }
+
+    #[test]
+    fn claim_without_a_schedule_fails() {
+        let mut vesting = Vesting { start: 0, schedules: vec![schedule(Pubkey::new_unique(), 100, 0, 0)], bump: 0, vault_bump: 0 };
+        set_slot(10);
+        assert_eq!(vesting.claim(Pubkey::new_unique()).unwrap_err(), DaoError::NothingToClaim.into());
+    }
+}
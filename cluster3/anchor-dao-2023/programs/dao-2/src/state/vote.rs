@@ -4,22 +4,28 @@ use anchor_lang::prelude::*;
 #[account]
 pub struct VoteState {
     pub owner: Pubkey,
-    pub amount: u64,
-    pub bump: u8
+    pub amount: u64, // Vote weight, stake age bonus included
+    pub approve: bool,
+    pub bump: u8,
+    pub stake: u64 // Stake behind the vote, which counts toward quorum
 }
 
 impl VoteState {
-    pub const LEN: usize = 8 + PUBKEY_L + U64_L + U8_L;
+    pub const LEN: usize = 8 + PUBKEY_L + 2 * U64_L + BOOL_L + U8_L;
 
     pub fn init(
         &mut self,
         owner: Pubkey,
         amount: u64,
+        stake: u64,
+        approve: bool,
         bump: u8,
     ) -> Result<()> {
         self.owner = owner;
         self.amount = amount;
+        self.approve = approve;
         self.bump = bump;
+        self.stake = stake;
         Ok(())
     }
 }
\ No newline at end of file